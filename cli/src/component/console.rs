@@ -0,0 +1,116 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use crossterm::event::{KeyCode, KeyModifiers};
+use ratatui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+
+use crate::{
+    component::{elements::block_with_title, AppState, Component, ComponentEvent, Input},
+    state::focus,
+};
+
+/// A full screen terminal of a container. Lines are edited locally and sent on `Enter`,
+/// `Esc` detaches from the container and returns to the dashboard.
+pub struct ConsoleView {
+    input: String,
+}
+
+impl ConsoleView {
+    pub fn new() -> Self {
+        Self { input: String::new() }
+    }
+}
+
+impl Input for ConsoleView {
+    type Output = ();
+
+    fn on_event(&mut self, event: ComponentEvent, state: &mut AppState) -> Option<Self::Output> {
+        if let ComponentEvent::KeyEvent(key) = event {
+            let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+            match key.code {
+                KeyCode::Esc => {
+                    self.input.clear();
+                    state.detach_console();
+                    state.focus_on(focus::BASE_NODE);
+                },
+                KeyCode::Enter => {
+                    state.console_input(&self.input);
+                    self.input.clear();
+                },
+                KeyCode::Backspace => {
+                    self.input.pop();
+                },
+                // Control keys are not passed to the container to not interrupt the process
+                KeyCode::Char(c) if !ctrl => {
+                    self.input.push(c);
+                },
+                _ => {},
+            }
+        }
+        None
+    }
+}
+
+impl<B: Backend> Component<B> for ConsoleView {
+    type State = AppState;
+
+    fn draw(&self, f: &mut Frame<B>, rect: Rect, state: &Self::State) {
+        let console = &state.console;
+        let task = console.task_id.as_ref().map(|task_id| task_id.as_ref()).unwrap_or("-");
+        let title = format!("{} Console [Esc to detach]", task);
+        let block = block_with_title(Some(&title), console.attached);
+        let inner_rect = block.inner(rect);
+        f.render_widget(block, rect);
+        let constraints = [Constraint::Min(0), Constraint::Length(1)];
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(constraints)
+            .split(inner_rect);
+
+        let mut lines: Vec<Line> = console.lines().map(Line::from).collect();
+        if let Some(error) = &console.error {
+            lines.push(Line::styled(error.as_str(), Style::default().fg(Color::Red)));
+        } else if !console.attached {
+            lines.push(Line::styled("Not attached", Style::default().fg(Color::Yellow)));
+        } else {
+            //
+        }
+        // Follows the end of the output
+        let scroll = lines.len().saturating_sub(chunks[0].height as usize) as u16;
+        let output = Paragraph::new(lines).scroll((scroll, 0));
+        f.render_widget(output, chunks[0]);
+
+        let prompt = Line::from(vec![
+            Span::styled("> ", Style::default().fg(Color::Yellow)),
+            Span::raw(self.input.as_str()),
+        ]);
+        f.render_widget(Paragraph::new(prompt), chunks[1]);
+    }
+}
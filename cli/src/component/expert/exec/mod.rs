@@ -0,0 +1,214 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use ratatui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+};
+use tari_launchpad_protocol::container::{ExecOutput, TaskId};
+
+use crate::{
+    component::{
+        elements::block_with_title,
+        widgets::{LabeledInput, Separator},
+        Component, ComponentEvent, Frame, Input, Pass,
+    },
+    focus_id,
+    state::{
+        focus::{self, Focus},
+        AppState,
+    },
+};
+
+pub static EXEC_PANEL: Focus = focus_id!();
+static EXEC_COMMAND: Focus = focus_id!();
+
+/// Runs diagnostic commands inside of the running containers.
+pub struct ExecScene {
+    container: Option<TaskId>,
+    command: LabeledInput,
+    output_sep: Separator,
+}
+
+impl ExecScene {
+    pub fn new() -> Self {
+        Self {
+            container: None,
+            command: LabeledInput::new("Command", EXEC_COMMAND),
+            output_sep: Separator::new("Output", []),
+        }
+    }
+
+    fn select(&mut self, state: &AppState, shift: isize) {
+        let containers = started_containers(state);
+        if containers.is_empty() {
+            self.container = None;
+            return;
+        }
+        let len = containers.len() as isize;
+        let next = match containers
+            .iter()
+            .position(|task_id| Some(*task_id) == self.container.as_ref())
+        {
+            Some(idx) => (idx as isize + shift).rem_euclid(len) as usize,
+            None => 0,
+        };
+        self.container = Some(containers[next].clone());
+    }
+
+    /// Arguments are split by whitespace, the command is not interpreted by a shell.
+    fn check_for_command(&mut self, state: &mut AppState) {
+        if let Some(command) = self.command.fetch_new_value() {
+            let command: Vec<String> = command.split_whitespace().map(String::from).collect();
+            if let (Some(task_id), false) = (self.container.clone(), command.is_empty()) {
+                state.exec(task_id, command);
+            }
+        }
+    }
+}
+
+impl Input for ExecScene {
+    type Output = ();
+
+    fn on_event(&mut self, event: ComponentEvent, state: &mut AppState) -> Option<Self::Output> {
+        if let ComponentEvent::StateChanged = event {
+            // Keeps the selection if the container is still running
+            self.select(state, 0);
+            return None;
+        }
+
+        if state.focus_on == EXEC_PANEL {
+            match event.pass() {
+                Pass::Up | Pass::Leave => {
+                    state.focus_on(focus::ROOT);
+                },
+                Pass::Left => {
+                    self.select(state, -1);
+                },
+                Pass::Right => {
+                    self.select(state, 1);
+                },
+                Pass::Down | Pass::Enter => {
+                    state.focus_on(EXEC_COMMAND);
+                },
+                _ => {},
+            }
+        } else if state.focus_on == EXEC_COMMAND {
+            let released = self.command.is_released();
+            match event.pass() {
+                Pass::Up | Pass::Leave if released => {
+                    state.focus_on(EXEC_PANEL);
+                },
+                _ => {
+                    self.command.on_event(event, state);
+                },
+            }
+        } else {
+            //
+        }
+        self.check_for_command(state);
+        None
+    }
+}
+
+impl<B: Backend> Component<B> for ExecScene {
+    type State = AppState;
+
+    fn draw(&self, f: &mut Frame<B>, rect: Rect, state: &Self::State) {
+        let block = block_with_title(Some("Exec"), state.focus_on == EXEC_PANEL);
+        let inner_rect = block.inner(rect);
+        f.render_widget(block, rect);
+        let constraints = [
+            Constraint::Length(1),
+            Constraint::Length(3),
+            // Output
+            Constraint::Length(1),
+            Constraint::Min(0),
+        ];
+        let chunks = Layout::default()
+            .vertical_margin(1)
+            .horizontal_margin(3)
+            .direction(Direction::Vertical)
+            .constraints(constraints)
+            .split(inner_rect);
+
+        let container = self
+            .container
+            .as_ref()
+            .map(|task_id| task_id.as_ref())
+            .unwrap_or("no running containers");
+        let line = Line::from(vec![
+            Span::raw("Container: "),
+            Span::styled(format!("< {} >", container), Style::default().fg(Color::Yellow)),
+        ]);
+        f.render_widget(Paragraph::new(line), chunks[0]);
+        self.command.draw(f, chunks[1], state);
+
+        self.output_sep.draw(f, chunks[2], state);
+        let lines = output_lines(state);
+        // Follows the end of the output
+        let scroll = lines.len().saturating_sub(chunks[3].height as usize) as u16;
+        let output = Paragraph::new(lines).scroll((scroll, 0));
+        f.render_widget(output, chunks[3]);
+    }
+}
+
+fn started_containers(state: &AppState) -> Vec<&TaskId> {
+    let mut containers: Vec<_> = state
+        .state
+        .containers
+        .iter()
+        .filter(|(_, task_state)| task_state.status.is_started())
+        .map(|(task_id, _)| task_id)
+        .collect();
+    containers.sort();
+    containers
+}
+
+fn output_lines(state: &AppState) -> Vec<Line<'_>> {
+    let mut lines = Vec::new();
+    for output in state.exec_output.iter() {
+        match output {
+            ExecOutput::Stdout(text) => {
+                lines.extend(text.lines().map(Line::from));
+            },
+            ExecOutput::Stderr(text) => {
+                let style = Style::default().fg(Color::Red);
+                lines.extend(text.lines().map(|line| Line::styled(line, style)));
+            },
+            ExecOutput::Exited(code) => {
+                let color = if *code == 0 { Color::Green } else { Color::Yellow };
+                let line = Line::styled(format!("Exited with code {}", code), Style::default().fg(color));
+                lines.push(line);
+            },
+            ExecOutput::Error(reason) => {
+                let line = Line::styled(format!("Can't execute: {}", reason), Style::default().fg(Color::Red));
+                lines.push(line);
+            },
+        }
+    }
+    lines
+}
@@ -22,10 +22,14 @@
 //
 
 mod errors;
+mod exec;
 pub mod logs;
+mod performance;
 
 use errors::ErrorsScene;
+use exec::ExecScene;
 use logs::LogsScene;
+use performance::PerformanceScene;
 use ratatui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Rect},
@@ -45,26 +49,34 @@ pub enum ExpertTabs {
     Performance,
     Logs,
     Errors,
+    Exec,
 }
 
 impl TabGetter for ExpertTabs {
     fn focus_to(&self, _: &AppState) -> Focus {
-        focus::ROOT
+        match self {
+            Self::Exec => exec::EXEC_PANEL,
+            _ => focus::ROOT,
+        }
     }
 }
 
 pub struct ExpertScene {
     expert_tabs: AppTabs<ExpertTabs>,
+    performance_scene: PerformanceScene,
     logs_scene: LogsScene,
     errors_scene: ErrorsScene,
+    exec_scene: ExecScene,
 }
 
 impl ExpertScene {
     pub fn new() -> Self {
         Self {
             expert_tabs: AppTabs::new(),
+            performance_scene: PerformanceScene::new(),
             logs_scene: LogsScene::new(),
             errors_scene: ErrorsScene::new(),
+            exec_scene: ExecScene::new(),
         }
     }
 }
@@ -75,13 +87,18 @@ impl Input for ExpertScene {
     fn on_event(&mut self, event: ComponentEvent, state: &mut AppState) -> Option<Self::Output> {
         self.expert_tabs.on_event(event, state);
         match self.expert_tabs.selected() {
-            ExpertTabs::Performance => {},
+            ExpertTabs::Performance => {
+                self.performance_scene.on_event(event, state);
+            },
             ExpertTabs::Logs => {
                 self.logs_scene.on_event(event, state);
             },
             ExpertTabs::Errors => {
                 self.errors_scene.on_event(event, state);
             },
+            ExpertTabs::Exec => {
+                self.exec_scene.on_event(event, state);
+            },
         }
         None
     }
@@ -98,13 +115,18 @@ impl<B: Backend> Component<B> for ExpertScene {
             .split(rect);
         self.expert_tabs.draw(f, chunks[0], state);
         match self.expert_tabs.selected() {
-            ExpertTabs::Performance => {},
+            ExpertTabs::Performance => {
+                self.performance_scene.draw(f, chunks[1], state);
+            },
             ExpertTabs::Logs => {
                 self.logs_scene.draw(f, chunks[1], state);
             },
             ExpertTabs::Errors => {
                 self.errors_scene.draw(f, chunks[1], state);
             },
+            ExpertTabs::Exec => {
+                self.exec_scene.draw(f, chunks[1], state);
+            },
         }
     }
 }
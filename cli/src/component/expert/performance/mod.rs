@@ -0,0 +1,130 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use std::borrow::Cow;
+
+use byte_unit::{Byte, UnitType};
+use ratatui::{
+    backend::Backend,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style},
+    widgets::{Row, Table},
+};
+
+use crate::component::{elements::block_with_title, AppState, Component, ComponentEvent, Frame, Input};
+
+pub struct PerformanceScene {}
+
+impl PerformanceScene {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Input for PerformanceScene {
+    type Output = ();
+
+    fn on_event(&mut self, _event: ComponentEvent, _state: &mut AppState) -> Option<Self::Output> {
+        None
+    }
+}
+
+impl<B: Backend> Component<B> for PerformanceScene {
+    type State = AppState;
+
+    fn draw(&self, f: &mut Frame<B>, rect: Rect, state: &Self::State) {
+        let block = block_with_title(Some("Performance"), false);
+        let rects = Layout::default()
+            .constraints([Constraint::Percentage(100)].as_ref())
+            .split(rect);
+        let mut containers: Vec<_> = state
+            .state
+            .containers
+            .iter()
+            .filter(|(_, task_state)| task_state.status.is_started())
+            .collect();
+        containers.sort_by(|l, r| l.0.cmp(r.0));
+        let mut rows = Vec::new();
+        for (task_id, task_state) in containers {
+            let stats = &task_state.stats;
+            let Some(last) = stats.last() else {
+                continue;
+            };
+            let cpu = stats
+                .last_cpu()
+                .map(|cpu| format!("{:.2} %", cpu))
+                .unwrap_or_else(|| "-".to_string());
+            let mem = format!("{} / {}", bytes(last.mem_usage), bytes(last.mem_limit));
+            let net = stats
+                .last_net_rates()
+                .map(|(rx, tx)| format!("{} / {}", rate(rx), rate(tx)))
+                .unwrap_or_else(|| "-".to_string());
+            let block_io = stats
+                .last_block_rates()
+                .map(|(read, write)| format!("{} / {}", rate(read), rate(write)))
+                .unwrap_or_else(|| "-".to_string());
+            let items = vec![
+                Cow::Borrowed(task_id.as_ref()),
+                Cow::Owned(cpu),
+                Cow::Owned(mem),
+                Cow::Owned(net),
+                Cow::Owned(block_io),
+                Cow::Owned(last.pids.to_string()),
+            ];
+            rows.push(Row::new(items).height(2));
+        }
+        let header_cells = [
+            "Container",
+            "CPU",
+            "Memory",
+            "Net In / Out",
+            "Block Read / Write",
+            "PIDs",
+        ];
+        let header = Row::new(header_cells)
+            .style(Style::default().fg(Color::Yellow))
+            .height(1)
+            .bottom_margin(1);
+        let table = Table::new(rows)
+            .block(block)
+            .header(header)
+            .widths(&[
+                Constraint::Percentage(16),
+                Constraint::Percentage(10),
+                Constraint::Percentage(20),
+                Constraint::Percentage(22),
+                Constraint::Percentage(22),
+                Constraint::Percentage(10),
+            ])
+            .column_spacing(2);
+        f.render_widget(table, rects[0]);
+    }
+}
+
+fn bytes(value: Byte) -> String {
+    value.get_appropriate_unit(UnitType::Decimal).to_string()
+}
+
+fn rate(value: f64) -> String {
+    format!("{}/s", bytes(Byte::from_u64(value as u64)))
+}
@@ -30,6 +30,7 @@ use ratatui::{
 
 use crate::{
     component::{
+        console::ConsoleView,
         expert::{logs::LogsScene, ExpertScene},
         header::{mode::Mode, Header},
         normal::NormalScene,
@@ -46,6 +47,7 @@ pub struct MainView {
     logs_scene: LogsScene,
     expert_scene: ExpertScene,
     settings_scene: SettingsScene,
+    console_view: ConsoleView,
 }
 
 impl MainView {
@@ -56,6 +58,7 @@ impl MainView {
             logs_scene: LogsScene::new(),
             expert_scene: ExpertScene::new(),
             settings_scene: SettingsScene::new(),
+            console_view: ConsoleView::new(),
         }
     }
 }
@@ -65,11 +68,8 @@ impl Input for MainView {
 
     fn on_event(&mut self, event: ComponentEvent, state: &mut AppState) -> Option<Self::Output> {
         if event.pass() == Pass::Quit {
-            let session = &mut state.state.config.session;
-            session.stop_all();
             state.terminate();
             state.focus_on(focus::TERMINATION);
-            state.update_state();
 
             // Spawn a new thread to exit the process as a failsafe if the process does not close normally
             if is_docker_running() {
@@ -86,6 +86,8 @@ impl Input for MainView {
         } else if matches!(event, ComponentEvent::StateChanged) {
             self.normal_scene.on_event(event, state);
             self.settings_scene.on_event(event, state);
+        } else if state.focus_on == focus::CONSOLE {
+            self.console_view.on_event(event, state);
         } else {
             self.header.on_event(event, state);
             match self.header.mode_selector.selected() {
@@ -111,6 +113,10 @@ impl<B: Backend> Component<B> for MainView {
     type State = AppState;
 
     fn draw(&self, f: &mut Frame<B>, rect: Rect, state: &Self::State) {
+        if state.focus_on == focus::CONSOLE {
+            self.console_view.draw(f, rect, state);
+            return;
+        }
         let constraints = [Constraint::Length(1), Constraint::Min(0)];
         let chunks = Layout::default()
             .direction(Direction::Vertical)
@@ -1,7 +1,10 @@
 // Copyright 2023. The Tari Project
 // SPDX-License-Identifier: BSD-3-Clause
 
-use crossterm::event::{KeyCode, KeyModifiers};
+use crossterm::{
+    event::{KeyCode, KeyModifiers},
+    terminal,
+};
 use ratatui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Rect},
@@ -9,6 +12,8 @@ use ratatui::{
     widgets::Padding,
 };
 use tari_common_types::types::PublicKey;
+use tari_sdm::ids::ManagedTask;
+use tari_sdm_launchpad::resources::images::TariBaseNode;
 use tari_utilities::ByteArray;
 
 use crate::{
@@ -19,9 +24,12 @@ use crate::{
         ComponentEvent::KeyEvent,
         Frame, Input,
     },
-    state::AppState,
+    state::{focus, AppState},
 };
 
+/// The size of the terminal if it can't be detected.
+const DEFAULT_SIZE: (u16, u16) = (80, 24);
+
 #[derive(Default)]
 struct BaseNodeStatus {}
 
@@ -73,6 +81,23 @@ impl BaseNodeWidget {
         session.base_layer_active = !session.base_layer_active;
         state.update_state();
     }
+
+    /// Attaches to the terminal of the running node and shows it on the whole screen.
+    pub fn open_console(state: &mut AppState) {
+        let task_id = TariBaseNode::id();
+        let running = state
+            .state
+            .containers
+            .get(&task_id)
+            .map(|task_state| task_state.status.is_started())
+            .unwrap_or_default();
+        if running {
+            let (width, height) = terminal::size().unwrap_or(DEFAULT_SIZE);
+            // Without the borders of the console and the input line
+            state.attach_console(task_id, width.saturating_sub(2), height.saturating_sub(3));
+            state.focus_on(focus::CONSOLE);
+        }
+    }
 }
 
 impl Input for BaseNodeWidget {
@@ -84,6 +109,10 @@ impl Input for BaseNodeWidget {
                 Self::toggle_base_node(state);
                 return Some(());
             }
+            if key.code == KeyCode::Char('o') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                Self::open_console(state);
+                return Some(());
+            }
         }
         None
     }
@@ -23,15 +23,16 @@
 
 use std::{borrow::Cow, cell::RefCell};
 
-use byte_unit::UnitType;
+use byte_unit::{Byte, UnitType};
 use ratatui::{
     backend::Backend,
     layout::{Constraint, Layout, Rect},
     style::{Color, Style},
     widgets::{Row, Table, TableState},
 };
+use tari_launchpad_protocol::container::ImageRecord;
 use tari_sdm::ids::{ManagedTask, TaskId};
-use tari_sdm_launchpad::resources::images;
+use tari_sdm_launchpad::resources::{images, volumes};
 
 use crate::{
     component::{elements::block_with_title, AppState, Component, ComponentEvent, Frame, Input, Pass},
@@ -93,12 +94,17 @@ impl<B: Backend> Component<B> for ContainersScene {
                         col_4 = Cow::Owned(task_state.status.to_string());
                     }
                 }
-                let items = vec![col_1, col_2, col_3, col_4];
+                let col_5 = Cow::Owned(task_state.image.as_ref().map(image_digest).unwrap_or_default());
+                let col_6 = task_state
+                    .disk_usage
+                    .map(|size| Cow::Owned(Byte::from_u64(size).get_appropriate_unit(UnitType::Decimal).to_string()))
+                    .unwrap_or(Cow::Borrowed("-"));
+                let items = vec![col_1, col_2, col_3, col_6, col_5, col_4];
                 let row = Row::new(items).height(2);
                 rows.push(row);
             }
         }
-        let header_cells = ["Container", "CPU", "Memory", "Status"];
+        let header_cells = ["Container", "CPU", "Memory", "Disk", "Image", "Status"];
         let header = Row::new(header_cells)
             .style(Style::default().fg(Color::Yellow))
             .height(1)
@@ -110,13 +116,27 @@ impl<B: Backend> Component<B> for ContainersScene {
                 Constraint::Percentage(20),
                 Constraint::Percentage(10),
                 Constraint::Percentage(10),
-                Constraint::Percentage(60),
+                Constraint::Percentage(10),
+                Constraint::Percentage(20),
+                Constraint::Percentage(30),
             ])
             .column_spacing(2);
         f.render_stateful_widget(table, rects[0], &mut *self.table_state.borrow_mut());
     }
 }
 
+/// A short digest of the image the container was started from.
+fn image_digest(record: &ImageRecord) -> String {
+    let digest = record.digest.as_deref().unwrap_or("unknown");
+    let digest = digest.strip_prefix("sha256:").unwrap_or(digest);
+    let short: String = digest.chars().take(12).collect();
+    if record.is_drifted() {
+        format!("{} (not pinned)", short)
+    } else {
+        short
+    }
+}
+
 fn containers() -> Vec<TaskId> {
     vec![
         images::Tor::id(),
@@ -127,5 +147,7 @@ fn containers() -> Vec<TaskId> {
         images::Grafana::id(),
         images::Loki::id(),
         images::Promtail::id(),
+        volumes::SharedVolume::id(),
+        volumes::SharedGrafanaVolume::id(),
     ]
 }
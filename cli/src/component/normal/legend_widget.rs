@@ -55,6 +55,7 @@ impl<B: Backend> Component<B> for LegendWidget {
         let command_items = [
             ["Ctrl-Q", "Quit"],
             ["Ctrl-B", "Start Tari node"],
+            ["Ctrl-O", "Open node console"],
             ["Ctrl-H", "Switch to Home"],
             ["Ctrl-L", "Switch to Logs"],
             ["Ctrl-S", "Switch to Settings"],
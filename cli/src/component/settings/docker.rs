@@ -21,10 +21,12 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
+use log::warn;
 use ratatui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Rect},
 };
+use tari_launchpad_protocol::settings::{LaunchpadSettings, NetworkSettings};
 
 use crate::{
     component::{
@@ -42,11 +44,14 @@ use crate::{
 pub static DOCKER_SETTINGS: Focus = focus_id!();
 static DOCKER_TAG: Focus = focus_id!();
 static DOCKER_REGISTRY: Focus = focus_id!();
+static DOCKER_NETWORK: Focus = focus_id!();
 
 pub struct DockerSettings {
     expert_sep: Separator,
     docker_tag: LabeledInput,
     docker_registry: LabeledInput,
+    /// Addresses in the form `subnet=172.30.0.0/16 gateway=172.30.0.1 ipv6=fd00:1::/64 internal`
+    docker_network: LabeledInput<NetworkSettings>,
     statuses_sep: Separator,
 }
 
@@ -56,15 +61,51 @@ impl DockerSettings {
             expert_sep: Separator::new("Expert", []),
             docker_tag: LabeledInput::new("Docker Tag", DOCKER_TAG),
             docker_registry: LabeledInput::new("Docker Registry", DOCKER_REGISTRY),
+            docker_network: LabeledInput::new("Docker Network", DOCKER_NETWORK),
             statuses_sep: Separator::new("Image Statuses", []),
         }
     }
+
+    /// An empty value resets the tag or the registry to the default one.
+    pub fn check_for_updated_settings(&mut self, state: &mut AppState) {
+        let mut should_write = false;
+        if let Some(LaunchpadSettings { saved_settings, .. }) = &mut state.state.config.settings {
+            if let Some(tag) = self.docker_tag.fetch_new_value() {
+                saved_settings.tag = Some(tag.clone()).filter(|tag| !tag.is_empty());
+                should_write = true;
+            }
+            if let Some(registry) = self.docker_registry.fetch_new_value() {
+                saved_settings.registry = Some(registry.clone()).filter(|registry| !registry.is_empty());
+                should_write = true;
+            }
+            if let Some(network) = self.docker_network.fetch_new_value() {
+                saved_settings.network = network.clone();
+                should_write = true;
+            }
+        } else {
+            warn!("The app state does not have a settings instance configured, so we cannot update the saved settings");
+        }
+        if should_write {
+            state.update_settings();
+        }
+    }
 }
 
 impl Input for DockerSettings {
     type Output = ();
 
     fn on_event(&mut self, event: ComponentEvent, state: &mut AppState) -> Option<Self::Output> {
+        if let ComponentEvent::StateChanged = event {
+            if let Some(settings) = &state.state.config.settings {
+                let saved_settings = &settings.saved_settings;
+                self.docker_tag.set(saved_settings.tag.clone().unwrap_or_default());
+                self.docker_registry
+                    .set(saved_settings.registry.clone().unwrap_or_default());
+                self.docker_network.set(saved_settings.network.clone());
+            }
+            return None;
+        }
+
         if state.focus_on == DOCKER_SETTINGS {
             match event.pass() {
                 Pass::Up | Pass::Leave => {
@@ -91,19 +132,36 @@ impl Input for DockerSettings {
         } else if state.focus_on == DOCKER_REGISTRY {
             let released = self.docker_registry.is_released();
             match event.pass() {
-                Pass::Leave | Pass::Down if released => {
+                Pass::Leave if released => {
                     state.focus_on(DOCKER_SETTINGS);
                 },
                 Pass::Up if released => {
                     state.focus_on(DOCKER_TAG);
                 },
+                Pass::Down if released => {
+                    state.focus_on(DOCKER_NETWORK);
+                },
                 _ => {
                     self.docker_registry.on_event(event, state);
                 },
             }
+        } else if state.focus_on == DOCKER_NETWORK {
+            let released = self.docker_network.is_released();
+            match event.pass() {
+                Pass::Leave | Pass::Down if released => {
+                    state.focus_on(DOCKER_SETTINGS);
+                },
+                Pass::Up if released => {
+                    state.focus_on(DOCKER_REGISTRY);
+                },
+                _ => {
+                    self.docker_network.on_event(event, state);
+                },
+            }
         } else {
             //
         }
+        self.check_for_updated_settings(state);
         None
     }
 }
@@ -120,6 +178,7 @@ impl<B: Backend> Component<B> for DockerSettings {
             Constraint::Length(1),
             Constraint::Length(3),
             Constraint::Length(3),
+            Constraint::Length(3),
             // Image Statuses
             Constraint::Length(1),
             Constraint::Min(0),
@@ -133,7 +192,8 @@ impl<B: Backend> Component<B> for DockerSettings {
         self.expert_sep.draw(f, chunks[0], state);
         self.docker_tag.draw(f, chunks[1], state);
         self.docker_registry.draw(f, chunks[2], state);
+        self.docker_network.draw(f, chunks[3], state);
 
-        self.statuses_sep.draw(f, chunks[3], state);
+        self.statuses_sep.draw(f, chunks[4], state);
     }
 }
@@ -24,6 +24,7 @@
 mod base_node;
 mod docker;
 mod mining;
+mod resources;
 
 use base_node::BaseNodeSettings;
 use docker::DockerSettings;
@@ -32,6 +33,7 @@ use ratatui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Rect},
 };
+use resources::ResourcesSettings;
 use strum::{Display, EnumCount, EnumIter, FromRepr};
 
 use crate::{
@@ -46,6 +48,7 @@ use crate::{
 pub enum SettingsTabs {
     Mining,
     BaseNode,
+    Resources,
     Docker,
 }
 
@@ -54,6 +57,7 @@ impl TabGetter for SettingsTabs {
         match self {
             Self::Mining => mining::MINING_SETTINGS,
             Self::BaseNode => base_node::BASE_NODE_SETTINGS,
+            Self::Resources => resources::RESOURCES_SETTINGS,
             Self::Docker => docker::DOCKER_SETTINGS,
         }
     }
@@ -63,6 +67,7 @@ pub struct SettingsScene {
     settings_tabs: AppTabs<SettingsTabs>,
     mining_settings: MiningSettings,
     base_node_settings: BaseNodeSettings,
+    resources_settings: ResourcesSettings,
     docker_settings: DockerSettings,
 }
 
@@ -72,6 +77,7 @@ impl SettingsScene {
             settings_tabs: AppTabs::new(),
             mining_settings: MiningSettings::new(),
             base_node_settings: BaseNodeSettings::new(),
+            resources_settings: ResourcesSettings::new(),
             docker_settings: DockerSettings::new(),
         }
     }
@@ -81,6 +87,13 @@ impl Input for SettingsScene {
     type Output = ();
 
     fn on_event(&mut self, event: ComponentEvent, state: &mut AppState) -> Option<Self::Output> {
+        if let ComponentEvent::StateChanged = event {
+            // All tabs keep their inputs in sync with the settings, not only the selected one
+            self.mining_settings.on_event(event, state);
+            self.resources_settings.on_event(event, state);
+            self.docker_settings.on_event(event, state);
+            return None;
+        }
         self.settings_tabs.on_event(event, state);
         match self.settings_tabs.selected() {
             SettingsTabs::Mining => {
@@ -89,6 +102,9 @@ impl Input for SettingsScene {
             SettingsTabs::BaseNode => {
                 self.base_node_settings.on_event(event, state);
             },
+            SettingsTabs::Resources => {
+                self.resources_settings.on_event(event, state);
+            },
             SettingsTabs::Docker => {
                 self.docker_settings.on_event(event, state);
             },
@@ -114,6 +130,9 @@ impl<B: Backend> Component<B> for SettingsScene {
             SettingsTabs::BaseNode => {
                 self.base_node_settings.draw(f, chunks[1], state);
             },
+            SettingsTabs::Resources => {
+                self.resources_settings.draw(f, chunks[1], state);
+            },
             SettingsTabs::Docker => {
                 self.docker_settings.draw(f, chunks[1], state);
             },
@@ -0,0 +1,191 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use log::warn;
+use ratatui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+};
+use tari_launchpad_protocol::settings::{LaunchpadSettings, LimitedService, ResourceLimits};
+
+use crate::{
+    component::{
+        elements::block_with_title,
+        widgets::{LabeledInput, Separator},
+        Component, ComponentEvent, Frame, Input, Pass,
+    },
+    focus_id,
+    state::{
+        focus::{self, Focus},
+        AppState,
+    },
+};
+
+pub static RESOURCES_SETTINGS: Focus = focus_id!();
+static BASE_NODE_LIMITS: Focus = focus_id!();
+static SHA3_MINER_LIMITS: Focus = focus_id!();
+static MM_PROXY_LIMITS: Focus = focus_id!();
+static XMRIG_LIMITS: Focus = focus_id!();
+
+/// Edits limits of containers in the form `cpus=2 cpuset=0-3 memory=4096 reservation=1024 pids=256`.
+/// The limits are applied when a container starts.
+pub struct ResourcesSettings {
+    limits_sep: Separator,
+    inputs: Vec<(LimitedService, LabeledInput<ResourceLimits>)>,
+    hint_sep: Separator,
+}
+
+impl ResourcesSettings {
+    pub fn new() -> Self {
+        Self {
+            limits_sep: Separator::new("Limits", []),
+            inputs: vec![
+                (
+                    LimitedService::BaseNode,
+                    LabeledInput::new("Base node", BASE_NODE_LIMITS),
+                ),
+                (
+                    LimitedService::Sha3Miner,
+                    LabeledInput::new("SHA3 miner", SHA3_MINER_LIMITS),
+                ),
+                (
+                    LimitedService::MmProxy,
+                    LabeledInput::new("Merge mining proxy", MM_PROXY_LIMITS),
+                ),
+                (LimitedService::XmRig, LabeledInput::new("XMRig", XMRIG_LIMITS)),
+            ],
+            hint_sep: Separator::new(
+                "cpus=2 cpuset=0-3 memory=4096 reservation=1024 pids=256 (memory in MiB)",
+                [],
+            ),
+        }
+    }
+
+    pub fn check_for_updated_settings(&mut self, state: &mut AppState) {
+        let mut should_write = false;
+        if let Some(LaunchpadSettings { saved_settings, .. }) = &mut state.state.config.settings {
+            for (service, input) in &mut self.inputs {
+                if let Some(limits) = input.fetch_new_value() {
+                    *saved_settings.resources_mut(*service) = limits.clone();
+                    should_write = true;
+                }
+            }
+        } else {
+            warn!("The app state does not have a settings instance configured, so we cannot update the saved settings");
+        }
+        if should_write {
+            state.update_settings();
+        }
+    }
+
+    fn focus_index(&self, state: &AppState) -> Option<usize> {
+        self.inputs
+            .iter()
+            .position(|(_, input)| input.focus() == state.focus_on)
+    }
+}
+
+impl Input for ResourcesSettings {
+    type Output = ();
+
+    fn on_event(&mut self, event: ComponentEvent, state: &mut AppState) -> Option<Self::Output> {
+        if let ComponentEvent::StateChanged = event {
+            if let Some(settings) = &state.state.config.settings {
+                for (service, input) in &mut self.inputs {
+                    if let Some(limits) = settings.saved_settings.resources(*service) {
+                        input.set(limits.clone());
+                    }
+                }
+            }
+            return None;
+        }
+
+        if state.focus_on == RESOURCES_SETTINGS {
+            match event.pass() {
+                Pass::Up | Pass::Leave => {
+                    state.focus_on(focus::ROOT);
+                },
+                Pass::Down | Pass::Enter => {
+                    state.focus_on(BASE_NODE_LIMITS);
+                },
+                _ => {},
+            }
+        } else if let Some(idx) = self.focus_index(state) {
+            let last = self.inputs.len() - 1;
+            let input = &mut self.inputs[idx].1;
+            let released = input.is_released();
+            match event.pass() {
+                Pass::Leave if released => {
+                    state.focus_on(RESOURCES_SETTINGS);
+                },
+                Pass::Up if released => {
+                    let focus = if idx == 0 {
+                        RESOURCES_SETTINGS
+                    } else {
+                        self.inputs[idx - 1].1.focus()
+                    };
+                    state.focus_on(focus);
+                },
+                Pass::Down if released => {
+                    let focus = if idx == last {
+                        RESOURCES_SETTINGS
+                    } else {
+                        self.inputs[idx + 1].1.focus()
+                    };
+                    state.focus_on(focus);
+                },
+                _ => {
+                    input.on_event(event, state);
+                },
+            }
+        } else {
+            //
+        }
+        self.check_for_updated_settings(state);
+        None
+    }
+}
+
+impl<B: Backend> Component<B> for ResourcesSettings {
+    type State = AppState;
+
+    fn draw(&self, f: &mut Frame<B>, rect: Rect, state: &Self::State) {
+        let block = block_with_title(Some("Resources Settings"), state.focus_on == RESOURCES_SETTINGS);
+        let inner_rect = block.inner(rect);
+        f.render_widget(block, rect);
+        let mut constraints = vec![Constraint::Length(1)];
+        constraints.extend(self.inputs.iter().map(|_| Constraint::Length(3)));
+        constraints.push(Constraint::Length(1));
+        constraints.push(Constraint::Min(0));
+        let chunks = Layout::default()
+            .vertical_margin(1)
+            .horizontal_margin(3)
+            .direction(Direction::Vertical)
+            .constraints(constraints)
+            .split(inner_rect);
+        self.limits_sep.draw(f, chunks[0], state);
+        for (idx, (_, input)) in self.inputs.iter().enumerate() {
+            input.draw(f, chunks[idx + 1], state);
+        }
+        self.hint_sep.draw(f, chunks[self.inputs.len() + 1], state);
+    }
+}
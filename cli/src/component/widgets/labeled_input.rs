@@ -135,6 +135,10 @@ where
         !self.input_mode
     }
 
+    pub fn focus(&self) -> Focus {
+        self.focus
+    }
+
     pub fn set(&mut self, value: T) {
         self.content = if let Ok(val) = TariAddress::from_str(&value.to_string()) {
             TariAddress::to_emoji_string(&val)
@@ -210,6 +210,30 @@ impl Do<Reaction> for Dashboard {
                     state.state.apply(delta);
                 }
             },
+            Reaction::Stopped => {
+                if let Some(state) = self.state.as_mut() {
+                    state.stopped = true;
+                }
+            },
+            Reaction::Logs(_)
+            | Reaction::Snapshot(_)
+            | Reaction::VolumeCleared { .. }
+            | Reaction::OrphansRemoved { .. } => {},
+            Reaction::Exec { task_id, output } => {
+                if let Some(state) = self.state.as_mut() {
+                    // Skips the output of commands started in other containers
+                    if state.exec_task.as_ref() == Some(&task_id) {
+                        state.exec_output.push(output);
+                    }
+                }
+            },
+            Reaction::Console { task_id, event } => {
+                if let Some(state) = self.state.as_mut() {
+                    if state.console.task_id.as_ref() == Some(&task_id) {
+                        state.console.apply(event);
+                    }
+                }
+            },
         }
         // Reporting about the state has changed - this gets triggered for every event, so isn't very efficient.
         if let Some(state) = self.state.as_mut() {
@@ -23,7 +23,9 @@
 
 mod dashboard;
 mod events;
+pub mod snapshot;
 mod state;
 pub mod supervisor;
+pub mod volume;
 
 mod component;
@@ -25,11 +25,32 @@ use std::env;
 
 use anyhow::{Context, Error};
 use tact::Actor;
-use tari_launchpad_cli::supervisor::Supervisor;
+use tari_launchpad_cli::{
+    snapshot::{self, SnapshotCommand},
+    supervisor::Supervisor,
+    volume::{self, VolumeCommand},
+};
 use tari_sdm_assets::configurator::Configurator;
 
+/// Commands that are executed without the dashboard.
+enum Command {
+    Snapshot(SnapshotCommand),
+    Volume(VolumeCommand),
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Error> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let command = match args.first().map(String::as_str) {
+        Some(snapshot::COMMAND) => {
+            let mut command = SnapshotCommand::parse(&args[1..])?;
+            command.absolutize()?;
+            Some(Command::Snapshot(command))
+        },
+        Some(volume::COMMAND) => Some(Command::Volume(VolumeCommand::parse(&args[1..])?)),
+        _ => None,
+    };
+
     let mut configurator = Configurator::init()?;
     configurator.init_configuration(false).await?;
 
@@ -38,8 +59,14 @@ async fn main() -> Result<(), Error> {
 
     log4rs::init_file("config/log4rs-cli.yml", Default::default()).context("Can't read a logs configuration file")?;
 
-    let supervisor = Supervisor::default();
-    let mut addr = supervisor.start();
-    addr.join().await?;
-    Ok(())
+    match command {
+        Some(Command::Snapshot(command)) => command.run().await,
+        Some(Command::Volume(command)) => command.run().await,
+        None => {
+            let supervisor = Supervisor::default();
+            let mut addr = supervisor.start();
+            addr.join().await?;
+            Ok(())
+        },
+    }
 }
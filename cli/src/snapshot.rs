@@ -0,0 +1,107 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+//! The `snapshot` command exports or restores the blockchain volume without the dashboard:
+//!
+//! `tari_launchpad_cli snapshot export|restore <file> [network]`
+//!
+//! Without a network the whole volume is copied, otherwise only the data of the network.
+
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Error};
+use tari_launchpad_protocol::{
+    launchpad::{Action, LaunchpadAction, Reaction, SnapshotResult},
+    settings::TariNetwork,
+};
+use tari_sdm_launchpad::bus::LaunchpadBus;
+
+pub const COMMAND: &str = "snapshot";
+const USAGE: &str = "Usage: snapshot export|restore <file> [network]";
+
+#[derive(Debug)]
+pub struct SnapshotCommand {
+    restore: bool,
+    network: Option<TariNetwork>,
+    path: PathBuf,
+}
+
+impl SnapshotCommand {
+    /// Parses the arguments that follow the name of the command.
+    pub fn parse(args: &[String]) -> Result<Self, Error> {
+        let restore = match args.first().map(String::as_str) {
+            Some("export") => false,
+            Some("restore") => true,
+            _ => return Err(anyhow!(USAGE)),
+        };
+        let path = args.get(1).map(PathBuf::from).ok_or_else(|| anyhow!(USAGE))?;
+        let network = args
+            .get(2)
+            .map(|network| TariNetwork::try_from(network.as_str()))
+            .transpose()?;
+        if args.len() > 3 {
+            return Err(anyhow!(USAGE));
+        }
+        Ok(Self { restore, network, path })
+    }
+
+    /// Relative paths are resolved against the current directory, since the launchpad changes it later.
+    pub fn absolutize(&mut self) -> Result<(), Error> {
+        if self.path.is_relative() {
+            self.path = std::env::current_dir()?.join(&self.path);
+        }
+        Ok(())
+    }
+
+    /// Sends the action to the bus and waits for the result of it.
+    pub async fn run(self) -> Result<(), Error> {
+        let mut bus = LaunchpadBus::start()?;
+        let action = if self.restore {
+            LaunchpadAction::RestoreSnapshot {
+                network: self.network,
+                path: self.path,
+            }
+        } else {
+            LaunchpadAction::ExportSnapshot {
+                network: self.network,
+                path: self.path,
+            }
+        };
+        bus.incoming.send(Action::Action(action))?;
+        while let Some(reaction) = bus.outgoing.recv().await {
+            if let Reaction::Snapshot(result) = reaction {
+                return match result {
+                    SnapshotResult::Exported(path) => {
+                        println!("The blockchain is exported to {}", path.display());
+                        Ok(())
+                    },
+                    SnapshotResult::Restored(path) => {
+                        println!("The blockchain is restored from {}", path.display());
+                        Ok(())
+                    },
+                    SnapshotResult::Failed(reason) => Err(anyhow!("The snapshot failed: {}", reason)),
+                };
+            }
+        }
+        Err(anyhow!("The bus was closed before the snapshot was done"))
+    }
+}
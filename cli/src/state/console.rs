@@ -0,0 +1,151 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use tari_launchpad_protocol::{
+    container::{ConsoleEvent, TaskId},
+    frame::Frame,
+};
+
+const LINES_LIMIT: usize = 1_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Escape {
+    None,
+    /// Right after `ESC`
+    Start,
+    /// A control sequence: `ESC [ ... final`
+    Csi,
+    /// An operating system command: `ESC ] ... BEL`
+    Osc,
+}
+
+/// The output of a terminal of a container attached by the user.
+///
+/// The output is kept as plain text: control sequences are dropped and a carriage return
+/// rewrites the current line, so the redrawn status lines don't pile up.
+pub struct Console {
+    /// The task of the terminal
+    pub task_id: Option<TaskId>,
+    pub attached: bool,
+    pub error: Option<String>,
+    lines: Frame<String>,
+    line: String,
+    carriage_return: bool,
+    escape: Escape,
+}
+
+impl Console {
+    pub fn new() -> Self {
+        Self {
+            task_id: None,
+            attached: false,
+            error: None,
+            lines: Frame::new(LINES_LIMIT),
+            line: String::new(),
+            carriage_return: false,
+            escape: Escape::None,
+        }
+    }
+
+    /// Starts a new session with the terminal of the task.
+    pub fn reset(&mut self, task_id: TaskId) {
+        *self = Self::new();
+        self.task_id = Some(task_id);
+    }
+
+    pub fn apply(&mut self, event: ConsoleEvent) {
+        match event {
+            ConsoleEvent::Attached => {
+                self.attached = true;
+                self.error = None;
+            },
+            ConsoleEvent::Output(text) => {
+                self.push(&text);
+            },
+            ConsoleEvent::Detached => {
+                self.attached = false;
+            },
+            ConsoleEvent::Error(reason) => {
+                self.error = Some(reason);
+            },
+        }
+    }
+
+    /// Complete lines followed by the current one.
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.lines.iter().map(String::as_str).chain(Some(self.line.as_str()))
+    }
+
+    fn push(&mut self, text: &str) {
+        for c in text.chars() {
+            match self.escape {
+                Escape::Start => {
+                    self.escape = match c {
+                        '[' => Escape::Csi,
+                        ']' => Escape::Osc,
+                        _ => Escape::None,
+                    };
+                },
+                Escape::Csi => {
+                    if ('@'..='~').contains(&c) {
+                        self.escape = Escape::None;
+                    }
+                },
+                Escape::Osc => {
+                    if c == '\x07' || c == '\x1b' {
+                        self.escape = Escape::None;
+                    }
+                },
+                Escape::None => {
+                    self.push_char(c);
+                },
+            }
+        }
+    }
+
+    fn push_char(&mut self, c: char) {
+        if self.carriage_return && c != '\n' {
+            self.line.clear();
+        }
+        self.carriage_return = false;
+        match c {
+            '\x1b' => {
+                self.escape = Escape::Start;
+            },
+            '\r' => {
+                self.carriage_return = true;
+            },
+            '\n' => {
+                let line = std::mem::take(&mut self.line);
+                self.lines.push(line);
+            },
+            '\x08' => {
+                self.line.pop();
+            },
+            c if c.is_control() => {},
+            c => {
+                self.line.push(c);
+            },
+        }
+    }
+}
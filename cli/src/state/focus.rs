@@ -33,6 +33,8 @@ macro_rules! focus_id {
 
 pub static TERMINATION: Focus = focus_id!();
 pub static ROOT: Focus = focus_id!();
+/// The full screen console of a container.
+pub static CONSOLE: Focus = focus_id!();
 
 pub static BASE_NODE: Focus = focus_id!();
 
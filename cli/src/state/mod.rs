@@ -21,27 +21,47 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
+pub mod console;
 pub mod focus;
 
 use std::collections::VecDeque;
 
 use anyhow::Error;
+pub use console::Console;
 pub use focus::Focus;
-use tari_launchpad_protocol::launchpad::{Action, LaunchpadAction, LaunchpadState};
+use tari_launchpad_protocol::{
+    container::{ExecOutput, TaskId},
+    frame::Frame,
+    launchpad::{Action, LaunchpadAction, LaunchpadState},
+};
 use tari_sdm_launchpad::bus::BusTx;
 
 pub enum AppEvent {
     SetFocus(Focus),
     SettingsChanged,
     UpdateState,
+    Shutdown,
+    Exec { task_id: TaskId, command: Vec<String> },
+    AttachConsole { task_id: TaskId, width: u16, height: u16 },
+    ConsoleInput { task_id: TaskId, input: String },
+    DetachConsole { task_id: TaskId },
 }
 
+const EXEC_OUTPUT_LIMIT: usize = 500;
+
 pub struct AppState {
     pub focus_on: Focus,
     pub events_queue: VecDeque<AppEvent>,
     pub bus_tx: BusTx,
     pub state: LaunchpadState,
     pub terminate: bool,
+    /// Set when the bus reported that all the containers are stopped.
+    pub stopped: bool,
+    /// The task the last command was executed in.
+    pub exec_task: Option<TaskId>,
+    pub exec_output: Frame<ExecOutput>,
+    /// The attached terminal of a container.
+    pub console: Console,
 }
 
 impl AppState {
@@ -52,17 +72,15 @@ impl AppState {
             bus_tx,
             state,
             terminate: false,
+            stopped: false,
+            exec_task: None,
+            exec_output: Frame::new(EXEC_OUTPUT_LIMIT),
+            console: Console::new(),
         }
     }
 
     pub fn is_terminated(&mut self) -> bool {
-        let has_active_task = self
-            .state
-            .containers
-            .values()
-            .filter(|state| !state.permanent)
-            .any(|state| state.status.is_started());
-        self.terminate && !has_active_task
+        self.terminate && self.stopped
     }
 
     pub fn focus_on(&mut self, value: Focus) {
@@ -72,6 +90,8 @@ impl AppState {
 
     pub fn terminate(&mut self) {
         self.terminate = true;
+        let event = AppEvent::Shutdown;
+        self.events_queue.push_front(event);
     }
 
     pub fn update_state(&mut self) {
@@ -84,6 +104,36 @@ impl AppState {
         self.events_queue.push_front(event);
     }
 
+    /// Runs the command in the container of the task and replaces the output of the previous one.
+    pub fn exec(&mut self, task_id: TaskId, command: Vec<String>) {
+        self.exec_task = Some(task_id.clone());
+        self.exec_output = Frame::new(EXEC_OUTPUT_LIMIT);
+        let event = AppEvent::Exec { task_id, command };
+        self.events_queue.push_front(event);
+    }
+
+    pub fn attach_console(&mut self, task_id: TaskId, width: u16, height: u16) {
+        self.console.reset(task_id.clone());
+        let event = AppEvent::AttachConsole { task_id, width, height };
+        self.events_queue.push_front(event);
+    }
+
+    /// Sends the line to the attached terminal. Lines are queued in order, unlike other events.
+    pub fn console_input(&mut self, line: &str) {
+        if let Some(task_id) = self.console.task_id.clone() {
+            let input = format!("{line}\n");
+            let event = AppEvent::ConsoleInput { task_id, input };
+            self.events_queue.push_back(event);
+        }
+    }
+
+    pub fn detach_console(&mut self) {
+        if let Some(task_id) = self.console.task_id.take() {
+            let event = AppEvent::DetachConsole { task_id };
+            self.events_queue.push_back(event);
+        }
+    }
+
     pub fn process_events(&mut self) -> bool {
         if self.events_queue.is_empty() {
             false
@@ -107,6 +157,26 @@ impl AppState {
                     let action = Action::Action(event);
                     self.bus_tx.send(action)?;
                 },
+                AppEvent::Shutdown => {
+                    let action = Action::Action(LaunchpadAction::Shutdown);
+                    self.bus_tx.send(action)?;
+                },
+                AppEvent::Exec { task_id, command } => {
+                    let action = Action::Action(LaunchpadAction::Exec { task_id, command });
+                    self.bus_tx.send(action)?;
+                },
+                AppEvent::AttachConsole { task_id, width, height } => {
+                    let action = Action::Action(LaunchpadAction::AttachConsole { task_id, width, height });
+                    self.bus_tx.send(action)?;
+                },
+                AppEvent::ConsoleInput { task_id, input } => {
+                    let action = Action::Action(LaunchpadAction::ConsoleInput { task_id, input });
+                    self.bus_tx.send(action)?;
+                },
+                AppEvent::DetachConsole { task_id } => {
+                    let action = Action::Action(LaunchpadAction::DetachConsole { task_id });
+                    self.bus_tx.send(action)?;
+                },
                 AppEvent::SettingsChanged => {
                     let settings = self
                         .state
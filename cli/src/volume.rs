@@ -0,0 +1,93 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+//! The `volume` command removes volumes or the blockchain of a network without the dashboard:
+//!
+//! `tari_launchpad_cli volume remove <task>` removes the volume of the task, e.g. `SharedVolume`,
+//! `tari_launchpad_cli volume reset <network>` removes the blockchain of the network only,
+//! `tari_launchpad_cli volume prune` removes the volumes left by removed tasks or older versions.
+
+use anyhow::{anyhow, Error};
+use tari_launchpad_protocol::{
+    container::TaskId,
+    launchpad::{Action, LaunchpadAction, Reaction},
+    settings::TariNetwork,
+};
+use tari_sdm_launchpad::bus::LaunchpadBus;
+
+pub const COMMAND: &str = "volume";
+const USAGE: &str = "Usage: volume remove <task> | volume reset <network> | volume prune";
+
+#[derive(Debug)]
+pub enum VolumeCommand {
+    Remove(TaskId),
+    ResetChainData(TariNetwork),
+    Prune,
+}
+
+impl VolumeCommand {
+    /// Parses the arguments that follow the name of the command.
+    pub fn parse(args: &[String]) -> Result<Self, Error> {
+        let args: Vec<_> = args.iter().map(String::as_str).collect();
+        match args.as_slice() {
+            ["remove", task] => Ok(Self::Remove((*task).into())),
+            ["reset", network] => Ok(Self::ResetChainData(TariNetwork::try_from(*network)?)),
+            ["prune"] => Ok(Self::Prune),
+            _ => Err(anyhow!(USAGE)),
+        }
+    }
+
+    /// Sends the action to the bus and waits for the result of it.
+    pub async fn run(self) -> Result<(), Error> {
+        let mut bus = LaunchpadBus::start()?;
+        let action = match self {
+            Self::Remove(task_id) => LaunchpadAction::RemoveVolume { task_id },
+            Self::ResetChainData(network) => LaunchpadAction::ResetChainData { network },
+            Self::Prune => LaunchpadAction::RemoveOrphanedVolumes,
+        };
+        bus.incoming.send(Action::Action(action))?;
+        while let Some(reaction) = bus.outgoing.recv().await {
+            match reaction {
+                Reaction::VolumeCleared { task_id, error } => {
+                    return match error {
+                        None => {
+                            println!("The volume of {} is cleared", task_id);
+                            Ok(())
+                        },
+                        Some(reason) => Err(anyhow!("Can't clear the volume of {}: {}", task_id, reason)),
+                    };
+                },
+                Reaction::OrphansRemoved { removed, error } => {
+                    for name in &removed {
+                        println!("The volume {} is removed", name);
+                    }
+                    return match error {
+                        None => Ok(()),
+                        Some(reason) => Err(anyhow!("Can't remove the orphaned volumes: {}", reason)),
+                    };
+                },
+                _ => {},
+            }
+        }
+        Err(anyhow!("The bus was closed before the volume was cleared"))
+    }
+}
@@ -23,8 +23,8 @@
 
 use std::fmt;
 
-use byte_unit::Byte;
-use chrono::NaiveDateTime;
+use byte_unit::{Byte, UnitType};
+use chrono::{Local, NaiveDateTime};
 use derive_more::{Display, From, Into};
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
@@ -60,7 +60,50 @@ pub struct LogRecord {
     pub message: String,
 }
 
-#[derive(Debug, Clone, AsRefStr, Serialize_repr, Deserialize_repr)]
+/// A request of archived logs of a task, ordered from the oldest to the newest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogQuery {
+    pub task_id: TaskId,
+    pub since: Option<NaiveDateTime>,
+    pub until: Option<NaiveDateTime>,
+    /// The number of matching records to skip
+    pub offset: usize,
+    pub limit: usize,
+}
+
+/// A page of archived logs of a task.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogPage {
+    pub task_id: TaskId,
+    pub offset: usize,
+    pub records: Vec<LogRecord>,
+    /// There are more records after the page
+    pub has_more: bool,
+}
+
+/// A piece of the output of a command executed inside of a container.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExecOutput {
+    Stdout(String),
+    Stderr(String),
+    /// The command has finished with the exit code
+    Exited(i64),
+    /// The command can't be executed
+    Error(String),
+}
+
+/// An event of a console attached to the terminal of a container.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConsoleEvent {
+    Attached,
+    /// A piece of the raw output of the terminal
+    Output(String),
+    /// The console was detached or the container has stopped
+    Detached,
+    Error(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AsRefStr, Serialize_repr, Deserialize_repr)]
 #[repr(u16)]
 pub enum LogLevel {
     Trace = 0b00001,
@@ -77,6 +120,12 @@ pub struct TaskState {
     pub fails: Frame<ErrorRecord>,
     pub stats: Frame<StatsData>,
     pub permanent: bool,
+    /// The image used by the last start of the container
+    #[serde(default)]
+    pub image: Option<ImageRecord>,
+    /// The disk space used by a volume in bytes
+    #[serde(default)]
+    pub disk_usage: Option<u64>,
 }
 
 impl TaskState {
@@ -87,6 +136,8 @@ impl TaskState {
             fails: Frame::new(FAILS_LIMIT),
             stats: Frame::new(STATS_LIMIT),
             permanent,
+            image: None,
+            disk_usage: None,
         }
     }
 
@@ -104,14 +155,41 @@ impl TaskState {
             TaskDelta::StatsRecord(record) => {
                 self.stats.push(record);
             },
+            TaskDelta::ImageUsed(record) => {
+                self.image = Some(record);
+            },
+            TaskDelta::DiskUsage(size) => {
+                self.disk_usage = size;
+            },
         }
     }
 }
 
+/// The image a container was started from.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ImageRecord {
+    pub image: String,
+    /// The digest of the local image, if the runtime reported it
+    pub digest: Option<String>,
+    /// The digest the image is pinned to
+    pub pinned: Option<String>,
+    pub started_at: NaiveDateTime,
+}
+
+impl ImageRecord {
+    /// The local image is not the pinned one, or it's unknown what it is.
+    pub fn is_drifted(&self) -> bool {
+        self.pinned.is_some() && self.digest != self.pinned
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct TaskProgress {
     pub pct: u8,
     pub stage: String,
+    /// The amount of transferred data, if the stage is a transfer like pulling an image
+    #[serde(default)]
+    pub transfer: Option<TransferProgress>,
 }
 
 impl TaskProgress {
@@ -119,7 +197,39 @@ impl TaskProgress {
         Self {
             pct: 0,
             stage: stage.to_string(),
+            transfer: None,
+        }
+    }
+}
+
+/// Bytes transferred by a stage of a task.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TransferProgress {
+    pub current: u64,
+    pub total: u64,
+    /// Bytes per second
+    pub speed: Option<u64>,
+    /// Estimated seconds to the end of the stage
+    pub eta: Option<u64>,
+}
+
+impl fmt::Display for TransferProgress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let current = Byte::from_u64(self.current).get_appropriate_unit(UnitType::Decimal);
+        let total = Byte::from_u64(self.total).get_appropriate_unit(UnitType::Decimal);
+        write!(f, "{:.1} of {:.1}", current, total)?;
+        if let Some(speed) = self.speed {
+            let speed = Byte::from_u64(speed).get_appropriate_unit(UnitType::Decimal);
+            write!(f, ", {:.1}/s", speed)?;
+        }
+        if let Some(eta) = self.eta {
+            if eta >= 60 {
+                write!(f, ", {}m {}s left", eta / 60, eta % 60)?;
+            } else {
+                write!(f, ", {}s left", eta)?;
+            }
         }
+        Ok(())
     }
 }
 
@@ -142,10 +252,21 @@ pub enum TaskStatus {
     Active,
     /// Task failed for other reasons.
     Failed(String),
+    /// The registry rejected the credentials used to pull the image.
+    Unauthorized {
+        registry: String,
+        reason: String,
+    },
     /// Task is shutting down.
     ShuttingDown,
     /// Task is waiting for dependencies to start.
     Waiting,
+    /// Task crashed and will be restarted at `next_retry`.
+    Restarting {
+        attempt: u32,
+        max_attempts: Option<u32>,
+        next_retry: NaiveDateTime,
+    },
 }
 
 impl TaskStatus {
@@ -169,13 +290,19 @@ impl TaskStatus {
     }
 
     pub fn is_failed(&self) -> bool {
-        matches!(self, Self::Failed(_))
+        matches!(self, Self::Failed(_) | Self::Unauthorized { .. })
     }
 
     pub fn progress(&self) -> Option<String> {
         match self {
             Self::Waiting => Some(format!("{}", self)),
             Self::ShuttingDown => Some(format!("{}", self)),
+            Self::Restarting { .. } => Some(format!("{}", self)),
+            Self::Progress(TaskProgress {
+                stage,
+                pct,
+                transfer: Some(transfer),
+            }) => Some(format!("{} - {}% ({})", stage, pct, transfer)),
             Self::Progress(progress) => Some(format!("{} - {}%", progress.stage, progress.pct)),
             _ => None,
         }
@@ -191,8 +318,27 @@ impl fmt::Display for TaskStatus {
             Self::Active => write!(f, "Active"),
             Self::MissingConfiguration(reason) => write!(f, "Missing configuration. Details: {}", reason),
             Self::Failed(reason) => write!(f, "Failed. Reason: {}", reason),
+            Self::Unauthorized { registry, reason } => {
+                write!(
+                    f,
+                    "Login to {} failed. Check the registry credentials. Reason: {}",
+                    registry, reason
+                )
+            },
             Self::ShuttingDown => write!(f, "Shutting down"),
             Self::Waiting => write!(f, "Waiting"),
+            Self::Restarting {
+                attempt,
+                max_attempts,
+                next_retry,
+            } => {
+                let secs = (*next_retry - Local::now().naive_local()).num_seconds().max(0);
+                write!(f, "Restarting in {}s (attempt {}", secs, attempt)?;
+                if let Some(max_attempts) = max_attempts {
+                    write!(f, "/{}", max_attempts)?;
+                }
+                write!(f, ")")
+            },
         }
     }
 }
@@ -203,16 +349,57 @@ pub enum TaskDelta {
     LogRecord(LogRecord),
     LogError(ErrorRecord),
     StatsRecord(StatsData),
+    ImageUsed(ImageRecord),
+    DiskUsage(Option<u64>),
 }
 
 impl Frame<StatsData> {
+    /// The CPU load between the last two records. 100% is a fully loaded CPU, so a container could use up to
+    /// `online_cpus * 100%`, like `docker stats` shows.
     pub fn last_cpu(&self) -> Option<f32> {
+        let (prev, last) = self.last_pair()?;
+        let cpu_delta = last.cpu_usage - prev.cpu_usage;
+        let system_delta = last.system_cpu_usage - prev.system_cpu_usage;
+        if system_delta <= 0 {
+            return None;
+        }
+        let cpus = last.cpus() as f32;
+        Some((cpu_delta as f32 / system_delta as f32 * cpus * 100.0).clamp(0.0, cpus * 100.0))
+    }
+
+    /// Received and transmitted bytes per second between the last two records.
+    pub fn last_net_rates(&self) -> Option<(f64, f64)> {
+        let rx = self.last_rate(|stats| stats.net_rx)?;
+        let tx = self.last_rate(|stats| stats.net_tx)?;
+        Some((rx, tx))
+    }
+
+    /// Read and written bytes per second of block devices between the last two records.
+    pub fn last_block_rates(&self) -> Option<(f64, f64)> {
+        let read = self.last_rate(|stats| stats.block_read)?;
+        let write = self.last_rate(|stats| stats.block_write)?;
+        Some((read, write))
+    }
+
+    fn last_pair(&self) -> Option<(&StatsData, &StatsData)> {
         let mut values = self.iter().rev();
         let last = values.next()?;
         let prev = values.next()?;
-        let cpu_delta = last.cpu_usage - prev.cpu_usage;
-        let system_delta = last.system_cpu_usage - prev.system_cpu_usage;
-        Some((cpu_delta as f32 / system_delta as f32 * 100.0).clamp(0.0, 100.0))
+        Some((prev, last))
+    }
+
+    fn last_rate<F>(&self, counter: F) -> Option<f64>
+    where
+        F: Fn(&StatsData) -> Byte,
+    {
+        let (prev, last) = self.last_pair()?;
+        let seconds = (last.timestamp - prev.timestamp).num_milliseconds() as f64 / 1000.0;
+        if seconds <= 0.0 {
+            return None;
+        }
+        // Counters start from zero if the container was restarted
+        let delta = counter(last).as_u64().saturating_sub(counter(prev).as_u64());
+        Some(delta as f64 / seconds)
     }
 }
 
@@ -221,12 +408,75 @@ pub struct StatsData {
     pub timestamp: NaiveDateTime,
     pub system_cpu_usage: i64,
     pub cpu_usage: i64,
+    /// The number of CPUs available to the container, `0` if it's not known
+    #[serde(default)]
+    pub online_cpus: u32,
     pub mem_limit: Byte,
     pub mem_usage: Byte,
+    /// Bytes received over all the networks since the container started
+    #[serde(default)]
+    pub net_rx: Byte,
+    /// Bytes transmitted over all the networks since the container started
+    #[serde(default)]
+    pub net_tx: Byte,
+    #[serde(default)]
+    pub block_read: Byte,
+    #[serde(default)]
+    pub block_write: Byte,
+    /// The number of processes and threads in the container
+    #[serde(default)]
+    pub pids: u64,
 }
 
 impl StatsData {
     pub fn get_mem_pct(&self) -> f32 {
         self.mem_usage.as_u128() as f32 * 100.0 / self.mem_limit.as_u128() as f32
     }
+
+    fn cpus(&self) -> u32 {
+        self.online_cpus.max(1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{Duration, NaiveDate};
+
+    use super::*;
+
+    fn stats(seconds: i64, cpu_usage: i64, net_rx: u64, block_write: u64) -> StatsData {
+        let start = NaiveDate::from_ymd_opt(2023, 7, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        StatsData {
+            timestamp: start + Duration::seconds(seconds),
+            system_cpu_usage: seconds * 4_000,
+            cpu_usage,
+            online_cpus: 4,
+            mem_limit: Byte::from_u64(1024),
+            mem_usage: Byte::from_u64(512),
+            net_rx: Byte::from_u64(net_rx),
+            net_tx: Byte::default(),
+            block_read: Byte::default(),
+            block_write: Byte::from_u64(block_write),
+            pids: 3,
+        }
+    }
+
+    #[test]
+    fn stats_rates() {
+        let mut frame = Frame::new(STATS_LIMIT);
+        frame.push(stats(0, 0, 0, 0));
+        assert_eq!(frame.last_cpu(), None);
+        frame.push(stats(2, 3_000, 2_000, 500));
+        // 3/8 of the host with 4 CPUs is 1.5 CPUs
+        assert_eq!(frame.last_cpu(), Some(150.0));
+        assert_eq!(frame.last_net_rates(), Some((1_000.0, 0.0)));
+        assert_eq!(frame.last_block_rates(), Some((0.0, 250.0)));
+        // The container was restarted
+        frame.push(stats(4, 0, 100, 0));
+        assert_eq!(frame.last_cpu(), Some(0.0));
+        assert_eq!(frame.last_net_rates(), Some((0.0, 0.0)));
+    }
 }
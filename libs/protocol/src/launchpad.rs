@@ -21,18 +21,22 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap},
+    mem,
+    path::PathBuf,
+};
 
 use serde::{Deserialize, Serialize};
 
 use crate::{
     config::LaunchpadConfig,
-    container::{TaskDelta, TaskId, TaskState},
+    container::{ConsoleEvent, ExecOutput, LogPage, LogQuery, TaskDelta, TaskId, TaskState},
     errors::ErrorRecord,
     frame::Frame,
     node::{NodeDelta, NodeState},
     session::LaunchpadSession,
-    settings::{LaunchpadSettings, PersistentSettings},
+    settings::{LaunchpadSettings, PersistentSettings, TariNetwork},
 };
 
 /// An action sent from UI to the backend.
@@ -47,24 +51,125 @@ pub enum LaunchpadAction {
     Connect,
     ChangeSession(LaunchpadSession),
     SaveSettings(Box<PersistentSettings>),
+    /// Stops all the containers. `Reaction::Stopped` is sent when everything is down.
+    Shutdown,
+    /// Writes the dependency graph of the tasks with their statuses to the `log` folder.
+    ExportGraph,
+    /// Reads a page of the archived logs of a task. The page is sent as `Reaction::Logs`.
+    QueryLogs(LogQuery),
+    /// Runs a command inside of the running container of a task.
+    /// The output is streamed back as `Reaction::Exec` ending with `ExecOutput::Exited`.
+    Exec {
+        task_id: TaskId,
+        command: Vec<String>,
+    },
+    /// Attaches to the terminal of the container of a task with the given size.
+    /// The output is streamed back as `Reaction::Console`.
+    AttachConsole {
+        task_id: TaskId,
+        width: u16,
+        height: u16,
+    },
+    /// Writes the input to the attached terminal as is, lines have to end with `\n`.
+    ConsoleInput {
+        task_id: TaskId,
+        input: String,
+    },
+    /// Detaches from the terminal, the container keeps running.
+    DetachConsole {
+        task_id: TaskId,
+    },
+    /// Exports the blockchain volume, or only the data of the `network`, to a compressed file.
+    /// The containers that use the volume are stopped meanwhile. The result is sent as `Reaction::Snapshot`.
+    ExportSnapshot {
+        network: Option<TariNetwork>,
+        path: PathBuf,
+    },
+    /// Restores the blockchain volume, or only the data of the `network`, from a file made by `ExportSnapshot`.
+    RestoreSnapshot {
+        network: Option<TariNetwork>,
+        path: PathBuf,
+    },
+    /// Removes a volume if no container uses it. The containers that depend on it are stopped meanwhile
+    /// and the volume is created again empty if it's still required. The result is sent as `Reaction::VolumeCleared`.
+    RemoveVolume {
+        task_id: TaskId,
+    },
+    /// Removes the blockchain data of the network, so the base node syncs it from scratch.
+    /// The result is sent as `Reaction::VolumeCleared`.
+    ResetChainData {
+        network: TariNetwork,
+    },
+    /// Removes the volumes of the selected network that don't belong to any of its tasks, e.g. volumes
+    /// of removed tasks. They are only reported on start, since they could keep the data of the user.
+    /// The result is sent as `Reaction::OrphansRemoved`.
+    RemoveOrphanedVolumes,
 }
 
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum LaunchpadDelta {
     UpdateConfig(LaunchpadSettings),
     UpdateSession(LaunchpadSession),
-    TaskAdded { id: TaskId, state: TaskState },
-    TaskDelta { id: TaskId, delta: TaskDelta },
+    TaskAdded {
+        id: TaskId,
+        state: TaskState,
+    },
+    TaskDelta {
+        id: TaskId,
+        delta: TaskDelta,
+    },
     NodeDelta(NodeDelta),
     AddError(ErrorRecord),
+    /// A change of a network that runs besides the selected one.
+    Network {
+        network: TariNetwork,
+        delta: Box<LaunchpadDelta>,
+    },
 }
 
+/// The session, the containers and the base node are the ones of the network selected in the settings.
+/// The states of the other running networks are kept in `networks` and swapped in when a network is selected.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LaunchpadState {
     pub config: LaunchpadConfig,
     pub containers: HashMap<TaskId, TaskState>,
     pub node: NodeState,
     pub errors: Frame<ErrorRecord>,
+    /// States of the networks that run besides the selected one
+    #[serde(default)]
+    pub networks: BTreeMap<TariNetwork, NetworkState>,
+}
+
+/// The state of a network that is not selected.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetworkState {
+    pub session: LaunchpadSession,
+    pub containers: HashMap<TaskId, TaskState>,
+    pub node: NodeState,
+}
+
+impl NetworkState {
+    pub fn apply(&mut self, delta: LaunchpadDelta) {
+        use LaunchpadDelta::*;
+        match delta {
+            UpdateSession(session) => {
+                self.session = session;
+            },
+            TaskAdded { id, state } => {
+                self.containers.insert(id, state);
+            },
+            TaskDelta { id, delta } => {
+                if let Some(state) = self.containers.get_mut(&id) {
+                    state.apply(delta);
+                }
+            },
+            NodeDelta(delta) => {
+                self.node.apply(delta);
+            },
+            UpdateConfig(_) | AddError(_) | Network { .. } => {},
+        }
+    }
 }
 
 impl Default for LaunchpadState {
@@ -74,6 +179,7 @@ impl Default for LaunchpadState {
             containers: HashMap::new(),
             node: NodeState::default(),
             errors: Frame::new(30),
+            networks: BTreeMap::new(),
         }
     }
 }
@@ -83,15 +189,61 @@ impl Default for LaunchpadState {
 pub enum Reaction {
     State(LaunchpadState),
     Delta(LaunchpadDelta),
+    /// All the containers were stopped after `LaunchpadAction::Shutdown`.
+    Stopped,
+    /// A page of the archived logs requested by `LaunchpadAction::QueryLogs`.
+    Logs(LogPage),
+    /// A piece of the output of a command started by `LaunchpadAction::Exec`.
+    Exec {
+        task_id: TaskId,
+        output: ExecOutput,
+    },
+    /// An event of a console attached by `LaunchpadAction::AttachConsole`.
+    Console {
+        task_id: TaskId,
+        event: ConsoleEvent,
+    },
+    /// The result of `LaunchpadAction::ExportSnapshot` or `LaunchpadAction::RestoreSnapshot`.
+    Snapshot(SnapshotResult),
+    /// The result of `LaunchpadAction::RemoveVolume` or `LaunchpadAction::ResetChainData`.
+    VolumeCleared {
+        task_id: TaskId,
+        error: Option<String>,
+    },
+    /// The result of `LaunchpadAction::RemoveOrphanedVolumes` with the names of the removed volumes.
+    OrphansRemoved {
+        removed: Vec<String>,
+        error: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SnapshotResult {
+    Exported(PathBuf),
+    Restored(PathBuf),
+    Failed(String),
 }
 
 impl LaunchpadState {
+    /// The network selected in the settings, if the settings are loaded.
+    pub fn network(&self) -> Option<TariNetwork> {
+        self.config
+            .settings
+            .as_ref()
+            .map(|settings| settings.saved_settings.tari_network)
+    }
+
     pub fn apply(&mut self, delta: LaunchpadDelta) {
         use LaunchpadDelta::*;
         match delta {
             // TODO: Rename to UpdateSettings
             UpdateConfig(settings) => {
+                let previous = self.network();
+                let selected = settings.saved_settings.tari_network;
                 self.config.settings = Some(settings);
+                if let Some(previous) = previous.filter(|previous| *previous != selected) {
+                    self.select_network(previous, selected);
+                }
             },
             UpdateSession(session) => {
                 self.config.session = session;
@@ -110,6 +262,77 @@ impl LaunchpadState {
             NodeDelta(delta) => {
                 self.node.apply(delta);
             },
+            Network { network, delta } => {
+                if self.network() == Some(network) {
+                    self.apply(*delta);
+                } else if let AddError(error) = *delta {
+                    self.errors.push(error);
+                } else {
+                    self.networks.entry(network).or_default().apply(*delta);
+                }
+            },
         }
     }
+
+    /// Keeps the state of the previous network aside and takes the state of the selected one.
+    fn select_network(&mut self, previous: TariNetwork, selected: TariNetwork) {
+        let state = NetworkState {
+            session: mem::take(&mut self.config.session),
+            containers: mem::take(&mut self.containers),
+            node: mem::take(&mut self.node),
+        };
+        self.networks.insert(previous, state);
+        let state = self.networks.remove(&selected).unwrap_or_default();
+        self.config.session = state.session;
+        self.containers = state.containers;
+        self.node = state.node;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::container::TaskStatus;
+
+    fn settings(network: TariNetwork) -> LaunchpadSettings {
+        let mut settings = LaunchpadSettings::default();
+        settings.saved_settings.tari_network = network;
+        settings
+    }
+
+    #[test]
+    fn swap_states_of_networks() {
+        let mut state = LaunchpadState::default();
+        state.apply(LaunchpadDelta::UpdateConfig(settings(TariNetwork::Nextnet)));
+        let id = TaskId::from("Tor");
+        state.apply(LaunchpadDelta::TaskAdded {
+            id: id.clone(),
+            state: TaskState::new(false),
+        });
+        state.apply(LaunchpadDelta::Network {
+            network: TariNetwork::Mainnet,
+            delta: Box::new(LaunchpadDelta::NodeDelta(NodeDelta::SetChainLength(10))),
+        });
+        assert!(state.containers.contains_key(&id));
+        assert_eq!(state.node.chain_height, 0);
+
+        state.apply(LaunchpadDelta::UpdateConfig(settings(TariNetwork::Mainnet)));
+        assert!(state.containers.is_empty());
+        assert_eq!(state.node.chain_height, 10);
+        assert!(state.networks.contains_key(&TariNetwork::Nextnet));
+        assert!(!state.networks.contains_key(&TariNetwork::Mainnet));
+
+        state.apply(LaunchpadDelta::Network {
+            network: TariNetwork::Nextnet,
+            delta: Box::new(LaunchpadDelta::TaskDelta {
+                id: id.clone(),
+                delta: TaskDelta::UpdateStatus(TaskStatus::Active),
+            }),
+        });
+        state.apply(LaunchpadDelta::UpdateConfig(settings(TariNetwork::Nextnet)));
+        assert_eq!(
+            state.containers.get(&id).map(|state| &state.status),
+            Some(&TaskStatus::Active)
+        );
+    }
 }
@@ -21,7 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
-use std::{fmt::Display, path::PathBuf, str::FromStr};
+use std::{collections::BTreeMap, fmt::Display, path::PathBuf, str::FromStr};
 
 use serde::{Deserialize, Serialize};
 use tari_common_types::tari_address::TariAddress;
@@ -29,10 +29,211 @@ use thiserror::Error;
 
 use crate::OptionUsizeWrapper;
 
+/// Resource limits of a container. The container isn't limited if a value is not set.
+#[derive(Default, Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ResourceLimits {
+    /// The number of CPUs available to the container, fractions are allowed
+    pub cpus: Option<f64>,
+    /// CPUs the container can run on, e.g. `0-3` or `0,2`
+    pub cpuset: Option<String>,
+    /// The memory limit in MiB
+    pub memory_mib: Option<u64>,
+    /// The soft memory limit in MiB, it's applied when the host is low on memory
+    pub memory_reservation_mib: Option<u64>,
+    /// The maximal number of processes in the container
+    pub pids_limit: Option<i64>,
+}
+
+#[derive(Debug, Error)]
+#[error("Invalid resource limits: {0}")]
+pub struct InvalidResourceLimits(String);
+
+/// Parses limits in the form `cpus=1.5 cpuset=0-3 memory=2048 reservation=1024 pids=256`.
+/// Memory values are in MiB. An empty string means no limits.
+impl FromStr for ResourceLimits {
+    type Err = InvalidResourceLimits;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut limits = ResourceLimits::default();
+        for pair in s.split_whitespace() {
+            let (key, value) = pair
+                .split_once('=')
+                .ok_or_else(|| InvalidResourceLimits(format!("`{}` is not a `key=value` pair", pair)))?;
+            let invalid = || InvalidResourceLimits(format!("`{}` is not a valid value of `{}`", value, key));
+            match key {
+                "cpus" => {
+                    let cpus: f64 = value.parse().map_err(|_| invalid())?;
+                    if !cpus.is_finite() || cpus <= 0.0 {
+                        return Err(invalid());
+                    }
+                    limits.cpus = Some(cpus);
+                },
+                "cpuset" => {
+                    if !value.chars().all(|c| c.is_ascii_digit() || c == ',' || c == '-') {
+                        return Err(invalid());
+                    }
+                    limits.cpuset = Some(value.to_string());
+                },
+                "memory" => limits.memory_mib = Some(value.parse().map_err(|_| invalid())?),
+                "reservation" => limits.memory_reservation_mib = Some(value.parse().map_err(|_| invalid())?),
+                "pids" => limits.pids_limit = Some(value.parse().map_err(|_| invalid())?),
+                other => return Err(InvalidResourceLimits(format!("unknown limit `{}`", other))),
+            }
+        }
+        if let (Some(memory), Some(reservation)) = (limits.memory_mib, limits.memory_reservation_mib) {
+            if reservation > memory {
+                return Err(InvalidResourceLimits(
+                    "the memory reservation is greater than the memory limit".to_string(),
+                ));
+            }
+        }
+        Ok(limits)
+    }
+}
+
+impl Display for ResourceLimits {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut pairs = Vec::new();
+        if let Some(cpus) = self.cpus {
+            pairs.push(format!("cpus={}", cpus));
+        }
+        if let Some(cpuset) = &self.cpuset {
+            pairs.push(format!("cpuset={}", cpuset));
+        }
+        if let Some(memory) = self.memory_mib {
+            pairs.push(format!("memory={}", memory));
+        }
+        if let Some(reservation) = self.memory_reservation_mib {
+            pairs.push(format!("reservation={}", reservation));
+        }
+        if let Some(pids) = self.pids_limit {
+            pairs.push(format!("pids={}", pids));
+        }
+        f.write_str(&pairs.join(" "))
+    }
+}
+
+/// Addresses of the network the containers are connected to.
+/// The engine picks a subnet if it's not set.
+#[derive(Default, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct NetworkSettings {
+    /// The network driver, `bridge` by default
+    pub driver: Option<String>,
+    /// The IPv4 subnet in the CIDR notation, e.g. `172.30.0.0/16`
+    pub subnet: Option<String>,
+    /// The gateway of the IPv4 subnet
+    pub gateway: Option<String>,
+    /// The IPv6 subnet, IPv6 is enabled if it's set
+    pub ipv6_subnet: Option<String>,
+    /// Disables access to the outside world
+    pub internal: bool,
+}
+
+#[derive(Debug, Error)]
+#[error("Invalid network settings: {0}")]
+pub struct InvalidNetworkSettings(String);
+
+/// Parses settings in the form `driver=bridge subnet=172.30.0.0/16 gateway=172.30.0.1 ipv6=fd00:1::/64 internal`.
+/// Addresses are checked when the network is created. An empty string means the defaults.
+impl FromStr for NetworkSettings {
+    type Err = InvalidNetworkSettings;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut settings = NetworkSettings::default();
+        for pair in s.split_whitespace() {
+            if pair == "internal" {
+                settings.internal = true;
+                continue;
+            }
+            let (key, value) = pair
+                .split_once('=')
+                .ok_or_else(|| InvalidNetworkSettings(format!("`{}` is not a `key=value` pair", pair)))?;
+            let value = Some(value.to_string());
+            match key {
+                "driver" => settings.driver = value,
+                "subnet" => settings.subnet = value,
+                "gateway" => settings.gateway = value,
+                "ipv6" => settings.ipv6_subnet = value,
+                other => return Err(InvalidNetworkSettings(format!("unknown setting `{}`", other))),
+            }
+        }
+        if settings.gateway.is_some() && settings.subnet.is_none() {
+            return Err(InvalidNetworkSettings(
+                "the gateway is set without a subnet".to_string(),
+            ));
+        }
+        Ok(settings)
+    }
+}
+
+impl Display for NetworkSettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut pairs = Vec::new();
+        if let Some(driver) = &self.driver {
+            pairs.push(format!("driver={}", driver));
+        }
+        if let Some(subnet) = &self.subnet {
+            pairs.push(format!("subnet={}", subnet));
+        }
+        if let Some(gateway) = &self.gateway {
+            pairs.push(format!("gateway={}", gateway));
+        }
+        if let Some(subnet) = &self.ipv6_subnet {
+            pairs.push(format!("ipv6={}", subnet));
+        }
+        if self.internal {
+            pairs.push("internal".to_string());
+        }
+        f.write_str(&pairs.join(" "))
+    }
+}
+
+/// Services with configurable resource limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitedService {
+    BaseNode,
+    Sha3Miner,
+    MmProxy,
+    XmRig,
+}
+
+/// The registry and the tag of an image that replace the common ones
+#[derive(Default, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ImageOverride {
+    pub registry: Option<String>,
+    pub tag: Option<String>,
+}
+
+/// Credentials of a private registry. The Docker CLI config is used for registries without them.
+/// The secrets are read from the settings, but never written back: the launchpad keeps them apart.
+#[derive(Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct RegistryCredentials {
+    pub username: Option<String>,
+    #[serde(default, skip_serializing)]
+    pub password: Option<String>,
+    /// A token that is used instead of the username and the password
+    #[serde(default, skip_serializing)]
+    pub identity_token: Option<String>,
+}
+
+impl std::fmt::Debug for RegistryCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // The settings are logged, but the secrets shouldn't be
+        f.debug_struct("RegistryCredentials")
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "***"))
+            .field("identity_token", &self.identity_token.as_ref().map(|_| "***"))
+            .finish()
+    }
+}
+
 #[derive(Default, Debug, Serialize, Deserialize, Clone)]
 pub struct BaseNodeConfig {
     /// Should node be started in interactive mode.
     pub interactive: bool,
+    /// Resource limits of the base node container
+    #[serde(default)]
+    pub resources: ResourceLimits,
 }
 
 #[derive(Default, Debug, Serialize, Deserialize, Clone)]
@@ -41,6 +242,9 @@ pub struct XmRigConfig {
     pub monero_mining_address: String,
     /// The number of mining threads to employ
     pub num_mining_threads: Option<OptionUsizeWrapper>,
+    /// Resource limits of the XMRig container
+    #[serde(default)]
+    pub resources: ResourceLimits,
 }
 
 #[derive(Default, Debug, Serialize, Deserialize, Clone)]
@@ -49,6 +253,9 @@ pub struct Sha3MinerConfig {
     pub num_mining_threads: usize,
     /// The address that will accept Tari mining rewards
     pub wallet_payment_address: Option<String>,
+    /// Resource limits of the SHA3 miner container
+    #[serde(default)]
+    pub resources: ResourceLimits,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
@@ -64,6 +271,9 @@ pub struct MmProxyConfig {
     pub monero_use_auth: bool,
     /// The address that will accept Tari mining rewards
     pub wallet_payment_address: Option<String>,
+    /// Resource limits of the merge mining proxy container
+    #[serde(default)]
+    pub resources: ResourceLimits,
 }
 
 impl MmProxyConfig {
@@ -92,8 +302,17 @@ pub struct PersistentSettings {
     pub xmrig: Option<XmRigConfig>,
     /// The Docker registry to use to download images. By default we use ghcr.io/tari-project
     pub registry: Option<String>,
-    /// The docker tag to use. By default, we use 'latest'
+    /// The docker tag to use. By default, the tag of the Tari network is used
     pub tag: Option<String>,
+    /// Registries and tags of the images that differ from the common ones, by the name of the image
+    #[serde(default)]
+    pub images: BTreeMap<String, ImageOverride>,
+    /// Credentials of private registries by the host of a registry, like `registry.example.com`
+    #[serde(default)]
+    pub credentials: BTreeMap<String, RegistryCredentials>,
+    /// Addresses of the network of the containers
+    #[serde(default)]
+    pub network: NetworkSettings,
 }
 
 impl PersistentSettings {
@@ -113,6 +332,47 @@ impl PersistentSettings {
         self.xmrig = Some(XmRigConfig::default());
     }
 
+    /// The registry of a Tari image: the one of the image, the common one, or `None` to use the default.
+    pub fn tari_image_registry(&self, image: &str) -> Option<&str> {
+        self.image_registry(image).or_else(|| not_empty(&self.registry))
+    }
+
+    /// The tag of a Tari image: the one of the image, the common one, or the tag of the network.
+    pub fn tari_image_tag(&self, image: &str) -> &str {
+        self.image_tag(image)
+            .or_else(|| not_empty(&self.tag))
+            .unwrap_or_else(|| self.tari_network.default_tag())
+    }
+
+    /// The registry set for the image only.
+    pub fn image_registry(&self, image: &str) -> Option<&str> {
+        self.images.get(image).and_then(|image| not_empty(&image.registry))
+    }
+
+    /// The tag set for the image only.
+    pub fn image_tag(&self, image: &str) -> Option<&str> {
+        self.images.get(image).and_then(|image| not_empty(&image.tag))
+    }
+
+    pub fn resources(&self, service: LimitedService) -> Option<&ResourceLimits> {
+        match service {
+            LimitedService::BaseNode => self.base_node.as_ref().map(|c| &c.resources),
+            LimitedService::Sha3Miner => self.sha3_miner.as_ref().map(|c| &c.resources),
+            LimitedService::MmProxy => self.mm_proxy.as_ref().map(|c| &c.resources),
+            LimitedService::XmRig => self.xmrig.as_ref().map(|c| &c.resources),
+        }
+    }
+
+    /// Returns the limits of the service to edit. Creates the settings of the service if they don't exist.
+    pub fn resources_mut(&mut self, service: LimitedService) -> &mut ResourceLimits {
+        match service {
+            LimitedService::BaseNode => &mut self.base_node.get_or_insert_with(Default::default).resources,
+            LimitedService::Sha3Miner => &mut self.sha3_miner.get_or_insert_with(Default::default).resources,
+            LimitedService::MmProxy => &mut self.mm_proxy.get_or_insert_with(Default::default).resources,
+            LimitedService::XmRig => &mut self.xmrig.get_or_insert_with(Default::default).resources,
+        }
+    }
+
     pub fn set_monero_mining_address<S: Into<String>>(&mut self, address: S) {
         if self.xmrig.is_none() {
             self.new_xmrig_settings();
@@ -182,6 +442,11 @@ impl PersistentSettings {
     }
 }
 
+/// An empty value from the settings means the default.
+fn not_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().filter(|value| !value.is_empty())
+}
+
 #[derive(Debug, Clone, Error)]
 pub struct InvalidTariAddress(pub String);
 
@@ -231,7 +496,7 @@ impl Default for LaunchpadSettings {
 pub struct UnsupportedNetwork(String);
 
 /// Supported networks for the launchpad
-#[derive(Serialize, Debug, Deserialize, Clone, Copy)]
+#[derive(Serialize, Debug, Deserialize, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum TariNetwork {
     Igor,
     Nextnet,
@@ -257,6 +522,28 @@ impl TariNetwork {
             Self::Mainnet => "MAINNET",
         }
     }
+
+    /// Host ports of the containers of the network are shifted by the offset,
+    /// so the networks could run side by side. The default network keeps the standard ports.
+    /// The steps are wide enough to keep the shifted ports away from Tor Browser (9150) and Monero (18080-18089).
+    pub fn port_offset(self) -> u16 {
+        match self {
+            Self::Nextnet => 0,
+            Self::Mainnet => 2_000,
+            Self::Stagenet => 4_000,
+            Self::Igor => 6_000,
+        }
+    }
+
+    /// The tag of the Tari images built for the network.
+    pub fn default_tag(self) -> &'static str {
+        match self {
+            Self::Igor => "latest-igor",
+            Self::Nextnet => "latest-nextnet",
+            Self::Stagenet => "latest-stagenet",
+            Self::Mainnet => "latest",
+        }
+    }
 }
 
 /// Default network is Nextnet. This will change after mainnet launch
@@ -278,3 +565,87 @@ impl TryFrom<&str> for TariNetwork {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_resource_limits() {
+        let limits: ResourceLimits = "cpus=1.5 cpuset=0-3 memory=2048 reservation=1024 pids=256"
+            .parse()
+            .unwrap();
+        assert_eq!(limits.cpus, Some(1.5));
+        assert_eq!(limits.cpuset.as_deref(), Some("0-3"));
+        assert_eq!(limits.memory_mib, Some(2048));
+        assert_eq!(limits.memory_reservation_mib, Some(1024));
+        assert_eq!(limits.pids_limit, Some(256));
+        assert_eq!(
+            limits.to_string(),
+            "cpus=1.5 cpuset=0-3 memory=2048 reservation=1024 pids=256"
+        );
+    }
+
+    #[test]
+    fn parse_empty_resource_limits() {
+        let limits: ResourceLimits = "".parse().unwrap();
+        assert_eq!(limits, ResourceLimits::default());
+        assert_eq!(limits.to_string(), "");
+    }
+
+    #[test]
+    fn reject_invalid_resource_limits() {
+        assert!("cpus=0".parse::<ResourceLimits>().is_err());
+        assert!("cpus=NaN".parse::<ResourceLimits>().is_err());
+        assert!("cpuset=all".parse::<ResourceLimits>().is_err());
+        assert!("memory=1024 reservation=2048".parse::<ResourceLimits>().is_err());
+        assert!("swap=1024".parse::<ResourceLimits>().is_err());
+        assert!("memory".parse::<ResourceLimits>().is_err());
+    }
+
+    #[test]
+    fn parse_network_settings() {
+        let settings: NetworkSettings = "subnet=172.30.0.0/16 gateway=172.30.0.1 ipv6=fd00:1::/64 internal"
+            .parse()
+            .unwrap();
+        assert_eq!(settings.driver, None);
+        assert_eq!(settings.subnet.as_deref(), Some("172.30.0.0/16"));
+        assert_eq!(settings.gateway.as_deref(), Some("172.30.0.1"));
+        assert_eq!(settings.ipv6_subnet.as_deref(), Some("fd00:1::/64"));
+        assert!(settings.internal);
+        assert_eq!(
+            settings.to_string(),
+            "subnet=172.30.0.0/16 gateway=172.30.0.1 ipv6=fd00:1::/64 internal"
+        );
+        assert_eq!("".parse::<NetworkSettings>().unwrap(), NetworkSettings::default());
+    }
+
+    #[test]
+    fn reject_invalid_network_settings() {
+        assert!("gateway=172.30.0.1".parse::<NetworkSettings>().is_err());
+        assert!("mtu=1400".parse::<NetworkSettings>().is_err());
+        assert!("subnet".parse::<NetworkSettings>().is_err());
+    }
+
+    #[test]
+    fn resolve_image_tags() {
+        let mut settings = PersistentSettings {
+            tari_network: TariNetwork::Mainnet,
+            ..Default::default()
+        };
+        assert_eq!(settings.tari_image_registry("minotari_node"), None);
+        assert_eq!(settings.tari_image_tag("minotari_node"), "latest");
+
+        settings.registry = Some("quay.io/tarilabs".to_string());
+        settings.tag = Some(String::new());
+        settings.images.insert("tor".to_string(), ImageOverride {
+            registry: None,
+            tag: Some("v0.4.8".to_string()),
+        });
+        assert_eq!(settings.tari_image_registry("minotari_node"), Some("quay.io/tarilabs"));
+        assert_eq!(settings.tari_image_tag("minotari_node"), "latest");
+        assert_eq!(settings.tari_image_registry("tor"), Some("quay.io/tarilabs"));
+        assert_eq!(settings.tari_image_tag("tor"), "v0.4.8");
+        assert_eq!(settings.image_registry("tor"), None);
+    }
+}
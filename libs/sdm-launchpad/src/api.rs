@@ -72,6 +72,13 @@ impl SdmWorker {
                 Reaction::Delta(delta) => {
                     state.apply(delta);
                 },
+                Reaction::Stopped
+                | Reaction::Logs(_)
+                | Reaction::Exec { .. }
+                | Reaction::Console { .. }
+                | Reaction::Snapshot(_)
+                | Reaction::VolumeCleared { .. }
+                | Reaction::OrphansRemoved { .. } => {},
             }
         }
     }
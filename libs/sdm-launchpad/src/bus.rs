@@ -21,20 +21,38 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
-use std::path::PathBuf;
+use std::{
+    collections::{BTreeMap, HashMap},
+    future::Future,
+    path::{Path, PathBuf},
+    pin::Pin,
+};
 
 use anyhow::Error;
+use futures::{
+    future::{self, FutureExt},
+    StreamExt,
+};
 use log::*;
 use tari_launchpad_protocol::{
-    container::{TaskDelta, TaskId, TaskProgress, TaskState, TaskStatus},
-    launchpad::{Action, LaunchpadAction, LaunchpadDelta, LaunchpadState, Reaction},
-    settings::PersistentSettings,
+    config::LaunchpadConfig,
+    container::{ConsoleEvent, ExecOutput, LogQuery, TaskDelta, TaskId, TaskProgress, TaskState, TaskStatus},
+    errors::ErrorRecord,
+    launchpad::{Action, LaunchpadAction, LaunchpadDelta, LaunchpadState, Reaction, SnapshotResult},
+    settings::{PersistentSettings, TariNetwork},
 };
-use tari_sdm::{ids::ManagedTask, utils::create_password, Report, ReportEnvelope, SdmScope};
+use tari_sdm::{ids::ManagedTask, runtime::Attachment, utils::create_password, Report, ReportEnvelope, SdmScope};
 use tari_sdm_assets::configurator::Configurator;
-use tokio::{select, sync::mpsc};
+use tokio::{
+    io::{AsyncWrite, AsyncWriteExt},
+    select,
+    sync::mpsc,
+    task::JoinHandle,
+};
 
 use crate::{
+    credentials::RegistrySecrets,
+    log_archive::{ArchiveWriter, LogArchive, RotationPolicy},
     node_grpc::NodeGrpc,
     resources::{
         config::{LaunchpadProtocol, LaunchpadSettings},
@@ -64,11 +82,46 @@ impl LaunchpadBus {
 
 pub struct LaunchpadWorker {
     state: LaunchpadState,
-    scope: SdmScope<LaunchpadProtocol>,
+    /// Scopes of the networks selected since the start. The other networks keep running
+    /// in the background when the selected network changes.
+    scopes: BTreeMap<TariNetwork, NetworkScope>,
     in_rx: mpsc::UnboundedReceiver<Action>,
     // TODO: Share the sender with the wallet
     out_tx: mpsc::UnboundedSender<Reaction>,
+    /// Changes of the networks reported by the base node gRPC pollers.
+    deltas_tx: mpsc::UnboundedSender<(TariNetwork, LaunchpadDelta)>,
+    deltas_rx: mpsc::UnboundedReceiver<(TariNetwork, LaunchpadDelta)>,
+    /// Set when the shutdown was requested.
+    stopping: bool,
+    /// Terminals of containers of the selected network attached by the user.
+    consoles: HashMap<TaskId, Console>,
+    /// Secrets of the registries, they are kept out of the state that is sent to the front ends.
+    secrets: RegistrySecrets,
+}
+
+/// The containers, volumes and networks of a Tari network. They are named after the network
+/// and published on the ports shifted by the offset of the network.
+struct NetworkScope {
+    scope: SdmScope<LaunchpadProtocol>,
     node_grpc: Option<NodeGrpc>,
+    /// The host port of the base node gRPC, it's reported by the task.
+    node_grpc_port: u16,
+    /// Keeps the logs of containers on disk, it's created when the data directory is known.
+    archive: Option<ArchiveWriter>,
+}
+
+struct Console {
+    input: Pin<Box<dyn AsyncWrite + Send>>,
+    /// Forwards the output of the terminal to the UI.
+    forwarder: JoinHandle<()>,
+}
+
+impl Console {
+    async fn write(&mut self, input: &str) -> Result<(), Error> {
+        self.input.write_all(input.as_bytes()).await?;
+        self.input.flush().await?;
+        Ok(())
+    }
 }
 
 impl LaunchpadWorker {
@@ -78,31 +131,19 @@ impl LaunchpadWorker {
         in_rx: mpsc::UnboundedReceiver<Action>,
         out_tx: mpsc::UnboundedSender<Reaction>,
     ) -> Result<(), Error> {
-        // TODO: This should respect the configured network and not be hardcoded
-        let mut scope = SdmScope::connect("nextnet")?;
-        scope.add_network(networks::LocalNet::default())?;
-        scope.add_volume(volumes::SharedVolume::default())?;
-        scope.add_volume(volumes::SharedGrafanaVolume::default())?;
-
-        scope.add_image(images::Tor::default())?;
-        scope.add_image(images::TariBaseNode::default())?;
-        scope.add_image(images::TariSha3Miner::default())?;
-
-        scope.add_image(images::Loki::default())?;
-        scope.add_image(images::Promtail::default())?;
-        scope.add_image(images::Grafana::default())?;
-
-        scope.add_image(images::MmProxy::default())?;
-        scope.add_image(images::XMRig::default())?;
-
         let state = LaunchpadState::default();
+        let (deltas_tx, deltas_rx) = mpsc::unbounded_channel();
 
         let worker = LaunchpadWorker {
             state,
-            scope,
+            scopes: BTreeMap::new(),
             in_rx,
             out_tx,
-            node_grpc: None,
+            deltas_tx,
+            deltas_rx,
+            stopping: false,
+            consoles: HashMap::new(),
+            secrets: RegistrySecrets::default(),
         };
         worker.entrypoint().await;
         Ok(())
@@ -111,8 +152,11 @@ impl LaunchpadWorker {
     async fn entrypoint(mut self) {
         self.load_configuration().await.ok();
         // TODO: Watch for the config file changes
-        let config = self.state.config.clone();
-        self.scope.set_config(Some(config)).ok();
+        let network = self.state.network().unwrap_or_default();
+        if let Err(err) = self.add_scope(network) {
+            error!("Can't start the scope of {}: {}", network.lower_case(), err);
+            self.add_error(err.to_string());
+        }
         loop {
             if let Err(err) = self.step().await {
                 error!("Bus failed: {}", err);
@@ -141,10 +185,11 @@ impl LaunchpadWorker {
         let mut configurator = Configurator::init()?;
         let data_directory = configurator.base_path().clone();
         configurator.init_configuration(false).await?;
-        let saved_settings = Self::load_settings(data_directory.clone()).await.unwrap_or_else(|| {
+        let mut saved_settings = Self::load_settings(data_directory.clone()).await.unwrap_or_else(|| {
             warn!("Can't parse the settings file. Reverting to defaults.");
             PersistentSettings::default()
         });
+        self.load_secrets(&data_directory, &mut saved_settings).await;
         let config = LaunchpadSettings {
             data_directory,
             with_monitoring: true,
@@ -156,6 +201,111 @@ impl LaunchpadWorker {
         Ok(())
     }
 
+    /// Reads the secrets of the registries and takes them out of the settings.
+    /// The secrets that were kept in the settings file are moved to the secrets file.
+    async fn load_secrets(&mut self, data_directory: &Path, settings: &mut PersistentSettings) {
+        let path = RegistrySecrets::path(data_directory);
+        let loaded = RegistrySecrets::load(&path).await;
+        if let Err(err) = &loaded {
+            warn!("Can't read the registry credentials: {err}");
+        }
+        self.secrets = loaded.as_ref().cloned().unwrap_or_default();
+        // The broken secrets file is not overwritten
+        if self.secrets.take_from(settings) && loaded.is_ok() {
+            let moved = match self.secrets.save(&path).await {
+                Ok(()) => Self::write_settings(data_directory, settings).await,
+                Err(err) => Err(err),
+            };
+            if let Err(err) = moved {
+                warn!("Can't move the registry credentials out of the settings: {err}");
+            }
+        }
+    }
+
+    /// Creates the scope of the network and starts its tasks with the current config.
+    fn add_scope(&mut self, network: TariNetwork) -> Result<(), Error> {
+        info!("Starting the scope of {}", network.lower_case());
+        let mut scope = SdmScope::connect(network.lower_case())?;
+        scope.set_version(env!("CARGO_PKG_VERSION"));
+        scope.set_port_offset(network.port_offset());
+        scope.add_network(networks::LocalNet::default())?;
+        scope.add_volume(volumes::SharedVolume::default())?;
+        scope.add_volume(volumes::SharedGrafanaVolume::default())?;
+
+        scope.add_image(images::Tor::default())?;
+        scope.add_image(images::TariBaseNode::default())?;
+        scope.add_image(images::TariSha3Miner::default())?;
+
+        scope.add_image(images::Loki::default())?;
+        scope.add_image(images::Promtail::default())?;
+        scope.add_image(images::Grafana::default())?;
+
+        scope.add_image(images::MmProxy::default())?;
+        scope.add_image(images::XMRig::default())?;
+
+        if let Err(err) = scope.validate() {
+            // Tasks that depend on the invalid ones will never start
+            error!("Invalid dependencies of the tasks: {}", err);
+            self.add_error(err.to_string());
+        }
+        self.remove_orphans(network, &scope);
+        scope.set_config(Some(self.network_config(network)))?;
+        let archive = self.state.config.settings.as_ref().map(|settings| {
+            let dir = settings.data_directory.join("log").join("containers");
+            ArchiveWriter::spawn(LogArchive::new(
+                dir.join(network.lower_case()),
+                RotationPolicy::default(),
+            ))
+        });
+        let scope = NetworkScope {
+            scope,
+            node_grpc: None,
+            node_grpc_port: images::BASE_NODE_GRPC_PORT.saturating_add(network.port_offset()),
+            archive,
+        };
+        self.scopes.insert(network, scope);
+        Ok(())
+    }
+
+    /// The config of the selected network, or the one of a network running in the background.
+    fn network_config(&self, network: TariNetwork) -> LaunchpadConfig {
+        let mut config = self.state.config.clone();
+        if let Some(settings) = config.settings.as_mut() {
+            self.secrets.fill(&mut settings.saved_settings);
+        }
+        if self.state.network() != Some(network) {
+            config.session = self
+                .state
+                .networks
+                .get(&network)
+                .map(|state| state.session.clone())
+                .unwrap_or_default();
+            if let Some(settings) = config.settings.as_mut() {
+                settings.saved_settings.tari_network = network;
+            }
+        }
+        config
+    }
+
+    fn configure(&mut self, network: TariNetwork) -> Result<(), Error> {
+        let config = self.network_config(network);
+        if let Some(scope) = self.scopes.get_mut(&network) {
+            scope.scope.set_config(Some(config))?;
+        }
+        Ok(())
+    }
+
+    fn selected_scope(&mut self) -> Result<&mut NetworkScope, Error> {
+        let network = self.state.network().unwrap_or_default();
+        self.scope_of(network)
+    }
+
+    fn scope_of(&mut self, network: TariNetwork) -> Result<&mut NetworkScope, Error> {
+        self.scopes
+            .get_mut(&network)
+            .ok_or_else(|| Error::msg(format!("The scope of {} is not started", network.lower_case())))
+    }
+
     async fn step(&mut self) -> Result<(), Error> {
         select! {
             // TODO: Watch config changes here
@@ -164,15 +314,33 @@ impl LaunchpadWorker {
                     self.process_incoming(action).await?;
                 }
             }
-            report = self.scope.recv() => {
+            (network, report) = Self::recv_report(&mut self.scopes) => {
                 if let Some(report) = report {
-                    self.process_report(report).await?;
+                    self.process_report(network, report).await?;
                 }
             }
+            Some((network, delta)) = self.deltas_rx.recv() => {
+                self.apply_network_delta(network, delta);
+            }
         }
         Ok(())
     }
 
+    /// Waits for a report of any of the scopes.
+    async fn recv_report(
+        scopes: &mut BTreeMap<TariNetwork, NetworkScope>,
+    ) -> (TariNetwork, Option<ReportEnvelope<LaunchpadProtocol>>) {
+        let receivers: Vec<_> = scopes
+            .iter_mut()
+            .map(|(network, scope)| scope.scope.recv().map(|report| (*network, report)).boxed())
+            .collect();
+        if receivers.is_empty() {
+            future::pending().await
+        } else {
+            future::select_all(receivers).await.0
+        }
+    }
+
     async fn process_incoming(&mut self, incoming: Action) -> Result<(), Error> {
         match incoming {
             Action::Action(action) => self.process_action(action).await,
@@ -187,16 +355,342 @@ impl LaunchpadWorker {
             },
             LaunchpadAction::ChangeSession(session) => {
                 self.apply_delta(LaunchpadDelta::UpdateSession(session));
-                let config = self.state.config.clone();
-                self.scope.set_config(Some(config))?;
+                let network = self.state.network().unwrap_or_default();
+                self.configure(network)?;
             },
             LaunchpadAction::SaveSettings(settings) => {
                 self.save_settings(*settings).await?;
+                let network = self.state.network().unwrap_or_default();
+                if !self.scopes.contains_key(&network) {
+                    self.add_scope(network)?;
+                }
+                // Applies the new images and limits to the tasks of all the networks
+                let networks: Vec<_> = self.scopes.keys().copied().collect();
+                for network in networks {
+                    self.configure(network)?;
+                }
+            },
+            LaunchpadAction::Shutdown => {
+                self.shutdown();
+            },
+            LaunchpadAction::ExportGraph => {
+                self.export_graph().await?;
+            },
+            LaunchpadAction::QueryLogs(query) => {
+                self.query_logs(query)?;
+            },
+            LaunchpadAction::Exec { task_id, command } => {
+                self.exec(task_id, command);
+            },
+            LaunchpadAction::AttachConsole { task_id, width, height } => {
+                self.attach_console(task_id, width, height).await;
+            },
+            LaunchpadAction::ConsoleInput { task_id, input } => {
+                self.console_input(task_id, input).await;
+            },
+            LaunchpadAction::DetachConsole { task_id } => {
+                self.detach_console(task_id);
+            },
+            LaunchpadAction::ExportSnapshot { network, path } => {
+                self.export_snapshot(network, path)?;
+            },
+            LaunchpadAction::RestoreSnapshot { network, path } => {
+                self.restore_snapshot(network, path)?;
+            },
+            LaunchpadAction::RemoveVolume { task_id } => {
+                info!("Removing the volume of {}", task_id);
+                let remove = self.selected_scope()?.scope.remove_volume(&task_id);
+                self.spawn_clearing(task_id, remove);
+            },
+            LaunchpadAction::ResetChainData { network } => {
+                info!("Removing the blockchain of {}", network.lower_case());
+                let task_id = volumes::SharedVolume::id();
+                let clear = self
+                    .scope_of(network)?
+                    .scope
+                    .clear_volume(&task_id, network.lower_case());
+                self.spawn_clearing(task_id, clear);
+            },
+            LaunchpadAction::RemoveOrphanedVolumes => {
+                self.remove_orphaned_volumes()?;
             },
         }
         Ok(())
     }
 
+    /// Stops the scopes in the background, since the reports have to be processed meanwhile.
+    fn shutdown(&mut self) {
+        if self.stopping {
+            return;
+        }
+        info!("Stopping all the containers");
+        self.stopping = true;
+        let stop = future::try_join_all(self.scopes.values().map(|scope| scope.scope.stop()));
+        let out_tx = self.out_tx.clone();
+        tokio::spawn(async move {
+            if let Err(err) = stop.await {
+                error!("Can't stop the containers: {}", err);
+            }
+            info!("All the containers are stopped");
+            out_tx.send(Reaction::Stopped).ok();
+        });
+    }
+
+    /// Removes containers and networks of the scope left by crashed or previous versions of the launchpad.
+    /// Orphaned volumes are reported to the user, who decides whether to remove them.
+    fn remove_orphans(&self, network: TariNetwork, scope: &SdmScope<LaunchpadProtocol>) {
+        let reconcile = scope.reconcile();
+        let deltas_tx = self.deltas_tx.clone();
+        tokio::spawn(async move {
+            match reconcile.await {
+                Ok(reconciliation) => {
+                    if !reconciliation.removed.is_empty() {
+                        info!("Orphaned resources are removed: {}", reconciliation.removed.join(", "));
+                    }
+                    if !reconciliation.orphaned_volumes.is_empty() {
+                        let volumes = reconciliation.orphaned_volumes.join(", ");
+                        warn!("Orphaned volumes are kept: {}", volumes);
+                        let record = ErrorRecord {
+                            datetime: chrono::Local::now().naive_local(),
+                            message: format!("Volumes that are not used anymore are kept with their data: {volumes}"),
+                        };
+                        deltas_tx.send((network, LaunchpadDelta::AddError(record))).ok();
+                    }
+                },
+                Err(err) => {
+                    warn!("Can't remove orphaned resources: {}", err);
+                },
+            }
+        });
+    }
+
+    fn remove_orphaned_volumes(&mut self) -> Result<(), Error> {
+        info!("Removing the orphaned volumes");
+        let remove = self.selected_scope()?.scope.remove_orphaned_volumes();
+        let out_tx = self.out_tx.clone();
+        tokio::spawn(async move {
+            let reaction = match remove.await {
+                Ok(removed) => Reaction::OrphansRemoved { removed, error: None },
+                Err(err) => {
+                    error!("Can't remove the orphaned volumes: {}", err);
+                    Reaction::OrphansRemoved {
+                        removed: Vec::new(),
+                        error: Some(err.to_string()),
+                    }
+                },
+            };
+            out_tx.send(reaction).ok();
+        });
+        Ok(())
+    }
+
+    /// The archive is read by its writer thread, since it could take a while to decompress the files.
+    fn query_logs(&mut self, query: LogQuery) -> Result<(), Error> {
+        let task_id = query.task_id.clone();
+        let page = self
+            .selected_scope()?
+            .archive
+            .as_ref()
+            .ok_or_else(|| Error::msg("Can't query the logs: the archive is not configured"))?
+            .query(query);
+        let out_tx = self.out_tx.clone();
+        tokio::spawn(async move {
+            match page.await {
+                Ok(page) => {
+                    out_tx.send(Reaction::Logs(page)).ok();
+                },
+                Err(err) => {
+                    error!("Can't read the logs of {}: {}", task_id, err);
+                },
+            }
+        });
+        Ok(())
+    }
+
+    /// The chain data of every network is kept in its own directory of the volume of the network.
+    /// The whole volume of the selected network is exported if the network is not set.
+    fn export_snapshot(&mut self, network: Option<TariNetwork>, path: PathBuf) -> Result<(), Error> {
+        info!("Exporting the blockchain to {}", path.display());
+        let dir = network.map(|network| network.lower_case().to_string());
+        let scope = match network {
+            Some(network) => self.scope_of(network)?,
+            None => self.selected_scope()?,
+        };
+        let export = scope
+            .scope
+            .export_volume(&volumes::SharedVolume::id(), dir, path.clone());
+        self.spawn_snapshot(export, SnapshotResult::Exported(path));
+        Ok(())
+    }
+
+    fn restore_snapshot(&mut self, network: Option<TariNetwork>, path: PathBuf) -> Result<(), Error> {
+        info!("Restoring the blockchain from {}", path.display());
+        let dir = network.map(|network| network.lower_case().to_string());
+        let scope = match network {
+            Some(network) => self.scope_of(network)?,
+            None => self.selected_scope()?,
+        };
+        let import = scope
+            .scope
+            .import_volume(&volumes::SharedVolume::id(), dir, path.clone());
+        self.spawn_snapshot(import, SnapshotResult::Restored(path));
+        Ok(())
+    }
+
+    /// Copies the volume in the background, since the reports have to be processed meanwhile.
+    fn spawn_snapshot<F>(&mut self, transfer: F, done: SnapshotResult)
+    where
+        F: Future<Output = Result<(), Error>> + Send + 'static,
+    {
+        let out_tx = self.out_tx.clone();
+        tokio::spawn(async move {
+            let result = match transfer.await {
+                Ok(()) => done,
+                Err(err) => {
+                    error!("Can't transfer the snapshot: {}", err);
+                    SnapshotResult::Failed(err.to_string())
+                },
+            };
+            out_tx.send(Reaction::Snapshot(result)).ok();
+        });
+    }
+
+    fn spawn_clearing<F>(&mut self, task_id: TaskId, clearing: F)
+    where
+        F: Future<Output = Result<(), Error>> + Send + 'static,
+    {
+        let out_tx = self.out_tx.clone();
+        tokio::spawn(async move {
+            let error = clearing.await.err().map(|err| {
+                error!("Can't clear the volume of {}: {}", task_id, err);
+                err.to_string()
+            });
+            out_tx.send(Reaction::VolumeCleared { task_id, error }).ok();
+        });
+    }
+
+    /// Forwards the output of the command in the background. Failures are reported
+    /// to the requester as `ExecOutput::Error` instead of stopping the worker.
+    fn exec(&mut self, task_id: TaskId, command: Vec<String>) {
+        info!("Executing {:?} in {}", command, task_id);
+        let out_tx = self.out_tx.clone();
+        let send = move |task_id: &TaskId, output| {
+            let reaction = Reaction::Exec {
+                task_id: task_id.clone(),
+                output,
+            };
+            out_tx.send(reaction).ok();
+        };
+        match self
+            .selected_scope()
+            .and_then(|scope| scope.scope.exec(&task_id, command))
+        {
+            Ok(mut stream) => {
+                tokio::spawn(async move {
+                    while let Some(res) = stream.next().await {
+                        let output = res.unwrap_or_else(|err| ExecOutput::Error(err.to_string()));
+                        send(&task_id, output);
+                    }
+                });
+            },
+            Err(err) => {
+                send(&task_id, ExecOutput::Error(err.to_string()));
+            },
+        }
+    }
+
+    /// The output is forwarded until the container stops or the console is detached.
+    async fn attach_console(&mut self, task_id: TaskId, width: u16, height: u16) {
+        if let Some(console) = self.consoles.remove(&task_id) {
+            console.forwarder.abort();
+        }
+        info!("Attaching to the console of {}", task_id);
+        let attachment = match self.selected_scope() {
+            Ok(scope) => scope.scope.attach(&task_id, width, height).await,
+            Err(err) => Err(err),
+        };
+        match attachment {
+            Ok(Attachment { mut output, input }) => {
+                self.send_console(&task_id, ConsoleEvent::Attached);
+                let out_tx = self.out_tx.clone();
+                let id = task_id.clone();
+                let forwarder = tokio::spawn(async move {
+                    while let Some(res) = output.next().await {
+                        let event = match res {
+                            Ok(text) => ConsoleEvent::Output(text),
+                            Err(err) => ConsoleEvent::Error(err.to_string()),
+                        };
+                        let task_id = id.clone();
+                        out_tx.send(Reaction::Console { task_id, event }).ok();
+                    }
+                    let event = ConsoleEvent::Detached;
+                    out_tx.send(Reaction::Console { task_id: id, event }).ok();
+                });
+                self.consoles.insert(task_id, Console { input, forwarder });
+            },
+            Err(err) => {
+                self.send_console(&task_id, ConsoleEvent::Error(err.to_string()));
+            },
+        }
+    }
+
+    async fn console_input(&mut self, task_id: TaskId, input: String) {
+        let res = match self.consoles.get_mut(&task_id) {
+            Some(console) => console.write(&input).await,
+            None => Err(Error::msg(format!("The console of {} is not attached", task_id))),
+        };
+        if let Err(err) = res {
+            warn!("Can't write to the console of {}: {}", task_id, err);
+            self.send_console(&task_id, ConsoleEvent::Error(err.to_string()));
+        }
+    }
+
+    /// Only the connection is closed, the process in the container keeps running.
+    fn detach_console(&mut self, task_id: TaskId) {
+        if let Some(console) = self.consoles.remove(&task_id) {
+            info!("Detaching from the console of {}", task_id);
+            console.forwarder.abort();
+        }
+        self.send_console(&task_id, ConsoleEvent::Detached);
+    }
+
+    /// The consoles are attached to the containers of the selected network only.
+    fn detach_consoles(&mut self) {
+        let task_ids: Vec<_> = self.consoles.keys().cloned().collect();
+        for task_id in task_ids {
+            self.detach_console(task_id);
+        }
+    }
+
+    fn send_console(&mut self, task_id: &TaskId, event: ConsoleEvent) {
+        let task_id = task_id.clone();
+        self.send(Reaction::Console { task_id, event });
+    }
+
+    async fn export_graph(&mut self) -> Result<(), Error> {
+        let mut path = self
+            .state
+            .config
+            .settings
+            .as_ref()
+            .map(|s| s.data_directory.clone())
+            .ok_or_else(|| Error::msg("Can't export the graph: no settings are attached to the config"))?;
+        path.push("log");
+        let network = self.state.network().unwrap_or_default();
+        let graph = self
+            .scopes
+            .get(&network)
+            .map(|scope| scope.scope.graph())
+            .ok_or_else(|| Error::msg("Can't export the graph: the scope is not started"))?;
+        let states = &self.state.containers;
+        let dot = graph.to_dot(states);
+        tokio::fs::write(path.join("dependencies.dot"), dot).await?;
+        let json = graph.to_json(states)?;
+        tokio::fs::write(path.join("dependencies.json"), json).await?;
+        info!("The dependency graph is exported to {}", path.display());
+        Ok(())
+    }
+
     fn apply_progress_update(&mut self, task_id: &TaskId, progress: &TaskProgress) {
         self.state
             .containers
@@ -217,34 +711,73 @@ impl LaunchpadWorker {
         self.send(reaction);
     }
 
+    /// Changes of the selected network are applied as is, to keep them simple for the front ends.
+    fn apply_network_delta(&mut self, network: TariNetwork, delta: LaunchpadDelta) {
+        if self.state.network().unwrap_or_default() == network || matches!(delta, LaunchpadDelta::AddError(_)) {
+            self.apply_delta(delta);
+        } else {
+            let delta = Box::new(delta);
+            self.apply_delta(LaunchpadDelta::Network { network, delta });
+        }
+    }
+
+    fn add_error(&mut self, message: String) {
+        let record = ErrorRecord {
+            datetime: chrono::Local::now().naive_local(),
+            message,
+        };
+        self.apply_delta(LaunchpadDelta::AddError(record));
+    }
+
     fn send(&mut self, out: Reaction) {
         if let Err(err) = self.out_tx.send(out) {
             error!("Can't send an outgoing message: {}", err);
         }
     }
 
-    async fn save_settings(&mut self, new_settings: PersistentSettings) -> Result<(), Error> {
+    async fn write_settings(data_directory: &Path, settings: &PersistentSettings) -> Result<(), Error> {
+        let path = data_directory.join("config").join("settings.toml");
+        let data = toml::to_string(settings).map_err(|e| Error::msg(format!("Can't save the settings: {e}")))?;
+        tokio::fs::write(path, data).await?;
+        Ok(())
+    }
+
+    async fn save_settings(&mut self, mut new_settings: PersistentSettings) -> Result<(), Error> {
         debug!("Saving the settings");
-        let mut path = self
+        let data_directory = self
             .state
             .config
             .settings
             .as_ref()
             .map(|s| s.data_directory.clone())
             .ok_or_else(|| Error::msg("Can't save the settings: no settings are attached to the config"))?;
-        path.push("config");
-        path.push("settings.toml");
+        if self.secrets.take_from(&mut new_settings) {
+            self.secrets.save(&RegistrySecrets::path(&data_directory)).await?;
+        }
         debug!("Stored settings: {new_settings:?}");
-        let data = toml::to_string(&new_settings).map_err(|e| Error::msg(format!("Can't save the settings: {e}")))?;
-        tokio::fs::write(path, data).await?;
-        if let Some(settings) = self.state.config.settings.as_mut() {
-            // We just checked that this exists above
-            settings.saved_settings = new_settings
+        Self::write_settings(&data_directory, &new_settings).await?;
+        if let Some(mut settings) = self.state.config.settings.clone() {
+            let switched = settings.saved_settings.tari_network != new_settings.tari_network;
+            settings.saved_settings = new_settings;
+            if switched {
+                self.detach_consoles();
+            }
+            self.apply_delta(LaunchpadDelta::UpdateConfig(settings));
+            if switched {
+                // The front ends have already changed the network in their copy of the settings,
+                // so they can't swap the states of the networks themselves.
+                let state = self.state.clone();
+                self.send(Reaction::State(state));
+            }
         }
         Ok(())
     }
 
-    async fn process_report(&mut self, report: ReportEnvelope<LaunchpadProtocol>) -> Result<(), Error> {
+    async fn process_report(
+        &mut self,
+        network: TariNetwork,
+        report: ReportEnvelope<LaunchpadProtocol>,
+    ) -> Result<(), Error> {
         // TODO: Convert to the `LaunchpadDelta` and apply
         match report.details {
             Report::State(state) => {
@@ -252,20 +785,38 @@ impl LaunchpadWorker {
                     id: report.task_id,
                     state,
                 };
-                self.apply_delta(state);
+                self.apply_network_delta(network, state);
             },
             Report::Delta(delta) => {
                 if report.task_id == images::TariBaseNode::id() {
-                    self.check_node_grpc(&delta);
+                    self.check_node_grpc(network, &delta);
                 }
                 if let TaskDelta::UpdateStatus(TaskStatus::Progress(progress)) = &delta {
-                    self.apply_progress_update(&report.task_id, progress);
+                    if self.state.network().unwrap_or_default() == network {
+                        self.apply_progress_update(&report.task_id, progress);
+                    }
+                }
+                let archive = self.scopes.get(&network).and_then(|scope| scope.archive.as_ref());
+                if let (TaskDelta::LogRecord(record), Some(archive)) = (&delta, archive) {
+                    archive.append(report.task_id.clone(), record.clone());
                 }
                 let delta = LaunchpadDelta::TaskDelta {
                     id: report.task_id,
                     delta,
                 };
-                self.apply_delta(delta);
+                self.apply_network_delta(network, delta);
+            },
+            Report::Ports(ports) => {
+                if report.task_id == images::TariBaseNode::id() {
+                    let grpc_port = ports
+                        .iter()
+                        .find(|port| port.container_port == images::BASE_NODE_GRPC_PORT)
+                        .map(|port| port.host_port);
+                    // The port set by `add_scope` is kept, it's shifted by the offset of the network
+                    if let Some(grpc_port) = grpc_port {
+                        self.scope_of(network)?.node_grpc_port = grpc_port;
+                    }
+                }
             },
             Report::Extras(_) => {},
         }
@@ -273,17 +824,21 @@ impl LaunchpadWorker {
     }
 
     // Only called if the task is the base node task
-    fn check_node_grpc(&mut self, delta: &TaskDelta) {
+    fn check_node_grpc(&mut self, network: TariNetwork, delta: &TaskDelta) {
+        let deltas_tx = self.deltas_tx.clone();
+        let Some(scope) = self.scopes.get_mut(&network) else {
+            return;
+        };
         if let TaskDelta::UpdateStatus(status) = delta {
             if status.is_active() {
-                if self.node_grpc.is_none() {
-                    let grpc = NodeGrpc::new(self.out_tx.clone());
-                    self.node_grpc = Some(grpc);
+                if scope.node_grpc.is_none() {
+                    let grpc = NodeGrpc::new(deltas_tx, network, scope.node_grpc_port);
+                    scope.node_grpc = Some(grpc);
                 }
             } else {
                 // Detaches grpc instances that closes a channel
-                if self.node_grpc.is_some() {
-                    self.node_grpc.take();
+                if scope.node_grpc.is_some() {
+                    scope.node_grpc.take();
                 }
             }
         }
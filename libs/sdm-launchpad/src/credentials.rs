@@ -0,0 +1,125 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! The secrets of private registries.
+//!
+//! The settings are broadcast to the front ends and stored as plain TOML, so the passwords and the tokens of
+//! registries are moved out of them into `{root}/config/credentials.toml` that is readable by the owner only.
+//! They are put back into the settings that are passed to the images only.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Error;
+use serde::{Deserialize, Serialize};
+use tari_launchpad_protocol::settings::PersistentSettings;
+
+/// The secrets of a registry, the username stays in the settings.
+#[derive(Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegistrySecret {
+    pub password: Option<String>,
+    pub identity_token: Option<String>,
+}
+
+impl fmt::Debug for RegistrySecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RegistrySecret")
+            .field("password", &self.password.as_ref().map(|_| "***"))
+            .field("identity_token", &self.identity_token.as_ref().map(|_| "***"))
+            .finish()
+    }
+}
+
+/// Secrets of the registries by the host of a registry.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RegistrySecrets(BTreeMap<String, RegistrySecret>);
+
+impl RegistrySecrets {
+    /// The path of the secrets file in the data directory.
+    pub fn path(data_directory: &Path) -> PathBuf {
+        data_directory.join("config").join("credentials.toml")
+    }
+
+    /// Reads the secrets file. There are no secrets if it doesn't exist.
+    pub async fn load(path: &Path) -> Result<Self, Error> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let data = tokio::fs::read_to_string(path).await?;
+        let secrets = toml::from_str(&data)?;
+        Ok(secrets)
+    }
+
+    /// Writes the secrets to a file that only the owner can read.
+    pub async fn save(&self, path: &Path) -> Result<(), Error> {
+        let data = toml::to_string(self)?;
+        let mut options = tokio::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        let mut file = options.open(path).await?;
+        #[cfg(unix)]
+        {
+            // The mode is applied to new files only
+            use std::{fs::Permissions, os::unix::fs::PermissionsExt};
+            file.set_permissions(Permissions::from_mode(0o600)).await?;
+        }
+        tokio::io::AsyncWriteExt::write_all(&mut file, data.as_bytes()).await?;
+        file.sync_all().await?;
+        Ok(())
+    }
+
+    /// Moves the secrets out of the settings. Returns `true` if there were any.
+    pub fn take_from(&mut self, settings: &mut PersistentSettings) -> bool {
+        let mut taken = false;
+        for (registry, credentials) in &mut settings.credentials {
+            let password = credentials.password.take();
+            let identity_token = credentials.identity_token.take();
+            if password.is_none() && identity_token.is_none() {
+                continue;
+            }
+            let secret = self.0.entry(registry.clone()).or_default();
+            if password.is_some() {
+                secret.password = password;
+            }
+            if identity_token.is_some() {
+                secret.identity_token = identity_token;
+            }
+            taken = true;
+        }
+        taken
+    }
+
+    /// Puts the secrets into the settings, use it for the copy of the settings that is passed to the images.
+    pub fn fill(&self, settings: &mut PersistentSettings) {
+        for (registry, secret) in &self.0 {
+            let credentials = settings.credentials.entry(registry.clone()).or_default();
+            credentials.password = secret.password.clone();
+            credentials.identity_token = secret.identity_token.clone();
+        }
+    }
+}
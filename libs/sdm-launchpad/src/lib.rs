@@ -24,6 +24,8 @@
 #[cfg(feature = "tauri")]
 pub mod api;
 pub mod bus;
+pub mod credentials;
+pub mod log_archive;
 
 mod node_grpc;
 pub mod resources;
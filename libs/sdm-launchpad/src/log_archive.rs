@@ -0,0 +1,343 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! The archive of the logs of containers.
+//!
+//! Every task writes its logs to `{dir}/{task}.log` as JSON lines. When the file grows over the size limit or
+//! gets older than the age limit, it's compressed to `{dir}/{task}.{timestamp}.log.gz` and a new file is started.
+//! Only the latest archives are kept. [`ArchiveWriter`] owns the archive in a dedicated thread, so async callers
+//! are not blocked by the writes and the compression.
+
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    future::Future,
+    io::{self, BufRead, BufReader, LineWriter, Read, Write},
+    path::{Path, PathBuf},
+    thread,
+};
+
+use anyhow::{anyhow, Error};
+use chrono::{Duration, NaiveDateTime};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use log::*;
+use tari_launchpad_protocol::container::{LogPage, LogQuery, LogRecord, TaskId};
+use tokio::sync::{mpsc, oneshot};
+
+const CURRENT_EXT: &str = ".log";
+const ARCHIVE_EXT: &str = ".log.gz";
+
+/// When the current file of a task is archived.
+#[derive(Debug, Clone)]
+pub struct RotationPolicy {
+    /// The size of the current file in bytes
+    pub max_size: u64,
+    /// The age of the first record of the current file
+    pub max_age: Duration,
+    /// The number of compressed files to keep for a task
+    pub max_archives: usize,
+}
+
+impl Default for RotationPolicy {
+    fn default() -> Self {
+        Self {
+            max_size: 10 * 1024 * 1024,
+            max_age: Duration::days(1),
+            max_archives: 5,
+        }
+    }
+}
+
+struct CurrentFile {
+    writer: LineWriter<File>,
+    size: u64,
+    /// The time of the first record in the file
+    started_at: Option<NaiveDateTime>,
+}
+
+pub struct LogArchive {
+    dir: PathBuf,
+    policy: RotationPolicy,
+    files: HashMap<TaskId, CurrentFile>,
+}
+
+impl LogArchive {
+    pub fn new(dir: PathBuf, policy: RotationPolicy) -> Self {
+        Self {
+            dir,
+            policy,
+            files: HashMap::new(),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Appends the record to the current file of the task, the file is rotated if it's necessary.
+    pub fn append(&mut self, task_id: &TaskId, record: &LogRecord) -> Result<(), Error> {
+        if self.should_rotate(task_id, record) {
+            self.rotate(task_id)?;
+        }
+        if !self.files.contains_key(task_id) {
+            let file = self.open(task_id)?;
+            self.files.insert(task_id.clone(), file);
+        }
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        if let Some(file) = self.files.get_mut(task_id) {
+            file.writer.write_all(&line)?;
+            file.size += line.len() as u64;
+            file.started_at.get_or_insert(record.datetime);
+        }
+        Ok(())
+    }
+
+    fn should_rotate(&self, task_id: &TaskId, record: &LogRecord) -> bool {
+        match self.files.get(task_id) {
+            Some(file) => {
+                let too_old = file
+                    .started_at
+                    .map(|started_at| record.datetime - started_at >= self.policy.max_age)
+                    .unwrap_or_default();
+                file.size >= self.policy.max_size || too_old
+            },
+            None => false,
+        }
+    }
+
+    /// Continues the existing file of the task, it could be left by the previous run.
+    fn open(&self, task_id: &TaskId) -> Result<CurrentFile, Error> {
+        fs::create_dir_all(&self.dir)?;
+        let path = current_path(&self.dir, task_id);
+        let started_at = File::open(&path).ok().and_then(|file| {
+            let mut line = String::new();
+            BufReader::new(file).read_line(&mut line).ok()?;
+            serde_json::from_str::<LogRecord>(&line)
+                .ok()
+                .map(|record| record.datetime)
+        });
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(CurrentFile {
+            writer: LineWriter::new(file),
+            size,
+            started_at,
+        })
+    }
+
+    /// Compresses the current file of the task and removes the oldest archives.
+    pub fn rotate(&mut self, task_id: &TaskId) -> Result<(), Error> {
+        let started_at = match self.files.remove(task_id) {
+            Some(mut file) => {
+                file.writer.flush()?;
+                file.started_at
+            },
+            None => None,
+        };
+        let path = current_path(&self.dir, task_id);
+        if !path.exists() {
+            return Ok(());
+        }
+        let started_at = started_at.unwrap_or_else(|| chrono::Local::now().naive_local());
+        let name = format!(
+            "{}.{}{}",
+            file_name(task_id),
+            started_at.format("%Y%m%d-%H%M%S%.3f"),
+            ARCHIVE_EXT
+        );
+        let archive = self.dir.join(name);
+        debug!("Rotating {} to {}", path.display(), archive.display());
+        let mut encoder = GzEncoder::new(File::create(&archive)?, Compression::default());
+        io::copy(&mut File::open(&path)?, &mut encoder)?;
+        encoder.finish()?;
+        fs::remove_file(&path)?;
+
+        let archives = archives(&self.dir, task_id)?;
+        let outdated = archives.len().saturating_sub(self.policy.max_archives);
+        for path in archives.into_iter().take(outdated) {
+            debug!("Removing the old log archive {}", path.display());
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    /// Flushes all the files, since the archive could be read meanwhile.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        for file in self.files.values_mut() {
+            file.writer.flush()?;
+        }
+        Ok(())
+    }
+}
+
+enum Command {
+    Append(TaskId, LogRecord),
+    Query(LogQuery, oneshot::Sender<Result<LogPage, Error>>),
+}
+
+/// Sends the records to the archive running in its own thread. The thread stops when the writer is dropped.
+pub struct ArchiveWriter {
+    tx: mpsc::UnboundedSender<Command>,
+}
+
+impl ArchiveWriter {
+    pub fn spawn(archive: LogArchive) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        thread::spawn(move || write_archive(archive, rx));
+        Self { tx }
+    }
+
+    pub fn append(&self, task_id: TaskId, record: LogRecord) {
+        if self.tx.send(Command::Append(task_id, record)).is_err() {
+            error!("The writer of the log archive has stopped");
+        }
+    }
+
+    /// Reads a page of the archived logs, the records appended before are included.
+    pub fn query(&self, query: LogQuery) -> impl Future<Output = Result<LogPage, Error>> + Send + 'static {
+        let (page_tx, page_rx) = oneshot::channel();
+        let sent = self.tx.send(Command::Query(query, page_tx)).is_ok();
+        async move {
+            if !sent {
+                return Err(anyhow!("The writer of the log archive has stopped"));
+            }
+            page_rx
+                .await
+                .map_err(|_| anyhow!("The writer of the log archive has stopped"))?
+        }
+    }
+}
+
+fn write_archive(mut archive: LogArchive, mut rx: mpsc::UnboundedReceiver<Command>) {
+    while let Some(command) = rx.blocking_recv() {
+        match command {
+            Command::Append(task_id, record) => {
+                if let Err(err) = archive.append(&task_id, &record) {
+                    error!("Can't archive the logs of {}: {}", task_id, err);
+                }
+            },
+            Command::Query(logs, page_tx) => {
+                let page = archive.flush().and_then(|()| query(archive.dir(), &logs));
+                page_tx.send(page).ok();
+            },
+        }
+    }
+    if let Err(err) = archive.flush() {
+        error!("Can't flush the log archive: {}", err);
+    }
+}
+
+/// Reads a page of the archived logs. It doesn't need the archive itself, so it could be called from
+/// a blocking task.
+pub fn query(dir: &Path, query: &LogQuery) -> Result<LogPage, Error> {
+    let mut paths = archives(dir, &query.task_id)?;
+    paths.push(current_path(dir, &query.task_id));
+    let mut matched = 0;
+    let mut records = Vec::new();
+    let mut has_more = false;
+    'files: for path in paths {
+        let reader: Box<dyn Read> = match File::open(&path) {
+            Ok(file) if is_archive(&path) => Box::new(GzDecoder::new(file)),
+            Ok(file) => Box::new(file),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err.into()),
+        };
+        for line in BufReader::new(reader).lines() {
+            let line = line?;
+            let record: LogRecord = match serde_json::from_str(&line) {
+                Ok(record) => record,
+                Err(err) => {
+                    // The last line could be written partially if the app crashed
+                    warn!("Skipping a broken record in {}: {}", path.display(), err);
+                    continue;
+                },
+            };
+            if query.since.map(|since| record.datetime < since).unwrap_or_default() {
+                continue;
+            }
+            if query.until.map(|until| record.datetime > until).unwrap_or_default() {
+                continue;
+            }
+            matched += 1;
+            if matched <= query.offset {
+                continue;
+            }
+            if records.len() == query.limit {
+                has_more = true;
+                break 'files;
+            }
+            records.push(record);
+        }
+    }
+    Ok(LogPage {
+        task_id: query.task_id.clone(),
+        offset: query.offset,
+        records,
+        has_more,
+    })
+}
+
+/// Task ids are human readable and could contain spaces.
+fn file_name(task_id: &TaskId) -> String {
+    task_id
+        .to_string()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn current_path(dir: &Path, task_id: &TaskId) -> PathBuf {
+    dir.join(format!("{}{}", file_name(task_id), CURRENT_EXT))
+}
+
+fn is_archive(path: &Path) -> bool {
+    path.to_string_lossy().ends_with(ARCHIVE_EXT)
+}
+
+/// Compressed files of the task from the oldest to the newest.
+fn archives(dir: &Path, task_id: &TaskId) -> Result<Vec<PathBuf>, Error> {
+    let prefix = format!("{}.", file_name(task_id));
+    let mut paths = Vec::new();
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(paths),
+        Err(err) => return Err(err.into()),
+    };
+    for entry in entries {
+        let path = entry?.path();
+        let name = path.file_name().map(|name| name.to_string_lossy().to_string());
+        if name.is_some_and(|name| name.starts_with(&prefix) && name.ends_with(ARCHIVE_EXT)) {
+            paths.push(path);
+        }
+    }
+    // Timestamps in the names keep the order
+    paths.sort();
+    Ok(paths)
+}
@@ -9,21 +9,22 @@ use minotari_app_grpc::tari_rpc::{
 };
 use tari_launchpad_protocol::{
     errors::ErrorRecord,
-    launchpad::{LaunchpadDelta, LaunchpadDelta::AddError, Reaction},
+    launchpad::{LaunchpadDelta, LaunchpadDelta::AddError},
     node::{BaseNodeIdentity, NodeDelta},
+    settings::TariNetwork,
 };
 use tokio::{
     sync::{mpsc, Mutex},
     time::sleep,
 };
 
-pub const BASE_NODE_GRPC_ADDRESS: &str = "http://127.0.0.1:18142";
 #[derive(Default, Debug)]
 pub struct NodeGrpc {}
 
 impl NodeGrpc {
-    pub fn new(out_tx: mpsc::UnboundedSender<Reaction>) -> Self {
-        let worker = NodeGrpcWorker::new(out_tx);
+    /// Starts polling the base node gRPC of the `network` published on the host `port`.
+    pub fn new(out_tx: mpsc::UnboundedSender<(TariNetwork, LaunchpadDelta)>, network: TariNetwork, port: u16) -> Self {
+        let worker = NodeGrpcWorker::new(out_tx, network, port);
         tokio::spawn(worker.entrypoint());
         Self {}
     }
@@ -31,19 +32,27 @@ impl NodeGrpc {
 
 pub struct NodeGrpcWorker {
     // The channel that receives updates about state changes in the node's state
-    out_tx: mpsc::UnboundedSender<Reaction>,
+    out_tx: mpsc::UnboundedSender<(TariNetwork, LaunchpadDelta)>,
+    // The network the node belongs to, the updates are routed to its state
+    network: TariNetwork,
+    address: String,
     // A long-lived connection to the gRPC server. It is lazily initialized.
     client: Option<Arc<Mutex<BaseNodeClient<tonic::transport::Channel>>>>,
 }
 
 impl NodeGrpcWorker {
-    pub fn new(out_tx: mpsc::UnboundedSender<Reaction>) -> Self {
-        Self { out_tx, client: None }
+    pub fn new(out_tx: mpsc::UnboundedSender<(TariNetwork, LaunchpadDelta)>, network: TariNetwork, port: u16) -> Self {
+        Self {
+            out_tx,
+            network,
+            address: format!("http://127.0.0.1:{}", port),
+            client: None,
+        }
     }
 
     async fn get_connection(&mut self) -> Result<Arc<Mutex<BaseNodeClient<tonic::transport::Channel>>>, Error> {
         if self.client.is_none() {
-            let client = Arc::new(Mutex::new(BaseNodeClient::connect(BASE_NODE_GRPC_ADDRESS).await?));
+            let client = Arc::new(Mutex::new(BaseNodeClient::connect(self.address.clone()).await?));
             self.client = Some(client);
         }
         Ok(Arc::clone(self.client.as_ref().unwrap()))
@@ -86,7 +95,7 @@ impl NodeGrpcWorker {
     }
 
     fn send_update(&mut self, delta: NodeDelta) {
-        let msg = Reaction::Delta(LaunchpadDelta::NodeDelta(delta));
+        let msg = (self.network, LaunchpadDelta::NodeDelta(delta));
         if let Err(e) = self.out_tx.send(msg) {
             log::error!("Can't send update for the node: {e}");
         }
@@ -97,7 +106,7 @@ impl NodeGrpcWorker {
             datetime: chrono::Utc::now().naive_local(),
             message: error.into(),
         };
-        let msg = Reaction::Delta(AddError(record));
+        let msg = (self.network, AddError(record));
         if let Err(e) = self.out_tx.send(msg) {
             log::error!("Can't send error for the node: {e}");
         }
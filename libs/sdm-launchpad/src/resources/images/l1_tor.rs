@@ -31,12 +31,14 @@ use tari_sdm::{
     ids::{ManagedTask, TaskId},
     image::{
         checker::{CheckerContext, CheckerEvent, ContainerChecker},
-        Args, Envs, ManagedContainer, Networks, Ports,
+        log_parser::{LogParser, TorParser},
+        Args, Envs, ManagedContainer, Networks, Ports, RestartPolicy,
     },
+    runtime::RegistryAuth,
 };
 use tor_hash_passwd::EncryptedKey;
 
-use super::DEFAULT_REGISTRY;
+use super::{ImageSource, DEFAULT_REGISTRY, DEFAULT_TAG, RESTART_POLICY};
 use crate::resources::{
     config::{ConnectionSettings, LaunchpadConfig, LaunchpadProtocol},
     networks::LocalNet,
@@ -45,6 +47,7 @@ use crate::resources::{
 #[derive(Debug, Default)]
 pub struct Tor {
     settings: Option<ConnectionSettings>,
+    source: ImageSource,
 }
 
 impl ManagedTask for Tor {
@@ -61,7 +64,7 @@ impl ManagedContainer for Tor {
     type Protocol = LaunchpadProtocol;
 
     fn registry(&self) -> &str {
-        DEFAULT_REGISTRY
+        self.source.registry(DEFAULT_REGISTRY)
     }
 
     fn image_name(&self) -> &str {
@@ -69,11 +72,20 @@ impl ManagedContainer for Tor {
     }
 
     fn tag(&self) -> &str {
-        "latest-nextnet"
+        self.source.tag(DEFAULT_TAG)
+    }
+
+    fn credentials(&self) -> Option<RegistryAuth> {
+        self.source.credentials(DEFAULT_REGISTRY)
+    }
+
+    fn log_parser(&self) -> Box<dyn LogParser> {
+        Box::<TorParser>::default()
     }
 
     fn reconfigure(&mut self, config: Option<&LaunchpadConfig>) -> Option<bool> {
         debug!("Reconfiguring Tor");
+        self.source = ImageSource::tari(self.image_name(), config);
         self.settings = ConnectionSettings::try_extract(config?);
         let session = &self.settings.as_ref()?.session;
         Some(session.is_tor_active())
@@ -103,13 +115,17 @@ impl ManagedContainer for Tor {
     }
 
     fn ports(&self, ports: &mut Ports) {
-        ports.add(9050);
-        ports.add(9051);
+        ports.add(9050).relocatable().localhost();
+        ports.add(9051).relocatable().localhost();
     }
 
     fn networks(&self, networks: &mut Networks) {
         networks.add("tor", LocalNet::id());
     }
+
+    fn restart_policy(&self) -> RestartPolicy {
+        RESTART_POLICY
+    }
 }
 
 struct Checker {
@@ -133,6 +149,7 @@ impl ContainerChecker<LaunchpadProtocol> for Checker {
                     let progress = TaskProgress {
                         pct: value as u8,
                         stage: "Bootstrapping...".into(),
+                        transfer: None,
                     };
                     ctx.report(CheckerEvent::Progress(progress)).ok();
                     if value == 100 {
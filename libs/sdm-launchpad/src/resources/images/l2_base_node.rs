@@ -21,6 +21,8 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
+use std::time::Duration;
+
 use anyhow::Error;
 use async_trait::async_trait;
 use log::debug;
@@ -30,13 +32,15 @@ use tari_sdm::{
     ids::{ManagedTask, TaskId},
     image::{
         checker::{CheckerContext, CheckerEvent, ContainerChecker},
-        Args, Envs, ManagedContainer, Mounts, Networks, Ports, Volumes,
+        log_parser::{Log4rsParser, LogParser},
+        Args, Envs, ManagedContainer, Mounts, Networks, Ports, RestartPolicy, Timeouts, Volumes,
     },
+    runtime::{RegistryAuth, Resources},
 };
 
 use super::{
-    sync_progress::SyncProgress, Tor, BLOCKCHAIN_PATH, BLOCKCHAIN_VOLUME, DEFAULT_REGISTRY, GENERAL_VOLUME,
-    VAR_TARI_PATH,
+    resources, sync_progress::SyncProgress, ImageSource, Tor, BLOCKCHAIN_PATH, BLOCKCHAIN_VOLUME, DEFAULT_REGISTRY,
+    DEFAULT_TAG, GENERAL_VOLUME, RESTART_POLICY, VAR_TARI_PATH,
 };
 use crate::resources::{
     config::{ConnectionSettings, LaunchpadConfig, LaunchpadInnerEvent, LaunchpadProtocol},
@@ -45,9 +49,13 @@ use crate::resources::{
     volumes::SharedVolume,
 };
 
+/// The gRPC port of the base node. It's published on a different host port if it's busy.
+pub const BASE_NODE_GRPC_PORT: u16 = 18_142;
+
 #[derive(Debug, Default)]
 pub struct TariBaseNode {
     settings: Option<ConnectionSettings>,
+    source: ImageSource,
     config: BaseNodeConfig,
 }
 
@@ -65,7 +73,7 @@ impl ManagedContainer for TariBaseNode {
     type Protocol = LaunchpadProtocol;
 
     fn registry(&self) -> &str {
-        DEFAULT_REGISTRY
+        self.source.registry(DEFAULT_REGISTRY)
     }
 
     fn image_name(&self) -> &str {
@@ -73,11 +81,20 @@ impl ManagedContainer for TariBaseNode {
     }
 
     fn tag(&self) -> &str {
-        "latest-nextnet"
+        self.source.tag(DEFAULT_TAG)
+    }
+
+    fn credentials(&self) -> Option<RegistryAuth> {
+        self.source.credentials(DEFAULT_REGISTRY)
+    }
+
+    fn log_parser(&self) -> Box<dyn LogParser> {
+        Box::<Log4rsParser>::default()
     }
 
     fn reconfigure(&mut self, config: Option<&LaunchpadConfig>) -> Option<bool> {
         debug!("Reconfiguring base node");
+        self.source = ImageSource::tari(self.image_name(), config);
         let config = config?;
         self.config = config
             .settings
@@ -113,7 +130,7 @@ impl ManagedContainer for TariBaseNode {
     }
 
     fn ports(&self, ports: &mut Ports) {
-        ports.add(18_142);
+        ports.add(BASE_NODE_GRPC_PORT).relocatable().localhost();
         ports.add(18_189);
     }
 
@@ -133,6 +150,22 @@ impl ManagedContainer for TariBaseNode {
             mounts.add_volume(SharedVolume::id(), BLOCKCHAIN_PATH);
         }
     }
+
+    fn resources(&self) -> Resources {
+        resources(&self.config.resources)
+    }
+
+    fn restart_policy(&self) -> RestartPolicy {
+        RESTART_POLICY
+    }
+
+    fn timeouts(&self) -> Timeouts {
+        Timeouts {
+            // The node flushes the blockchain database on exit
+            stop: Duration::from_secs(30),
+            ..Timeouts::default()
+        }
+    }
 }
 
 /// A helper struct to track the progress of the initial block download.
@@ -164,7 +197,8 @@ impl ContainerChecker<LaunchpadProtocol> for Checker {
             return Ok(());
         }
         // TODO: Keep the client
-        let mut client = BaseNodeGrpcClient::connect("http://127.0.0.1:18142").await?;
+        let port = ctx.host_port(BASE_NODE_GRPC_PORT).unwrap_or(BASE_NODE_GRPC_PORT);
+        let mut client = BaseNodeGrpcClient::connect(format!("http://127.0.0.1:{}", port)).await?;
 
         if !self.identity_sent {
             let identity = client.identify(grpc::Empty {}).await?.into_inner().try_into()?;
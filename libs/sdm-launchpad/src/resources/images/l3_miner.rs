@@ -25,12 +25,17 @@ use std::str::FromStr;
 
 use log::{info, warn};
 use tari_common_types::tari_address::TariAddress;
+use tari_launchpad_protocol::settings::ResourceLimits;
 use tari_sdm::{
     ids::{ManagedTask, TaskId},
-    image::{Args, Envs, ManagedContainer, Mounts, Networks, Volumes},
+    image::{
+        log_parser::{Log4rsParser, LogParser},
+        Args, Envs, ManagedContainer, Mounts, Networks, RestartPolicy, Volumes,
+    },
+    runtime::{RegistryAuth, Resources},
 };
 
-use super::{TariBaseNode, DEFAULT_REGISTRY, GENERAL_VOLUME};
+use super::{resources, ImageSource, TariBaseNode, DEFAULT_REGISTRY, DEFAULT_TAG, GENERAL_VOLUME, RESTART_POLICY};
 use crate::resources::{
     config::{ConnectionSettings, LaunchpadConfig, LaunchpadProtocol},
     images::VAR_TARI_PATH,
@@ -41,8 +46,10 @@ use crate::resources::{
 #[derive(Debug, Default)]
 pub struct TariSha3Miner {
     settings: Option<ConnectionSettings>,
+    source: ImageSource,
     wallet_payment_address: Option<TariAddress>,
     num_mining_threads: Option<usize>,
+    limits: ResourceLimits,
 }
 
 impl ManagedTask for TariSha3Miner {
@@ -59,7 +66,7 @@ impl ManagedContainer for TariSha3Miner {
     type Protocol = LaunchpadProtocol;
 
     fn registry(&self) -> &str {
-        DEFAULT_REGISTRY
+        self.source.registry(DEFAULT_REGISTRY)
     }
 
     fn image_name(&self) -> &str {
@@ -67,13 +74,28 @@ impl ManagedContainer for TariSha3Miner {
     }
 
     fn tag(&self) -> &str {
-        "latest-nextnet"
+        self.source.tag(DEFAULT_TAG)
+    }
+
+    fn credentials(&self) -> Option<RegistryAuth> {
+        self.source.credentials(DEFAULT_REGISTRY)
+    }
+
+    fn log_parser(&self) -> Box<dyn LogParser> {
+        Box::<Log4rsParser>::default()
     }
 
     fn reconfigure(&mut self, config: Option<&LaunchpadConfig>) -> Option<bool> {
+        self.source = ImageSource::tari(self.image_name(), config);
         self.settings = ConnectionSettings::try_extract(config?);
         let session = &self.settings.as_ref()?.session;
 
+        self.limits = config?
+            .settings
+            .as_ref()
+            .and_then(|settings| settings.saved_settings.sha3_miner.as_ref())
+            .map(|sha3_miner| sha3_miner.resources.clone())
+            .unwrap_or_default();
         (self.wallet_payment_address, self.num_mining_threads) = match config?.settings {
             Some(ref settings) if settings.saved_settings.sha3_miner.is_none() => {
                 info!("No Sha3 Miner settings found for the container configuration. Falling back on defaults.");
@@ -140,4 +162,12 @@ impl ManagedContainer for TariSha3Miner {
             mounts.bind_path(settings.data_directory.display(), VAR_TARI_PATH);
         }
     }
+
+    fn resources(&self) -> Resources {
+        resources(&self.limits)
+    }
+
+    fn restart_policy(&self) -> RestartPolicy {
+        RESTART_POLICY
+    }
 }
@@ -21,25 +21,35 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
-use std::ops::Deref;
+use std::{ops::Deref, time::Duration};
 
 use log::*;
 use tari_launchpad_protocol::settings::MmProxyConfig;
 use tari_sdm::{
     ids::{ManagedTask, TaskId},
-    image::{Envs, ManagedContainer, Mounts, Networks, Ports, Volumes},
+    image::{
+        checker::{ContainerChecker, LogMatch, ReadyWhen},
+        log_parser::{Log4rsParser, LogParser},
+        Envs, ManagedContainer, Mounts, Networks, Ports, RestartPolicy, Volumes,
+    },
+    runtime::{RegistryAuth, Resources},
 };
 
-use super::{TariBaseNode, DEFAULT_REGISTRY, GENERAL_VOLUME, VAR_TARI_PATH};
+use super::{
+    resources, ImageSource, TariBaseNode, DEFAULT_REGISTRY, DEFAULT_TAG, GENERAL_VOLUME, RESTART_POLICY, VAR_TARI_PATH,
+};
 use crate::resources::{
     config::{ConnectionSettings, LaunchpadConfig, LaunchpadProtocol},
     networks::LocalNet,
     volumes::SharedVolume,
 };
 
+const MMPROXY_PORT: u16 = 18_081;
+
 #[derive(Debug, Default)]
 pub struct MmProxy {
     settings: Option<ConnectionSettings>,
+    source: ImageSource,
     mm_proxy: Option<MmProxyConfig>,
 }
 
@@ -57,7 +67,7 @@ impl ManagedContainer for MmProxy {
     type Protocol = LaunchpadProtocol;
 
     fn registry(&self) -> &str {
-        DEFAULT_REGISTRY
+        self.source.registry(DEFAULT_REGISTRY)
     }
 
     fn image_name(&self) -> &str {
@@ -65,10 +75,24 @@ impl ManagedContainer for MmProxy {
     }
 
     fn tag(&self) -> &str {
-        "latest-nextnet"
+        self.source.tag(DEFAULT_TAG)
+    }
+
+    fn credentials(&self) -> Option<RegistryAuth> {
+        self.source.credentials(DEFAULT_REGISTRY)
+    }
+
+    fn log_parser(&self) -> Box<dyn LogParser> {
+        Box::<Log4rsParser>::default()
+    }
+
+    fn checker(&mut self) -> Box<dyn ContainerChecker<LaunchpadProtocol>> {
+        let probe = LogMatch::new(r"Listening on").timeout(Duration::from_secs(120));
+        Box::new(ReadyWhen::new(probe))
     }
 
     fn reconfigure(&mut self, config: Option<&LaunchpadConfig>) -> Option<bool> {
+        self.source = ImageSource::tari(self.image_name(), config);
         self.settings = config.and_then(ConnectionSettings::try_extract).or_else(|| {
             warn!("No connection settings found for MM proxy");
             None
@@ -127,6 +151,17 @@ impl ManagedContainer for MmProxy {
     }
 
     fn ports(&self, ports: &mut Ports) {
-        ports.add(18_081);
+        ports.add(MMPROXY_PORT);
+    }
+
+    fn resources(&self) -> Resources {
+        self.mm_proxy
+            .as_ref()
+            .map(|mm_proxy| resources(&mm_proxy.resources))
+            .unwrap_or_default()
+    }
+
+    fn restart_policy(&self) -> RestartPolicy {
+        RESTART_POLICY
     }
 }
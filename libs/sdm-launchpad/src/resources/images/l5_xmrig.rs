@@ -21,13 +21,20 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
+use std::time::Duration;
+
 use tari_launchpad_protocol::settings::XmRigConfig;
 use tari_sdm::{
     ids::{ManagedTask, TaskId},
-    image::{Args, Envs, ManagedContainer, Networks, Volumes},
+    image::{
+        checker::{ContainerChecker, LogMatch, ReadyWhen},
+        log_parser::{LogParser, XmrigParser},
+        Args, Envs, ManagedContainer, Networks, RestartPolicy, Volumes,
+    },
+    runtime::{RegistryAuth, Resources},
 };
 
-use super::{MmProxy, DEFAULT_REGISTRY, GENERAL_VOLUME};
+use super::{resources, ImageSource, MmProxy, DEFAULT_REGISTRY, DEFAULT_TAG, GENERAL_VOLUME, RESTART_POLICY};
 use crate::resources::{
     config::{ConnectionSettings, LaunchpadConfig, LaunchpadProtocol},
     networks::LocalNet,
@@ -36,6 +43,7 @@ use crate::resources::{
 #[derive(Debug, Default)]
 pub struct XMRig {
     settings: Option<ConnectionSettings>,
+    source: ImageSource,
     xmrig: Option<XmRigConfig>,
 }
 
@@ -53,7 +61,7 @@ impl ManagedContainer for XMRig {
     type Protocol = LaunchpadProtocol;
 
     fn registry(&self) -> &str {
-        DEFAULT_REGISTRY
+        self.source.registry(DEFAULT_REGISTRY)
     }
 
     fn image_name(&self) -> &str {
@@ -61,10 +69,25 @@ impl ManagedContainer for XMRig {
     }
 
     fn tag(&self) -> &str {
-        "latest-nextnet"
+        self.source.tag(DEFAULT_TAG)
+    }
+
+    fn credentials(&self) -> Option<RegistryAuth> {
+        self.source.credentials(DEFAULT_REGISTRY)
+    }
+
+    fn log_parser(&self) -> Box<dyn LogParser> {
+        Box::<XmrigParser>::default()
+    }
+
+    fn checker(&mut self) -> Box<dyn ContainerChecker<LaunchpadProtocol>> {
+        // Workers are started when the first job is received
+        let probe = LogMatch::new(r"READY threads").timeout(Duration::from_secs(600));
+        Box::new(ReadyWhen::new(probe))
     }
 
     fn reconfigure(&mut self, config: Option<&LaunchpadConfig>) -> Option<bool> {
+        self.source = ImageSource::tari(self.image_name(), config);
         self.settings = ConnectionSettings::try_extract(config?);
         let session = &self.settings.as_ref()?.session;
 
@@ -79,8 +102,6 @@ impl ManagedContainer for XMRig {
         args.set("--url", "tari_mm_proxy:18081");
         args.set("--user", "${TARI_MONERO_WALLET_ADDRESS}");
         args.set("--coin", "monero");
-        args.flag("--daemon");
-        args.set("--log-file", "/home/tari/xmrig.log");
         args.flag("--verbose");
         // No value or a value of 0 will let XMRig use auto config, otherwise the provided value will be used
         if let Some(xmrig) = self.xmrig.as_ref() {
@@ -119,4 +140,15 @@ impl ManagedContainer for XMRig {
     fn volumes(&self, volumes: &mut Volumes) {
         volumes.add(GENERAL_VOLUME);
     }
+
+    fn resources(&self) -> Resources {
+        self.xmrig
+            .as_ref()
+            .map(|xmrig| resources(&xmrig.resources))
+            .unwrap_or_default()
+    }
+
+    fn restart_policy(&self) -> RestartPolicy {
+        RESTART_POLICY
+    }
 }
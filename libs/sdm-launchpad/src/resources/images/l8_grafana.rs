@@ -23,10 +23,15 @@
 
 use tari_sdm::{
     ids::{ManagedTask, TaskId},
-    image::{Envs, ManagedContainer, Mounts, Networks, Ports, Volumes},
+    image::{
+        checker::{ContainerChecker, HttpStatus, ReadyWhen},
+        log_parser::{LogParser, LogfmtParser},
+        Envs, ManagedContainer, Mounts, Networks, Ports, Volumes,
+    },
+    runtime::RegistryAuth,
 };
 
-use super::GRAFANA_REGISTRY;
+use super::{ImageSource, GRAFANA_REGISTRY, GRAFANA_TAG};
 use crate::resources::{
     config::{ConnectionSettings, LaunchpadConfig, LaunchpadProtocol},
     images::{GENERAL_VOLUME, GRAFANA_DEFAULTS_PATH, GRAFANA_PROVISION_PATH, GRAFANA_VOLUME, VAR_TARI_PATH},
@@ -34,9 +39,12 @@ use crate::resources::{
     volumes::SharedGrafanaVolume,
 };
 
+const GRAFANA_PORT: u16 = 18_300;
+
 #[derive(Debug, Default)]
 pub struct Grafana {
     settings: Option<ConnectionSettings>,
+    source: ImageSource,
 }
 
 impl ManagedTask for Grafana {
@@ -53,13 +61,29 @@ impl ManagedContainer for Grafana {
     type Protocol = LaunchpadProtocol;
 
     fn registry(&self) -> &str {
-        GRAFANA_REGISTRY
+        self.source.registry(GRAFANA_REGISTRY)
     }
 
     fn image_name(&self) -> &str {
         "grafana"
     }
 
+    fn tag(&self) -> &str {
+        self.source.tag(GRAFANA_TAG)
+    }
+
+    fn credentials(&self) -> Option<RegistryAuth> {
+        self.source.credentials(GRAFANA_REGISTRY)
+    }
+
+    fn log_parser(&self) -> Box<dyn LogParser> {
+        Box::<LogfmtParser>::default()
+    }
+
+    fn checker(&mut self) -> Box<dyn ContainerChecker<LaunchpadProtocol>> {
+        Box::new(ReadyWhen::new(HttpStatus::new(GRAFANA_PORT, "/api/health")))
+    }
+
     fn envs(&self, envs: &mut Envs) {
         let path = concat!(
             "/usr/share/grafana/bin:",
@@ -82,10 +106,11 @@ impl ManagedContainer for Grafana {
     }
 
     fn ports(&self, ports: &mut Ports) {
-        ports.add(18_300);
+        ports.add(GRAFANA_PORT);
     }
 
     fn reconfigure(&mut self, config: Option<&LaunchpadConfig>) -> Option<bool> {
+        self.source = ImageSource::third_party(self.image_name(), config);
         self.settings = ConnectionSettings::try_extract(config?);
         let session = &self.settings.as_ref()?.session;
         Some(session.is_grafana_active())
@@ -23,10 +23,15 @@
 
 use tari_sdm::{
     ids::{ManagedTask, TaskId},
-    image::{Args, Envs, ManagedContainer, Mounts, Networks, Ports, Volumes},
+    image::{
+        checker::{ContainerChecker, HttpStatus, ReadyWhen},
+        log_parser::{LogParser, LogfmtParser},
+        Args, Envs, ManagedContainer, Mounts, Networks, Ports, Volumes,
+    },
+    runtime::RegistryAuth,
 };
 
-use super::GRAFANA_REGISTRY;
+use super::{ImageSource, GRAFANA_REGISTRY, GRAFANA_TAG};
 use crate::resources::{
     config::{ConnectionSettings, LaunchpadConfig, LaunchpadProtocol},
     images::{Grafana, GENERAL_VOLUME, GRAFANA_VOLUME, PROMTAIL_CONFIG_PATH, VAR_TARI_PATH},
@@ -34,9 +39,12 @@ use crate::resources::{
     volumes::{SharedGrafanaVolume, SharedVolume},
 };
 
+const PROMTAIL_PORT: u16 = 18_980;
+
 #[derive(Debug, Default)]
 pub struct Promtail {
     settings: Option<ConnectionSettings>,
+    source: ImageSource,
 }
 
 impl ManagedTask for Promtail {
@@ -58,13 +66,29 @@ impl ManagedContainer for Promtail {
     type Protocol = LaunchpadProtocol;
 
     fn registry(&self) -> &str {
-        GRAFANA_REGISTRY
+        self.source.registry(GRAFANA_REGISTRY)
     }
 
     fn image_name(&self) -> &str {
         "promtail"
     }
 
+    fn tag(&self) -> &str {
+        self.source.tag(GRAFANA_TAG)
+    }
+
+    fn credentials(&self) -> Option<RegistryAuth> {
+        self.source.credentials(GRAFANA_REGISTRY)
+    }
+
+    fn log_parser(&self) -> Box<dyn LogParser> {
+        Box::<LogfmtParser>::default()
+    }
+
+    fn checker(&mut self) -> Box<dyn ContainerChecker<LaunchpadProtocol>> {
+        Box::new(ReadyWhen::new(HttpStatus::new(PROMTAIL_PORT, "/ready")))
+    }
+
     fn envs(&self, envs: &mut Envs) {
         let path = concat!(
             "/usr/share/grafana/bin:",
@@ -91,10 +115,11 @@ impl ManagedContainer for Promtail {
     }
 
     fn ports(&self, ports: &mut Ports) {
-        ports.add(18_980);
+        ports.add(PROMTAIL_PORT);
     }
 
     fn reconfigure(&mut self, config: Option<&LaunchpadConfig>) -> Option<bool> {
+        self.source = ImageSource::third_party(self.image_name(), config);
         self.settings = ConnectionSettings::try_extract(config?);
         let session = &self.settings.as_ref()?.session;
         Some(session.is_promtail_active())
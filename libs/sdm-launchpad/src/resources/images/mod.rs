@@ -31,17 +31,31 @@ mod l8_loki;
 mod l8_promtail;
 mod sync_progress;
 
+use std::collections::BTreeMap;
+
 pub use l1_tor::Tor;
-pub use l2_base_node::TariBaseNode;
+pub use l2_base_node::{TariBaseNode, BASE_NODE_GRPC_PORT};
 pub use l3_miner::TariSha3Miner;
 pub use l5_mmproxy::MmProxy;
 pub use l5_xmrig::XMRig;
 pub use l8_grafana::Grafana;
 pub use l8_loki::Loki;
 pub use l8_promtail::Promtail;
+use tari_launchpad_protocol::settings::{PersistentSettings, RegistryCredentials, ResourceLimits};
+use tari_sdm::{
+    image::RestartPolicy,
+    runtime::{registry_host, RegistryAuth, Resources},
+};
+
+use crate::resources::config::LaunchpadConfig;
 
 static DEFAULT_REGISTRY: &str = "ghcr.io/tari-project";
+/// The tag of the default network. It's used until the settings are loaded.
+static DEFAULT_TAG: &str = "latest-nextnet";
 static GRAFANA_REGISTRY: &str = "grafana";
+static GRAFANA_TAG: &str = "latest";
+
+static RESTART_POLICY: RestartPolicy = RestartPolicy::OnFailure { max_attempts: 5 };
 
 static GENERAL_VOLUME: &str = "/var/tari";
 static BLOCKCHAIN_VOLUME: &str = "/blockchain";
@@ -54,3 +68,77 @@ static GRAFANA_DEFAULTS_PATH: &str = "/usr/share/grafana/conf/defaults.ini";
 static GRAFANA_PROVISION_PATH: &str = "/etc/grafana/provisioning/datasources/all.yml";
 static LOKI_DEFAULTS_PATH: &str = "/usr/share/grafana/conf/defaults.ini";
 static PROMTAIL_CONFIG_PATH: &str = "/etc/promtail/config.yml";
+
+/// Applies the limits from the settings to a container.
+fn resources(limits: &ResourceLimits) -> Resources {
+    let mut resources = Resources::default();
+    if let Some(cpus) = limits.cpus {
+        resources.cpus(cpus);
+    }
+    if let Some(cpuset) = limits.cpuset.as_ref() {
+        resources.cpuset(cpuset);
+    }
+    if let Some(memory) = limits.memory_mib {
+        resources.memory_mib(memory);
+    }
+    if let Some(reservation) = limits.memory_reservation_mib {
+        resources.memory_reservation_mib(reservation);
+    }
+    if let Some(pids) = limits.pids_limit {
+        resources.pids(pids);
+    }
+    resources
+}
+
+/// The registry and the tag of an image taken from the settings. Unset values fall back to the defaults of the image.
+#[derive(Debug, Default)]
+struct ImageSource {
+    registry: Option<String>,
+    tag: Option<String>,
+    credentials: BTreeMap<String, RegistryCredentials>,
+}
+
+impl ImageSource {
+    /// Tari images follow the common registry and tag, and the tag of the network by default.
+    fn tari(image: &str, config: Option<&LaunchpadConfig>) -> Self {
+        let settings = saved_settings(config);
+        Self {
+            registry: settings.and_then(|s| s.tari_image_registry(image)).map(String::from),
+            tag: settings.map(|s| s.tari_image_tag(image).to_string()),
+            credentials: settings.map(|s| s.credentials.clone()).unwrap_or_default(),
+        }
+    }
+
+    /// Third-party images could be changed by the settings of the image only.
+    fn third_party(image: &str, config: Option<&LaunchpadConfig>) -> Self {
+        let settings = saved_settings(config);
+        Self {
+            registry: settings.and_then(|s| s.image_registry(image)).map(String::from),
+            tag: settings.and_then(|s| s.image_tag(image)).map(String::from),
+            credentials: settings.map(|s| s.credentials.clone()).unwrap_or_default(),
+        }
+    }
+
+    fn registry<'a>(&'a self, default: &'a str) -> &'a str {
+        self.registry.as_deref().unwrap_or(default)
+    }
+
+    fn tag<'a>(&'a self, default: &'a str) -> &'a str {
+        self.tag.as_deref().unwrap_or(default)
+    }
+
+    /// Credentials of the host of the registry, if they're set.
+    fn credentials(&self, default_registry: &str) -> Option<RegistryAuth> {
+        let image = format!("{}/image", self.registry(default_registry));
+        let credentials = self.credentials.get(registry_host(&image))?;
+        Some(RegistryAuth {
+            username: credentials.username.clone(),
+            password: credentials.password.clone(),
+            identity_token: credentials.identity_token.clone(),
+        })
+    }
+}
+
+fn saved_settings(config: Option<&LaunchpadConfig>) -> Option<&PersistentSettings> {
+    config?.settings.as_ref().map(|settings| &settings.saved_settings)
+}
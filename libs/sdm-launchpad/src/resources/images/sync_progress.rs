@@ -210,18 +210,22 @@ impl From<&SyncProgress> for TaskProgress {
             SyncType::Startup => TaskProgress {
                 pct: 0,
                 stage: "Starting...".to_string(),
+                transfer: None,
             },
             SyncType::Block => TaskProgress {
                 pct: (value.blocks_sync.progress() * 100.0) as u8,
                 stage: "Syncing blockchain...".to_string(),
+                transfer: None,
             },
             SyncType::Header => TaskProgress {
                 pct: (value.header_sync.progress() * 100.0) as u8,
                 stage: "Syncing headers...".to_string(),
+                transfer: None,
             },
             SyncType::Done => TaskProgress {
                 pct: 100,
                 stage: "Complete".to_string(),
+                transfer: None,
             },
         }
     }
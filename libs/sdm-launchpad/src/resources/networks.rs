@@ -21,15 +21,19 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
+use tari_launchpad_protocol::settings::NetworkSettings;
 use tari_sdm::{
     ids::{ManagedTask, TaskId},
     network::ManagedNetwork,
+    runtime::NetworkSpec,
 };
 
-use crate::resources::config::LaunchpadProtocol;
+use crate::resources::config::{LaunchpadConfig, LaunchpadProtocol};
 
 #[derive(Debug, Default)]
-pub struct LocalNet {}
+pub struct LocalNet {
+    settings: NetworkSettings,
+}
 
 impl ManagedTask for LocalNet {
     fn id() -> TaskId {
@@ -40,7 +44,35 @@ impl ManagedTask for LocalNet {
 impl ManagedNetwork for LocalNet {
     type Protocol = LaunchpadProtocol;
 
+    fn reconfigure(&mut self, config: Option<&LaunchpadConfig>) -> bool {
+        self.settings = config
+            .and_then(|config| config.settings.as_ref())
+            .map(|settings| settings.saved_settings.network.clone())
+            .unwrap_or_default();
+        config.is_some()
+    }
+
     fn network_name(&self) -> &str {
         "network"
     }
+
+    fn spec(&self) -> NetworkSpec {
+        let mut spec = NetworkSpec::default();
+        if let Some(driver) = &self.settings.driver {
+            spec.driver(driver);
+        }
+        if let Some(subnet) = &self.settings.subnet {
+            let subnet = spec.subnet(subnet);
+            if let Some(gateway) = &self.settings.gateway {
+                subnet.gateway(gateway);
+            }
+        }
+        if let Some(subnet) = &self.settings.ipv6_subnet {
+            spec.ipv6().subnet(subnet);
+        }
+        if self.settings.internal {
+            spec.internal();
+        }
+        spec
+    }
 }
@@ -21,12 +21,19 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
+use std::time::Duration;
+
 use anyhow::Error;
-use tari_launchpad_protocol::{ACTIONS, REACTIONS};
+use tari_launchpad_protocol::{launchpad::Reaction, ACTIONS, REACTIONS};
 use tauri::{App, Manager, Wry};
 
 use crate::bus::LaunchpadBus;
 
+/// The app exits anyway if the containers are not stopped in time or the bus has died.
+/// The tasks are stopped in stages one after another and the base node has the longest
+/// grace period of 30 seconds, so it's enough for all the stages with a margin.
+pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(120);
+
 pub fn bus_setup(app: &mut App<Wry>) -> Result<(), Box<dyn std::error::Error>> {
     let handle = app.handle();
     let bus = LaunchpadBus::start()?;
@@ -53,7 +60,12 @@ pub fn bus_setup(app: &mut App<Wry>) -> Result<(), Box<dyn std::error::Error>> {
     let mut out_rx = bus.outgoing;
     tauri::async_runtime::spawn(async move {
         while let Some(event) = out_rx.recv().await {
+            let stopped = matches!(event, Reaction::Stopped);
             handle.emit_all(REACTIONS, event)?;
+            if stopped {
+                // The exit was postponed until all the containers are stopped
+                handle.exit(0);
+            }
         }
         Ok::<(), Error>(())
     });
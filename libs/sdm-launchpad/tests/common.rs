@@ -124,6 +124,13 @@ impl TestStateInner {
                     state.apply(delta);
                 }
             },
+            Reaction::Stopped
+            | Reaction::Logs(_)
+            | Reaction::Exec { .. }
+            | Reaction::Console { .. }
+            | Reaction::Snapshot(_)
+            | Reaction::VolumeCleared { .. }
+            | Reaction::OrphansRemoved { .. } => {},
         }
         Ok(())
     }
@@ -0,0 +1,92 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use std::{fs, path::PathBuf};
+
+use anyhow::Error;
+use tari_launchpad_protocol::settings::{PersistentSettings, RegistryCredentials};
+use tari_sdm_launchpad::credentials::RegistrySecrets;
+
+const REGISTRY: &str = "registry.example.com";
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("launchpad-{}-{}", name, std::process::id()));
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(dir.join("config")).unwrap();
+    dir
+}
+
+fn settings_with_password() -> PersistentSettings {
+    let mut settings = PersistentSettings::default();
+    let credentials = RegistryCredentials {
+        username: Some("tari".into()),
+        password: Some("hunter2".into()),
+        identity_token: None,
+    };
+    settings.credentials.insert(REGISTRY.into(), credentials);
+    settings
+}
+
+#[test]
+fn test_settings_never_serialize_secrets() -> Result<(), Error> {
+    let settings = settings_with_password();
+    let toml = toml::to_string(&settings)?;
+    assert!(toml.contains("tari"));
+    assert!(!toml.contains("hunter2"));
+    let json = serde_json::to_string(&settings)?;
+    assert!(!json.contains("hunter2"));
+
+    // The secrets of older settings files are still read to move them out
+    let legacy = toml.replace("username = \"tari\"", "username = \"tari\"\npassword = \"hunter2\"");
+    let settings: PersistentSettings = toml::from_str(&legacy)?;
+    assert_eq!(settings.credentials[REGISTRY].password.as_deref(), Some("hunter2"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_secrets_are_kept_apart() -> Result<(), Error> {
+    let dir = temp_dir("credentials");
+    let path = RegistrySecrets::path(&dir);
+    let mut settings = settings_with_password();
+
+    let mut secrets = RegistrySecrets::load(&path).await?;
+    assert!(secrets.take_from(&mut settings));
+    assert_eq!(settings.credentials[REGISTRY].password, None);
+    assert_eq!(settings.credentials[REGISTRY].username.as_deref(), Some("tari"));
+    assert!(!secrets.take_from(&mut settings));
+    secrets.save(&path).await?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&path)?.permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    let loaded = RegistrySecrets::load(&path).await?;
+    assert_eq!(loaded, secrets);
+    loaded.fill(&mut settings);
+    assert_eq!(settings.credentials[REGISTRY].password.as_deref(), Some("hunter2"));
+    assert_eq!(settings.credentials[REGISTRY].username.as_deref(), Some("tari"));
+    Ok(())
+}
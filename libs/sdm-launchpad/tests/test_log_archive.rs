@@ -0,0 +1,218 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use std::{fs, path::PathBuf};
+
+use anyhow::Error;
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use tari_launchpad_protocol::container::{LogLevel, LogQuery, LogRecord, TaskId};
+use tari_sdm_launchpad::log_archive::{self, ArchiveWriter, LogArchive, RotationPolicy};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("launchpad-{}-{}", name, std::process::id()));
+    fs::remove_dir_all(&dir).ok();
+    dir
+}
+
+fn start() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2023, 7, 1)
+        .unwrap()
+        .and_hms_opt(12, 0, 0)
+        .unwrap()
+}
+
+fn record(n: i64) -> LogRecord {
+    LogRecord {
+        datetime: start() + Duration::seconds(n),
+        level: LogLevel::Info,
+        message: format!("line {}", n),
+    }
+}
+
+fn query(task_id: &TaskId, offset: usize, limit: usize) -> LogQuery {
+    LogQuery {
+        task_id: task_id.clone(),
+        since: None,
+        until: None,
+        offset,
+        limit,
+    }
+}
+
+fn messages(records: &[LogRecord]) -> Vec<String> {
+    records.iter().map(|record| record.message.clone()).collect()
+}
+
+fn archives(dir: &PathBuf) -> Vec<String> {
+    let mut names: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .filter(|name| name.ends_with(".log.gz"))
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_pages_of_the_current_file() -> Result<(), Error> {
+    let dir = temp_dir("pages");
+    let task_id = TaskId::from("Base Node");
+    let mut archive = LogArchive::new(dir.clone(), RotationPolicy::default());
+    for n in 0..5 {
+        archive.append(&task_id, &record(n))?;
+    }
+    archive.flush()?;
+    assert!(dir.join("base_node.log").exists());
+
+    let page = log_archive::query(&dir, &query(&task_id, 0, 2))?;
+    assert_eq!(messages(&page.records), vec!["line 0", "line 1"]);
+    assert!(page.has_more);
+
+    let page = log_archive::query(&dir, &query(&task_id, 4, 2))?;
+    assert_eq!(messages(&page.records), vec!["line 4"]);
+    assert!(!page.has_more);
+
+    let page = log_archive::query(&dir, &query(&TaskId::from("Tor"), 0, 2))?;
+    assert!(page.records.is_empty());
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_rotation_keeps_the_latest_archives() -> Result<(), Error> {
+    let dir = temp_dir("rotation");
+    let task_id = TaskId::from("Tor");
+    let policy = RotationPolicy {
+        max_age: Duration::seconds(10),
+        max_archives: 2,
+        ..Default::default()
+    };
+    let mut archive = LogArchive::new(dir.clone(), policy);
+    // Every 10 records go to a separate file
+    for n in 0..40 {
+        archive.append(&task_id, &record(n))?;
+    }
+    archive.flush()?;
+    assert_eq!(archives(&dir), vec![
+        "tor.20230701-120010.000.log.gz",
+        "tor.20230701-120020.000.log.gz"
+    ]);
+
+    // The records of the removed archives are lost
+    let page = log_archive::query(&dir, &query(&task_id, 0, 100))?;
+    assert_eq!(page.records.len(), 30);
+    assert_eq!(page.records[0].message, "line 10");
+    assert_eq!(page.records[29].message, "line 39");
+
+    // Pages continue across the files
+    let page = log_archive::query(&dir, &query(&task_id, 8, 4))?;
+    assert_eq!(messages(&page.records), vec![
+        "line 18", "line 19", "line 20", "line 21"
+    ]);
+    assert!(page.has_more);
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_rotation_by_size() -> Result<(), Error> {
+    let dir = temp_dir("size");
+    let task_id = TaskId::from("Loki");
+    let policy = RotationPolicy {
+        max_size: 1,
+        ..Default::default()
+    };
+    let mut archive = LogArchive::new(dir.clone(), policy);
+    for n in 0..3 {
+        archive.append(&task_id, &record(n))?;
+    }
+    archive.flush()?;
+    assert_eq!(archives(&dir).len(), 2);
+    let page = log_archive::query(&dir, &query(&task_id, 0, 10))?;
+    assert_eq!(messages(&page.records), vec!["line 0", "line 1", "line 2"]);
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_query_by_time_range() -> Result<(), Error> {
+    let dir = temp_dir("range");
+    let task_id = TaskId::from("Grafana");
+    let mut archive = LogArchive::new(dir.clone(), RotationPolicy::default());
+    for n in 0..10 {
+        archive.append(&task_id, &record(n))?;
+    }
+    archive.flush()?;
+    let query = LogQuery {
+        since: Some(start() + Duration::seconds(3)),
+        until: Some(start() + Duration::seconds(6)),
+        ..query(&task_id, 1, 10)
+    };
+    let page = log_archive::query(&dir, &query)?;
+    assert_eq!(messages(&page.records), vec!["line 4", "line 5", "line 6"]);
+    assert!(!page.has_more);
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_archive_continues_the_existing_file() -> Result<(), Error> {
+    let dir = temp_dir("continue");
+    let task_id = TaskId::from("XMRig");
+    {
+        let mut archive = LogArchive::new(dir.clone(), RotationPolicy::default());
+        archive.append(&task_id, &record(0))?;
+    }
+    let mut archive = LogArchive::new(dir.clone(), RotationPolicy::default());
+    archive.append(&task_id, &record(1))?;
+    archive.rotate(&task_id)?;
+    // The name of the archive is based on the first record of the previous run
+    assert_eq!(archives(&dir), vec!["xmrig.20230701-120000.000.log.gz"]);
+    let page = log_archive::query(&dir, &query(&task_id, 0, 10))?;
+    assert_eq!(messages(&page.records), vec!["line 0", "line 1"]);
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_writer_archives_in_its_own_thread() -> Result<(), Error> {
+    let dir = temp_dir("writer");
+    let task_id = TaskId::from("Tor");
+    let policy = RotationPolicy {
+        max_size: 100,
+        ..RotationPolicy::default()
+    };
+    let writer = ArchiveWriter::spawn(LogArchive::new(dir.clone(), policy));
+    for n in 0..5 {
+        writer.append(task_id.clone(), record(n));
+    }
+    // The records appended before the query are written already
+    let page = futures::executor::block_on(writer.query(query(&task_id, 0, 10)))?;
+    assert_eq!(messages(&page.records), vec![
+        "line 0", "line 1", "line 2", "line 3", "line 4"
+    ]);
+    assert!(!archives(&dir).is_empty());
+    drop(writer);
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
@@ -0,0 +1,101 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use std::collections::BTreeMap;
+
+use tari_launchpad_protocol::settings::TariNetwork;
+use tari_sdm::image::{ManagedContainer, Ports};
+use tari_sdm_launchpad::resources::{config::LaunchpadProtocol, images};
+
+const NETWORKS: [TariNetwork; 4] = [
+    TariNetwork::Nextnet,
+    TariNetwork::Mainnet,
+    TariNetwork::Stagenet,
+    TariNetwork::Igor,
+];
+
+/// Ports of other software a user could run next to the launchpad.
+const WELL_KNOWN: &[(u16, &str)] = &[
+    (9050, "Tor"),
+    (9051, "Tor control"),
+    (9150, "Tor Browser"),
+    (9151, "Tor Browser control"),
+    (10050, "Zabbix agent"),
+    (10051, "Zabbix server"),
+    (18080, "Monero P2P"),
+    (18081, "Monero RPC"),
+    (18083, "Monero ZMQ"),
+    (18089, "Monero restricted RPC"),
+    (18143, "Tari wallet gRPC"),
+    (18188, "Tari wallet P2P"),
+    (28080, "Monero testnet P2P"),
+    (28081, "Monero testnet RPC"),
+    (38080, "Monero stagenet P2P"),
+    (38081, "Monero stagenet RPC"),
+];
+
+fn published_ports() -> Vec<(&'static str, u16)> {
+    let containers: Vec<(&str, Box<dyn ManagedContainer<Protocol = LaunchpadProtocol>>)> = vec![
+        ("tor", Box::new(images::Tor::default())),
+        ("base node", Box::new(images::TariBaseNode::default())),
+        ("miner", Box::new(images::TariSha3Miner::default())),
+        ("mmproxy", Box::new(images::MmProxy::default())),
+        ("xmrig", Box::new(images::XMRig::default())),
+        ("grafana", Box::new(images::Grafana::default())),
+        ("loki", Box::new(images::Loki::default())),
+        ("promtail", Box::new(images::Promtail::default())),
+    ];
+    let mut published = Vec::new();
+    for (name, container) in containers {
+        let mut ports = Ports::default();
+        container.ports(&mut ports);
+        published.extend(ports.build().into_iter().map(|port| (name, port.host_port)));
+    }
+    published
+}
+
+#[test]
+fn test_networks_publish_distinct_ports() {
+    let published = published_ports();
+    assert!(!published.is_empty());
+    let mut taken = BTreeMap::new();
+    for network in NETWORKS {
+        let offset = network.port_offset();
+        for (name, port) in &published {
+            let host_port = port.checked_add(offset).expect("the shifted port overflows");
+            if let Some(other) = taken.insert(host_port, (network, *name)) {
+                panic!("{network:?} {name} and {other:?} both publish the port {host_port}");
+            }
+            if offset == 0 {
+                continue;
+            }
+            assert!(
+                host_port >= 1024,
+                "{network:?} {name} publishes a privileged port {host_port}"
+            );
+            if let Some((_, owner)) = WELL_KNOWN.iter().find(|(known, _)| *known == host_port) {
+                panic!("{network:?} {name} publishes the port {host_port} of {owner}");
+            }
+        }
+    }
+}
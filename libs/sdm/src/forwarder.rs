@@ -30,7 +30,7 @@ use tokio::sync::mpsc;
 use crate::utils::TaskGuard;
 
 pub trait Converter<I, O>: Sync + Send + 'static {
-    fn convert(&self, res: Result<I, Error>) -> Option<O>;
+    fn convert(&mut self, res: Result<I, Error>) -> Option<O>;
 }
 
 pub struct Forwarder<I, O> {
@@ -0,0 +1,310 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! The dependency graph of the tasks of a scope.
+//!
+//! Tasks refer to each other by free-form ids, so the graph is checked for unknown ids, cycles
+//! and resources used without a dependency, since any of them leaves a task waiting forever.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+};
+
+use serde::Serialize;
+use tari_launchpad_protocol::container::{TaskId, TaskState, TaskStatus};
+use thiserror::Error;
+
+use crate::runtime::ResourceKind;
+
+#[derive(Debug, Error)]
+pub enum GraphError {
+    #[error("Task {0} was added twice")]
+    Duplicate(TaskId),
+    #[error("Task {task} depends on the unknown task {dependency}")]
+    UnknownDependency { task: TaskId, dependency: TaskId },
+    #[error("Dependency cycle: {}", join(.0))]
+    Cycle(Vec<TaskId>),
+    #[error("Task {task} uses {resource} that is not a {} task", .kind.as_str())]
+    NotAResource {
+        task: TaskId,
+        resource: TaskId,
+        kind: ResourceKind,
+    },
+    #[error("Task {task} uses the {} {resource} that is not in its dependencies", .kind.as_str())]
+    UndeclaredResource {
+        task: TaskId,
+        resource: TaskId,
+        kind: ResourceKind,
+    },
+}
+
+fn join(path: &[TaskId]) -> String {
+    let path: Vec<_> = path.iter().map(TaskId::to_string).collect();
+    path.join(" -> ")
+}
+
+#[derive(Debug, Clone)]
+pub struct TaskNode {
+    pub id: TaskId,
+    pub kind: ResourceKind,
+    pub deps: Vec<TaskId>,
+    /// Networks and volumes used by a container
+    pub resources: Vec<(ResourceKind, TaskId)>,
+}
+
+impl TaskNode {
+    pub fn new(id: TaskId, kind: ResourceKind, deps: Vec<TaskId>) -> Self {
+        Self {
+            id,
+            kind,
+            deps,
+            resources: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct DependencyGraph {
+    /// Tasks in the order they were added
+    nodes: Vec<TaskNode>,
+}
+
+impl DependencyGraph {
+    pub fn nodes(&self) -> &[TaskNode] {
+        &self.nodes
+    }
+
+    fn get(&self, id: &TaskId) -> Option<&TaskNode> {
+        self.nodes.iter().find(|node| node.id == *id)
+    }
+
+    /// Adds a task if it doesn't make a cycle. Dependencies could be added later.
+    pub fn add(&mut self, node: TaskNode) -> Result<(), GraphError> {
+        if self.get(&node.id).is_some() {
+            return Err(GraphError::Duplicate(node.id));
+        }
+        for dependency in &node.deps {
+            let mut path = vec![node.id.clone()];
+            if self.find_path(dependency, &node.id, &mut path, &mut HashSet::new()) {
+                return Err(GraphError::Cycle(path));
+            }
+        }
+        self.nodes.push(node);
+        Ok(())
+    }
+
+    /// Looks for a path from `from` to `to` over the dependencies and fills the `path` if found.
+    fn find_path<'a>(
+        &'a self,
+        from: &'a TaskId,
+        to: &TaskId,
+        path: &mut Vec<TaskId>,
+        visited: &mut HashSet<&'a TaskId>,
+    ) -> bool {
+        path.push(from.clone());
+        if from == to {
+            return true;
+        }
+        if visited.insert(from) {
+            if let Some(node) = self.get(from) {
+                for dependency in &node.deps {
+                    if self.find_path(dependency, to, path, visited) {
+                        return true;
+                    }
+                }
+            }
+        }
+        path.pop();
+        false
+    }
+
+    /// Checks that all the dependencies and resources are known. Cycles are rejected by `add`.
+    pub fn validate(&self) -> Result<(), GraphError> {
+        for node in &self.nodes {
+            for dependency in &node.deps {
+                if self.get(dependency).is_none() {
+                    return Err(GraphError::UnknownDependency {
+                        task: node.id.clone(),
+                        dependency: dependency.clone(),
+                    });
+                }
+            }
+            for (kind, resource) in &node.resources {
+                if self.get(resource).map(|res| res.kind) != Some(*kind) {
+                    return Err(GraphError::NotAResource {
+                        task: node.id.clone(),
+                        resource: resource.clone(),
+                        kind: *kind,
+                    });
+                }
+                if !node.deps.contains(resource) {
+                    return Err(GraphError::UndeclaredResource {
+                        task: node.id.clone(),
+                        resource: resource.clone(),
+                        kind: *kind,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Groups tasks into stages in the reverse order of dependencies. A task gets to a stage
+    /// when no remaining task depends on it. Tasks of a dependency cycle are stopped together.
+    pub fn stop_stages(&self) -> Vec<HashSet<TaskId>> {
+        let all = self.nodes.iter().map(|node| node.id.clone()).collect();
+        self.stop_stages_of(&all)
+    }
+
+    /// Stop stages of a part of the graph. The dependants of the tasks should be included.
+    pub fn stop_stages_of(&self, tasks: &HashSet<TaskId>) -> Vec<HashSet<TaskId>> {
+        let mut remaining: Vec<&TaskNode> = self.nodes.iter().filter(|node| tasks.contains(&node.id)).collect();
+        let mut stages = Vec::new();
+        while !remaining.is_empty() {
+            let required: HashSet<&TaskId> = remaining.iter().flat_map(|node| &node.deps).collect();
+            let mut stage: HashSet<TaskId> = remaining
+                .iter()
+                .map(|node| &node.id)
+                .filter(|id| !required.contains(id))
+                .cloned()
+                .collect();
+            if stage.is_empty() {
+                stage = remaining.iter().map(|node| node.id.clone()).collect();
+            }
+            remaining.retain(|node| !stage.contains(&node.id));
+            stages.push(stage);
+        }
+        stages
+    }
+
+    /// Tasks that depend on the task directly or through other tasks.
+    pub fn dependants(&self, id: &TaskId) -> HashSet<TaskId> {
+        let mut found = HashSet::new();
+        let mut queue = vec![id];
+        while let Some(current) = queue.pop() {
+            for node in &self.nodes {
+                if node.deps.contains(current) && found.insert(node.id.clone()) {
+                    queue.push(&node.id);
+                }
+            }
+        }
+        found
+    }
+
+    /// Dependencies of the task that are not active according to the `states`.
+    pub fn blocked_by<'a>(&'a self, node: &'a TaskNode, states: &HashMap<TaskId, TaskState>) -> Vec<&'a TaskId> {
+        node.deps
+            .iter()
+            .filter(|dependency| !is_active(states.get(dependency)))
+            .collect()
+    }
+
+    /// Renders the graph in the Graphviz format. Edges point from a task to its dependencies,
+    /// the ones that block a task are red.
+    pub fn to_dot(&self, states: &HashMap<TaskId, TaskState>) -> String {
+        let mut dot = String::from("digraph sdm {\n    rankdir=LR;\n");
+        let mut unknown = HashSet::new();
+        for node in &self.nodes {
+            let shape = match node.kind {
+                ResourceKind::Container => "box",
+                ResourceKind::Network => "ellipse",
+                ResourceKind::Volume => "cylinder",
+            };
+            let state = states.get(&node.id);
+            let status = state.map(|state| state.status.to_string()).unwrap_or_default();
+            let color = if is_active(state) { "darkgreen" } else { "gray" };
+            writeln!(
+                dot,
+                "    {} [shape={}, color={}, label=\"{}\\n{}\"];",
+                quote(&node.id),
+                shape,
+                color,
+                escape(&node.id),
+                escape(&status)
+            )
+            .ok();
+            let blocked_by = self.blocked_by(node, states);
+            for dependency in &node.deps {
+                if self.get(dependency).is_none() {
+                    unknown.insert(dependency);
+                }
+                let color = if blocked_by.contains(&dependency) {
+                    "red"
+                } else {
+                    "black"
+                };
+                writeln!(
+                    dot,
+                    "    {} -> {} [color={}];",
+                    quote(&node.id),
+                    quote(dependency),
+                    color
+                )
+                .ok();
+            }
+        }
+        for id in unknown {
+            writeln!(dot, "    {} [shape=box, style=dashed, color=red];", quote(id)).ok();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Renders the graph with the statuses of the tasks as JSON.
+    pub fn to_json(&self, states: &HashMap<TaskId, TaskState>) -> Result<String, serde_json::Error> {
+        let tasks: Vec<_> = self
+            .nodes
+            .iter()
+            .map(|node| TaskView {
+                id: &node.id,
+                kind: node.kind.as_str(),
+                status: states.get(&node.id).map(|state| &state.status),
+                deps: &node.deps,
+                blocked_by: self.blocked_by(node, states),
+            })
+            .collect();
+        serde_json::to_string_pretty(&tasks)
+    }
+}
+
+#[derive(Serialize)]
+struct TaskView<'a> {
+    id: &'a TaskId,
+    kind: &'static str,
+    status: Option<&'a TaskStatus>,
+    deps: &'a [TaskId],
+    blocked_by: Vec<&'a TaskId>,
+}
+
+fn is_active(state: Option<&TaskState>) -> bool {
+    state.map(|state| state.status.is_active()).unwrap_or_default()
+}
+
+fn escape(value: &impl ToString) -> String {
+    value.to_string().replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn quote(id: &TaskId) -> String {
+    format!("\"{}\"", escape(id))
+}
@@ -0,0 +1,291 @@
+// Copyright 2022. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+mod probes;
+
+use std::{
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Error;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use derive_more::{Deref, DerefMut};
+use futures::{
+    future,
+    stream::{FusedStream, Stream, StreamExt},
+};
+pub use probes::{AllOf, AnyOf, GrpcHealth, HttpStatus, LogMatch, Probe, ProbeStatus, ReadyWhen, TcpPort};
+use tari_launchpad_protocol::container::{StatsData, TaskProgress};
+use tokio::{
+    select,
+    time::{interval_at, sleep_until, Duration, Instant, MissedTickBehavior},
+};
+
+use super::{log_parser::LogParser, task::Event};
+use crate::{
+    image::ManagedProtocol,
+    runtime::{LogLine, PortMapping, RuntimeStream},
+    scope::ControlEvent,
+    task::TaskSender,
+};
+
+/// The minimal interval between attempts to reconnect to the logs of a container.
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug)]
+pub enum CheckerEvent {
+    Progress(TaskProgress),
+    Ready,
+    /// The container will never be ready.
+    Failed(String),
+}
+
+pub struct CheckerContext<P: ManagedProtocol> {
+    logs: Logs,
+    parser: Box<dyn LogParser>,
+    stats: Stats,
+    ports: Vec<PortMapping>,
+    sender: TaskSender<Event, P>,
+}
+
+impl<P: ManagedProtocol> CheckerContext<P> {
+    pub(crate) fn new(
+        logs: Logs,
+        parser: Box<dyn LogParser>,
+        stats: Stats,
+        ports: Vec<PortMapping>,
+        sender: TaskSender<Event, P>,
+    ) -> Self {
+        Self {
+            logs,
+            parser,
+            stats,
+            ports,
+            sender,
+        }
+    }
+
+    /// The host port the container port is published on.
+    pub fn host_port(&self, container_port: u16) -> Option<u16> {
+        self.ports
+            .iter()
+            .find(|port| port.container_port == container_port)
+            .map(|port| port.host_port)
+    }
+
+    /// Reports the task about the progress.
+    pub fn report(&self, event: CheckerEvent) -> Result<(), Error> {
+        let event = Event::CheckerProgress(event);
+        self.sender.send_direct(event)
+    }
+
+    /// Notifies all tasks with the inner event.
+    pub fn notify(&self, event: P::Inner) -> Result<(), Error> {
+        let event = ControlEvent::InnerEvent(event);
+
+        self.sender.send_broadcast(event)
+    }
+}
+
+/// Polls a container for the logs and stats, and executes the related hooks for the event. The `on_interval` hook
+/// is called every second. The default implementation of all of the hooks do nothing.
+///
+/// In each of the hooks, a mutable reference to a `CheckerContext` is provided, which can be used to access / update
+/// the log and stats history, and update the progress of a task.
+#[async_trait]
+pub trait ContainerChecker<P: ManagedProtocol>: Send {
+    async fn entrypoint(mut self: Box<Self>, mut ctx: CheckerContext<P>) {
+        let progress = TaskProgress::new("Starting...");
+        ctx.report(CheckerEvent::Progress(progress)).ok();
+        // Streams of logs and stats must not delay the interval
+        let period = Duration::from_secs(1);
+        let mut interval = interval_at(Instant::now() + period, period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            let reconnect_at = ctx.logs.reconnect_at();
+            select! {
+                log_event = ctx.logs.next(), if !ctx.logs.is_terminated() => {
+                    if let Some(Ok(msg)) = log_event {
+                        self.on_log_event(&msg, &mut ctx).await;
+                        let record = ctx.parser.parse_or_plain(&msg);
+                        ctx.sender.send_log_record(record).ok();
+                    }
+                }
+                stat_event = ctx.stats.next(), if !ctx.stats.is_terminated() => {
+                    if let Some(Ok(msg)) = stat_event {
+                        self.on_stat_event(&msg, &mut ctx).await;
+                        ctx.sender.send_stats(msg).ok();
+                    }
+                }
+                _ = sleep_until(reconnect_at), if ctx.logs.is_terminated() => {
+                    log::debug!("The log stream is closed. Reconnecting...");
+                    ctx.logs.reconnect();
+                }
+                _ = interval.tick() => {
+                    if let Err(err) = self.on_interval(&mut ctx).await {
+                        log::error!("On interval checker failed: {}", err);
+                    }
+                }
+            }
+        }
+    }
+
+    async fn on_log_event(&mut self, _record: &str, _ctx: &mut CheckerContext<P>) {}
+
+    async fn on_stat_event(&mut self, _record: &StatsData, _ctx: &mut CheckerContext<P>) {}
+
+    async fn on_interval(&mut self, _ctx: &mut CheckerContext<P>) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct ReadyIfStarted {
+    started: bool,
+}
+
+#[async_trait]
+impl<P: ManagedProtocol> ContainerChecker<P> for ReadyIfStarted {
+    async fn on_interval(&mut self, ctx: &mut CheckerContext<P>) -> Result<(), Error> {
+        if !self.started {
+            ctx.report(CheckerEvent::Ready)?;
+            self.started = true;
+        }
+        Ok(())
+    }
+}
+
+/// The position in the logs of a container. It's kept by the task while the container exists, so
+/// reconnected streams continue where the previous ones stopped.
+#[derive(Debug, Default)]
+pub struct LogCursor {
+    last: Option<DateTime<Utc>>,
+    /// Messages received at the `last` timestamp
+    boundary: Vec<String>,
+}
+
+impl LogCursor {
+    /// The timestamp to request the logs since.
+    pub fn since(&self) -> Option<DateTime<Utc>> {
+        self.last
+    }
+
+    /// Moves the cursor and returns `false` if the line was received already.
+    pub fn accept(&mut self, line: &LogLine) -> bool {
+        let timestamp = match line.timestamp {
+            Some(timestamp) => timestamp,
+            // Lines without timestamps can't be compared
+            None => return true,
+        };
+        match self.last {
+            Some(last) if timestamp < last => false,
+            Some(last) if timestamp == last => {
+                if self.boundary.contains(&line.message) {
+                    false
+                } else {
+                    self.boundary.push(line.message.clone());
+                    true
+                }
+            },
+            _ => {
+                self.last = Some(timestamp);
+                self.boundary = vec![line.message.clone()];
+                true
+            },
+        }
+    }
+}
+
+/// Opens the log stream of a container since the given time.
+pub type LogSource = Box<dyn Fn(Option<DateTime<Utc>>) -> RuntimeStream<LogLine> + Send>;
+
+#[derive(Deref, DerefMut)]
+pub struct Logs {
+    #[deref]
+    #[deref_mut]
+    stream: Pin<Box<dyn FusedStream<Item = Result<String, Error>> + Send>>,
+    source: LogSource,
+    cursor: Arc<Mutex<LogCursor>>,
+    reconnect_at: Instant,
+}
+
+impl Logs {
+    pub fn new(source: LogSource, cursor: Arc<Mutex<LogCursor>>) -> Self {
+        let stream = Self::open(&source, &cursor);
+        Self {
+            stream,
+            source,
+            cursor,
+            reconnect_at: Instant::now() + RECONNECT_INTERVAL,
+        }
+    }
+
+    /// Opens the stream since the last received line and skips the lines that were received already.
+    fn open(
+        source: &LogSource,
+        cursor: &Arc<Mutex<LogCursor>>,
+    ) -> Pin<Box<dyn FusedStream<Item = Result<String, Error>> + Send>> {
+        let since = cursor.lock().ok().and_then(|cursor| cursor.since());
+        let cursor = cursor.clone();
+        let stream = source(since).filter_map(move |res| {
+            let item = match res {
+                Ok(line) => {
+                    let fresh = cursor.lock().map(|mut cursor| cursor.accept(&line)).unwrap_or(true);
+                    fresh.then_some(Ok(line.message))
+                },
+                Err(err) => Some(Err(err)),
+            };
+            future::ready(item)
+        });
+        Box::pin(stream.fuse())
+    }
+
+    /// The time when the closed stream could be opened again.
+    pub fn reconnect_at(&self) -> Instant {
+        self.reconnect_at
+    }
+
+    pub fn reconnect(&mut self) {
+        self.stream = Self::open(&self.source, &self.cursor);
+        self.reconnect_at = Instant::now() + RECONNECT_INTERVAL;
+    }
+}
+
+#[derive(Deref, DerefMut)]
+pub struct Stats {
+    stream: Pin<Box<dyn FusedStream<Item = Result<StatsData, Error>> + Send>>,
+}
+
+impl Stats {
+    pub fn new<S>(stream: S) -> Self
+    where
+        S: Stream<Item = Result<StatsData, Error>>,
+        S: Send + 'static,
+    {
+        Self {
+            stream: Box::pin(stream.fuse()),
+        }
+    }
+}
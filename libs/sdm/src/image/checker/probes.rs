@@ -0,0 +1,417 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Reusable readiness checks.
+//!
+//! A [`Probe`] checks a single condition, like an open port or a line in the logs. Probes are combined
+//! with [`AllOf`] and [`AnyOf`], and [`ReadyWhen`] reports the task as ready once the probe passes.
+
+use std::ops::RangeInclusive;
+
+use anyhow::Error;
+use async_trait::async_trait;
+use regex::Regex;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    time::{timeout, Duration, Instant},
+};
+use tonic::{codec::ProstCodec, codegen::http::uri::PathAndQuery, transport::Endpoint, Code};
+
+use super::{CheckerContext, CheckerEvent, ContainerChecker};
+use crate::{
+    image::{log_parser::strip_ansi, ManagedProtocol},
+    runtime::PortMapping,
+};
+
+/// How long a single attempt to connect to a container could take.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProbeStatus {
+    Ready,
+    /// The condition is not met yet, it's checked again on the next interval.
+    Waiting,
+    /// The condition will never be met, the container has to be restarted.
+    Failed(String),
+}
+
+#[async_trait]
+pub trait Probe: Send {
+    /// Inspects a line of the logs of the container.
+    fn on_log_event(&mut self, _record: &str) {}
+
+    /// Checks the condition. `ports` are the ports published by the container.
+    async fn check(&mut self, ports: &[PortMapping]) -> ProbeStatus;
+}
+
+/// The host port the container port is published on. Ports that are not published are used as is.
+fn host_port(ports: &[PortMapping], container_port: u16) -> u16 {
+    ports
+        .iter()
+        .find(|port| port.container_port == container_port)
+        .map(|port| port.host_port)
+        .unwrap_or(container_port)
+}
+
+async fn connect(port: u16) -> Option<TcpStream> {
+    timeout(CONNECT_TIMEOUT, TcpStream::connect(("127.0.0.1", port)))
+        .await
+        .ok()?
+        .ok()
+}
+
+/// The container accepts TCP connections on the port.
+///
+/// A published port is opened by the proxy of the engine when the container starts, so the probe
+/// can't tell it apart from a listening service. Prefer probes that need an answer from the service.
+pub struct TcpPort {
+    port: u16,
+}
+
+impl TcpPort {
+    pub fn new(port: u16) -> Self {
+        Self { port }
+    }
+}
+
+#[async_trait]
+impl Probe for TcpPort {
+    async fn check(&mut self, ports: &[PortMapping]) -> ProbeStatus {
+        match connect(host_port(ports, self.port)).await {
+            Some(_) => ProbeStatus::Ready,
+            None => ProbeStatus::Waiting,
+        }
+    }
+}
+
+/// The container responds to `GET {path}` with an expected HTTP status, `2xx` by default.
+pub struct HttpStatus {
+    port: u16,
+    path: String,
+    expected: RangeInclusive<u16>,
+}
+
+impl HttpStatus {
+    pub fn new(port: u16, path: &str) -> Self {
+        Self {
+            port,
+            path: path.to_string(),
+            expected: 200..=299,
+        }
+    }
+
+    pub fn expect(mut self, expected: RangeInclusive<u16>) -> Self {
+        self.expected = expected;
+        self
+    }
+
+    async fn status(&self, port: u16) -> Result<u16, Error> {
+        let mut stream = connect(port)
+            .await
+            .ok_or_else(|| Error::msg("Can't connect to the container"))?;
+        let request = format!(
+            "GET {} HTTP/1.0\r\nHost: 127.0.0.1:{}\r\nConnection: close\r\n\r\n",
+            self.path, port
+        );
+        stream.write_all(request.as_bytes()).await?;
+        // Only the status line is required: `HTTP/1.1 200 OK`
+        let mut buf = [0; 32];
+        let len = timeout(CONNECT_TIMEOUT, stream.read(&mut buf)).await??;
+        let line = String::from_utf8_lossy(&buf[..len]);
+        let status = line
+            .split_whitespace()
+            .nth(1)
+            .ok_or_else(|| Error::msg(format!("Invalid HTTP response: {}", line)))?;
+        Ok(status.parse()?)
+    }
+}
+
+#[async_trait]
+impl Probe for HttpStatus {
+    async fn check(&mut self, ports: &[PortMapping]) -> ProbeStatus {
+        match self.status(host_port(ports, self.port)).await {
+            Ok(status) if self.expected.contains(&status) => ProbeStatus::Ready,
+            Ok(status) => {
+                log::trace!("{} responded with {}", self.path, status);
+                ProbeStatus::Waiting
+            },
+            Err(err) => {
+                log::trace!("{} is not available: {}", self.path, err);
+                ProbeStatus::Waiting
+            },
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct HealthCheckRequest {
+    #[prost(string, tag = "1")]
+    service: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct HealthCheckResponse {
+    #[prost(int32, tag = "1")]
+    status: i32,
+}
+
+/// `SERVING` in `grpc.health.v1.HealthCheckResponse.ServingStatus`
+const SERVING: i32 = 1;
+
+/// The container reports the service as serving with the standard gRPC health checking protocol.
+pub struct GrpcHealth {
+    port: u16,
+    /// An empty name asks for the health of the whole server
+    service: String,
+}
+
+impl GrpcHealth {
+    pub fn new(port: u16) -> Self {
+        Self {
+            port,
+            service: String::new(),
+        }
+    }
+
+    pub fn service(mut self, service: &str) -> Self {
+        self.service = service.to_string();
+        self
+    }
+
+    async fn status(&self, port: u16) -> Result<i32, tonic::Status> {
+        let channel = Endpoint::from_shared(format!("http://127.0.0.1:{}", port))
+            .map_err(|err| tonic::Status::invalid_argument(err.to_string()))?
+            .connect_timeout(CONNECT_TIMEOUT)
+            .timeout(CONNECT_TIMEOUT)
+            .connect()
+            .await
+            .map_err(|err| tonic::Status::unavailable(err.to_string()))?;
+        let mut client = tonic::client::Grpc::new(channel);
+        client
+            .ready()
+            .await
+            .map_err(|err| tonic::Status::unavailable(err.to_string()))?;
+        let request = HealthCheckRequest {
+            service: self.service.clone(),
+        };
+        let path = PathAndQuery::from_static("/grpc.health.v1.Health/Check");
+        let response: tonic::Response<HealthCheckResponse> = client
+            .unary(tonic::Request::new(request), path, ProstCodec::default())
+            .await?;
+        Ok(response.into_inner().status)
+    }
+}
+
+#[async_trait]
+impl Probe for GrpcHealth {
+    async fn check(&mut self, ports: &[PortMapping]) -> ProbeStatus {
+        match self.status(host_port(ports, self.port)).await {
+            Ok(SERVING) => ProbeStatus::Ready,
+            Ok(_) => ProbeStatus::Waiting,
+            Err(status) if status.code() == Code::Unimplemented => {
+                ProbeStatus::Failed("The gRPC health service is not implemented".into())
+            },
+            Err(status) => {
+                log::trace!("The gRPC health check failed: {}", status);
+                ProbeStatus::Waiting
+            },
+        }
+    }
+}
+
+/// A line of the logs matches the pattern, the color codes are removed before matching.
+/// If `timeout` is set, the probe fails if the line didn't appear in time.
+pub struct LogMatch {
+    re: Regex,
+    matched: bool,
+    deadline: Option<Instant>,
+}
+
+impl LogMatch {
+    /// # Panics
+    ///
+    /// Panics if the pattern is not a valid regular expression.
+    pub fn new(pattern: &str) -> Self {
+        Self {
+            re: Regex::new(pattern).expect("invalid log pattern"),
+            matched: false,
+            deadline: None,
+        }
+    }
+
+    /// The timeout is counted from the creation of the probe, i.e. from the start of the container.
+    pub fn timeout(mut self, duration: Duration) -> Self {
+        self.deadline = Some(Instant::now() + duration);
+        self
+    }
+}
+
+#[async_trait]
+impl Probe for LogMatch {
+    fn on_log_event(&mut self, record: &str) {
+        if !self.matched && self.re.is_match(&strip_ansi(record)) {
+            self.matched = true;
+        }
+    }
+
+    async fn check(&mut self, _ports: &[PortMapping]) -> ProbeStatus {
+        match self.deadline {
+            _ if self.matched => ProbeStatus::Ready,
+            Some(deadline) if deadline <= Instant::now() => {
+                ProbeStatus::Failed(format!("No log line matched `{}` in time", self.re))
+            },
+            _ => ProbeStatus::Waiting,
+        }
+    }
+}
+
+/// All the probes have to pass.
+pub struct AllOf {
+    probes: Vec<Box<dyn Probe>>,
+}
+
+impl AllOf {
+    pub fn new() -> Self {
+        Self { probes: Vec::new() }
+    }
+
+    pub fn with(mut self, probe: impl Probe + 'static) -> Self {
+        self.probes.push(Box::new(probe));
+        self
+    }
+}
+
+impl Default for AllOf {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Probe for AllOf {
+    fn on_log_event(&mut self, record: &str) {
+        for probe in &mut self.probes {
+            probe.on_log_event(record);
+        }
+    }
+
+    async fn check(&mut self, ports: &[PortMapping]) -> ProbeStatus {
+        let mut result = ProbeStatus::Ready;
+        for probe in &mut self.probes {
+            match probe.check(ports).await {
+                ProbeStatus::Ready => {},
+                ProbeStatus::Waiting => result = ProbeStatus::Waiting,
+                failed @ ProbeStatus::Failed(_) => return failed,
+            }
+        }
+        result
+    }
+}
+
+/// At least one of the probes has to pass.
+pub struct AnyOf {
+    probes: Vec<Box<dyn Probe>>,
+}
+
+impl AnyOf {
+    pub fn new() -> Self {
+        Self { probes: Vec::new() }
+    }
+
+    pub fn with(mut self, probe: impl Probe + 'static) -> Self {
+        self.probes.push(Box::new(probe));
+        self
+    }
+}
+
+impl Default for AnyOf {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Probe for AnyOf {
+    fn on_log_event(&mut self, record: &str) {
+        for probe in &mut self.probes {
+            probe.on_log_event(record);
+        }
+    }
+
+    async fn check(&mut self, ports: &[PortMapping]) -> ProbeStatus {
+        let mut reasons = Vec::new();
+        for probe in &mut self.probes {
+            match probe.check(ports).await {
+                ProbeStatus::Ready => return ProbeStatus::Ready,
+                ProbeStatus::Waiting => {},
+                ProbeStatus::Failed(reason) => reasons.push(reason),
+            }
+        }
+        if !reasons.is_empty() && reasons.len() == self.probes.len() {
+            ProbeStatus::Failed(reasons.join("; "))
+        } else {
+            ProbeStatus::Waiting
+        }
+    }
+}
+
+/// Reports the task as ready once the probe passes, or as failed if the probe fails.
+pub struct ReadyWhen {
+    probe: Box<dyn Probe>,
+    done: bool,
+}
+
+impl ReadyWhen {
+    pub fn new(probe: impl Probe + 'static) -> Self {
+        Self {
+            probe: Box::new(probe),
+            done: false,
+        }
+    }
+}
+
+#[async_trait]
+impl<P: ManagedProtocol> ContainerChecker<P> for ReadyWhen {
+    async fn on_log_event(&mut self, record: &str, _ctx: &mut CheckerContext<P>) {
+        self.probe.on_log_event(record);
+    }
+
+    async fn on_interval(&mut self, ctx: &mut CheckerContext<P>) -> Result<(), Error> {
+        if self.done {
+            return Ok(());
+        }
+        match self.probe.check(&ctx.ports).await {
+            ProbeStatus::Ready => {
+                self.done = true;
+                ctx.report(CheckerEvent::Ready)?;
+            },
+            ProbeStatus::Waiting => {},
+            ProbeStatus::Failed(reason) => {
+                self.done = true;
+                ctx.report(CheckerEvent::Failed(reason))?;
+            },
+        }
+        Ok(())
+    }
+}
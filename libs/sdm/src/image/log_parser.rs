@@ -0,0 +1,292 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Parsers of the output of containers.
+//!
+//! Every image writes logs in its own format. A parser extracts the level, the time and the message of a line,
+//! so the logs could be filtered by the level and errors could be reported as fails of a task.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveDateTime, NaiveTime};
+use regex::Regex;
+use tari_launchpad_protocol::container::{LogLevel, LogRecord};
+
+/// Extracts a record from a line of logs.
+pub trait LogParser: Send + Sync {
+    /// Returns `None` if the line has a different format.
+    fn parse(&self, line: &str) -> Option<LogRecord>;
+
+    /// Parses the line, or keeps it as is with the `Info` level.
+    fn parse_or_plain(&self, line: &str) -> LogRecord {
+        self.parse(line).unwrap_or_else(|| plain(line))
+    }
+}
+
+/// Keeps lines as they are.
+#[derive(Debug, Default)]
+pub struct PlainParser;
+
+impl LogParser for PlainParser {
+    fn parse(&self, line: &str) -> Option<LogRecord> {
+        Some(plain(line))
+    }
+}
+
+/// The format of `log4rs` used by Tari binaries: `2023-07-12 10:11:12.345 [target] INFO  message`.
+/// The console pattern contains the time only: `10:11 INFO  message`.
+#[derive(Debug)]
+pub struct Log4rsParser {
+    re: Regex,
+}
+
+impl Default for Log4rsParser {
+    fn default() -> Self {
+        let re = Regex::new(concat!(
+            r"^(?:(?P<date>\d{4}-\d{2}-\d{2}[ T]\d{2}:\d{2}:\d{2}(?:\.\d+)?)|(?P<time>\d{2}:\d{2}(?::\d{2})?))?",
+            r"\s*(?:\[[^\]]*\]\s*)*(?P<level>TRACE|DEBUG|INFO|WARN|ERROR)\s+(?P<message>.*)$"
+        ))
+        .expect("Invalid log4rs pattern");
+        Self { re }
+    }
+}
+
+impl LogParser for Log4rsParser {
+    fn parse(&self, line: &str) -> Option<LogRecord> {
+        let line = strip_ansi(line);
+        let caps = self.re.captures(&line)?;
+        let level = parse_level(&caps["level"])?;
+        let datetime = if let Some(date) = caps.name("date") {
+            parse_datetime(date.as_str(), &["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"])
+        } else if let Some(time) = caps.name("time") {
+            parse_time(time.as_str())
+        } else {
+            None
+        };
+        Some(LogRecord {
+            datetime: datetime.unwrap_or_else(now),
+            level,
+            message: caps["message"].trim_end().to_string(),
+        })
+    }
+}
+
+/// The format of Tor: `Jul 12 10:11:12.345 [notice] message`.
+#[derive(Debug)]
+pub struct TorParser {
+    re: Regex,
+}
+
+impl Default for TorParser {
+    fn default() -> Self {
+        let re = Regex::new(
+            r"^(?P<date>[A-Z][a-z]{2} +\d{1,2} \d{2}:\d{2}:\d{2}(?:\.\d+)?) \[(?P<level>\w+)\] (?P<message>.*)$",
+        )
+        .expect("Invalid Tor pattern");
+        Self { re }
+    }
+}
+
+impl LogParser for TorParser {
+    fn parse(&self, line: &str) -> Option<LogRecord> {
+        let caps = self.re.captures(line)?;
+        let level = match &caps["level"] {
+            "debug" => LogLevel::Debug,
+            "info" | "notice" => LogLevel::Info,
+            "warn" => LogLevel::Warn,
+            "err" => LogLevel::Error,
+            _ => return None,
+        };
+        // Tor doesn't print the year
+        let date = format!("{} {}", Local::now().year(), &caps["date"]);
+        let datetime = parse_datetime(&date, &["%Y %b %e %H:%M:%S%.f"]);
+        Some(LogRecord {
+            datetime: datetime.unwrap_or_else(now),
+            level,
+            message: caps["message"].to_string(),
+        })
+    }
+}
+
+/// The colored output of XMRig: `[2023-07-12 10:11:12.345]  net  message`. XMRig doesn't print levels,
+/// but it colors errors red and warnings yellow.
+#[derive(Debug)]
+pub struct XmrigParser {
+    re: Regex,
+}
+
+impl Default for XmrigParser {
+    fn default() -> Self {
+        let re = Regex::new(r"^\[(?P<date>\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}(?:\.\d+)?)\]\s+(?P<message>.*)$")
+            .expect("Invalid XMRig pattern");
+        Self { re }
+    }
+}
+
+impl LogParser for XmrigParser {
+    fn parse(&self, line: &str) -> Option<LogRecord> {
+        let level = if line.contains("\x1b[31m") || line.contains("\x1b[1;31m") {
+            LogLevel::Error
+        } else if line.contains("\x1b[33m") || line.contains("\x1b[1;33m") {
+            LogLevel::Warn
+        } else {
+            LogLevel::Info
+        };
+        let line = strip_ansi(line);
+        let caps = self.re.captures(&line)?;
+        let datetime = parse_datetime(&caps["date"], &["%Y-%m-%d %H:%M:%S%.f"]);
+        Some(LogRecord {
+            datetime: datetime.unwrap_or_else(now),
+            level,
+            message: caps["message"].trim_end().to_string(),
+        })
+    }
+}
+
+/// The `logfmt` format of Grafana, Loki and Promtail: `level=warn ts=2023-07-12T10:11:12.345Z msg="message"`.
+#[derive(Debug, Default)]
+pub struct LogfmtParser;
+
+impl LogParser for LogfmtParser {
+    fn parse(&self, line: &str) -> Option<LogRecord> {
+        let pairs = logfmt_pairs(line);
+        let level = match pairs.get("level").or_else(|| pairs.get("lvl"))?.to_lowercase().as_str() {
+            "trace" => LogLevel::Trace,
+            "debug" | "dbug" => LogLevel::Debug,
+            "info" => LogLevel::Info,
+            "warn" | "warning" => LogLevel::Warn,
+            "error" | "eror" | "crit" | "fatal" => LogLevel::Error,
+            _ => return None,
+        };
+        let datetime = pairs
+            .get("ts")
+            .or_else(|| pairs.get("t"))
+            .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+            .map(|ts| ts.with_timezone(&Local).naive_local());
+        let mut message = pairs.get("msg").cloned().unwrap_or_else(|| line.to_string());
+        if let Some(err) = pairs.get("err").or_else(|| pairs.get("error")) {
+            message = format!("{}: {}", message, err);
+        }
+        Some(LogRecord {
+            datetime: datetime.unwrap_or_else(now),
+            level,
+            message,
+        })
+    }
+}
+
+/// Splits a line to `key=value` pairs. Values could be quoted.
+fn logfmt_pairs(line: &str) -> HashMap<&str, String> {
+    let mut pairs = HashMap::new();
+    let mut rest = line.trim();
+    while !rest.is_empty() {
+        let Some((key, tail)) = rest.split_once('=') else {
+            break;
+        };
+        let key = key.trim();
+        let (value, tail) = if let Some(quoted) = tail.strip_prefix('"') {
+            let mut value = String::new();
+            let mut chars = quoted.char_indices();
+            let mut end = quoted.len();
+            while let Some((idx, ch)) = chars.next() {
+                match ch {
+                    '\\' => {
+                        if let Some((_, escaped)) = chars.next() {
+                            value.push(escaped);
+                        }
+                    },
+                    '"' => {
+                        end = idx + 1;
+                        break;
+                    },
+                    _ => value.push(ch),
+                }
+            }
+            (value, &quoted[end..])
+        } else {
+            let end = tail.find(' ').unwrap_or(tail.len());
+            (tail[..end].to_string(), &tail[end..])
+        };
+        pairs.insert(key, value);
+        rest = tail.trim_start();
+    }
+    pairs
+}
+
+fn plain(line: &str) -> LogRecord {
+    LogRecord {
+        datetime: now(),
+        level: LogLevel::Info,
+        message: strip_ansi(line),
+    }
+}
+
+fn now() -> NaiveDateTime {
+    Local::now().naive_local()
+}
+
+fn parse_level(level: &str) -> Option<LogLevel> {
+    match level {
+        "TRACE" => Some(LogLevel::Trace),
+        "DEBUG" => Some(LogLevel::Debug),
+        "INFO" => Some(LogLevel::Info),
+        "WARN" => Some(LogLevel::Warn),
+        "ERROR" => Some(LogLevel::Error),
+        _ => None,
+    }
+}
+
+fn parse_datetime(value: &str, formats: &[&str]) -> Option<NaiveDateTime> {
+    formats
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+}
+
+/// A time without a date is considered as a time of today.
+fn parse_time(value: &str) -> Option<NaiveDateTime> {
+    let time = NaiveTime::parse_from_str(value, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(value, "%H:%M"))
+        .ok()?;
+    let today: NaiveDate = Local::now().date_naive();
+    Some(today.and_time(time))
+}
+
+/// Removes the color codes of terminals.
+pub fn strip_ansi(line: &str) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch == '\x1b' && chars.peek() == Some(&'[') {
+            chars.next();
+            // Parameters end with a letter
+            for ch in chars.by_ref() {
+                if ch.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            result.push(ch);
+        }
+    }
+    result
+}
@@ -22,15 +22,20 @@
 //
 
 pub mod checker;
+pub mod log_parser;
 mod task;
 
-use std::fmt;
+use std::{fmt, time::Duration};
 
 use checker::{ContainerChecker, ReadyIfStarted};
+use log_parser::{LogParser, PlainParser};
 use tari_launchpad_protocol::container::TaskId;
 pub(crate) use task::ImageTask;
 
-use crate::config::ManagedProtocol;
+use crate::{
+    config::ManagedProtocol,
+    runtime::{PortMapping, RegistryAuth, Resources},
+};
 
 /// A container that can be managed by SDM.
 pub trait ManagedContainer: fmt::Debug + Send + 'static {
@@ -40,6 +45,11 @@ pub trait ManagedContainer: fmt::Debug + Send + 'static {
         Box::<ReadyIfStarted>::default()
     }
 
+    /// The parser of the output of the container. Error lines are reported as fails of the task.
+    fn log_parser(&self) -> Box<dyn LogParser> {
+        Box::<PlainParser>::default()
+    }
+
     /// Reconfigures the task and return a flag should the container be active?
     fn reconfigure(&mut self, config: Option<&<Self::Protocol as ManagedProtocol>::Config>) -> Option<bool> {
         // Start if config exists
@@ -48,6 +58,10 @@ pub trait ManagedContainer: fmt::Debug + Send + 'static {
 
     fn on_event(&mut self, _event: <Self::Protocol as ManagedProtocol>::Inner) {}
 
+    /// Called before the container is created with the host ports of a ready dependency.
+    /// Relocatable ports could differ from the requested ones.
+    fn on_dependency_ports(&mut self, _task_id: &TaskId, _ports: &[PortMapping]) {}
+
     fn registry(&self) -> &str;
 
     fn image_name(&self) -> &str;
@@ -56,6 +70,17 @@ pub trait ManagedContainer: fmt::Debug + Send + 'static {
         "latest"
     }
 
+    /// The digest the image is pinned to, like `sha256:...`. The task warns if the local image
+    /// has a different digest.
+    fn digest(&self) -> Option<&str> {
+        None
+    }
+
+    /// Credentials of the registry. If there are none, the runtime could use its own ones.
+    fn credentials(&self) -> Option<RegistryAuth> {
+        None
+    }
+
     fn args(&self, _args: &mut Args) {}
 
     fn envs(&self, _envs: &mut Envs) {}
@@ -67,6 +92,73 @@ pub trait ManagedContainer: fmt::Debug + Send + 'static {
     fn volumes(&self, _volumes: &mut Volumes) {}
 
     fn mounts(&self, _mounts: &mut Mounts) {}
+
+    /// CPU, memory and processes limits of the container.
+    fn resources(&self) -> Resources {
+        Resources::default()
+    }
+
+    /// Whether the container should be brought back if it exits by itself.
+    fn restart_policy(&self) -> RestartPolicy {
+        RestartPolicy::Never
+    }
+
+    fn timeouts(&self) -> Timeouts {
+        Timeouts::default()
+    }
+}
+
+/// Deadlines for the transient states of a container. `None` disables a deadline.
+#[derive(Debug, Clone)]
+pub struct Timeouts {
+    pub pull: Option<Duration>,
+    pub create: Option<Duration>,
+    pub start: Option<Duration>,
+    /// How long a started container could stay not ready.
+    pub ready: Option<Duration>,
+    /// A grace period to stop the container before it's killed.
+    pub stop: Duration,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            pull: Some(Duration::from_secs(60 * 60)),
+            create: Some(Duration::from_secs(30)),
+            start: Some(Duration::from_secs(30)),
+            ready: None,
+            stop: Duration::from_secs(10),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RestartPolicy {
+    #[default]
+    Never,
+    /// Restarts the container if it exited with a non-zero code, but no more than `max_attempts` times in a row.
+    OnFailure {
+        max_attempts: u32,
+    },
+    Always,
+}
+
+impl RestartPolicy {
+    pub fn max_attempts(&self) -> Option<u32> {
+        match self {
+            Self::OnFailure { max_attempts } => Some(*max_attempts),
+            Self::Never | Self::Always => None,
+        }
+    }
+
+    /// Checks whether the next restart is allowed after the given number of attempts.
+    pub fn should_restart(&self, failed: bool, attempts: u32) -> bool {
+        match self {
+            Self::Never => false,
+            Self::OnFailure { max_attempts } => failed && attempts < *max_attempts,
+            Self::Always => true,
+        }
+    }
 }
 
 #[derive(Default)]
@@ -106,21 +198,25 @@ impl Envs {
     }
 }
 
+/// Published ports. By default ports are available on all interfaces,
+/// use [`PortMapping::localhost`] for ports that shouldn't be reachable from other hosts.
 #[derive(Default)]
-pub struct Ports(Vec<u16>);
+pub struct Ports(Vec<PortMapping>);
 
 impl Ports {
-    pub fn add(&mut self, port: u16) {
-        // let item = (port, port);
-        self.0.push(port);
+    /// Publishes the port with the same number on the host.
+    pub fn add(&mut self, port: u16) -> &mut PortMapping {
+        self.forward(port, port)
     }
 
-    // pub fn forward(&mut self, from: u16, to: u16) {
-    // let item = (from, to);
-    // self.0.push(item);
-    // }
+    /// Publishes the `container` port as the `host` port.
+    pub fn forward(&mut self, host: u16, container: u16) -> &mut PortMapping {
+        self.0.push(PortMapping::new(host, container));
+        let last = self.0.len() - 1;
+        &mut self.0[last]
+    }
 
-    pub fn build(self) -> Vec<u16> {
+    pub fn build(self) -> Vec<PortMapping> {
         self.0
     }
 }
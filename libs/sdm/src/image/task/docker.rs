@@ -21,27 +21,11 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
-use std::{collections::HashMap, path::Path};
-
 use anyhow::{anyhow, Error};
-use bollard::{
-    container::{
-        Config, CreateContainerOptions, LogOutput, LogsOptions, NetworkingConfig, RemoveContainerOptions,
-        Stats as BollardStats, StatsOptions, StopContainerOptions,
-    },
-    errors::Error as BollardError,
-    image::{CreateImageOptions, RemoveImageOptions},
-    models::{
-        ContainerStateStatusEnum, CreateImageInfo, EndpointSettings, EventMessage, EventMessageTypeEnum, HostConfig,
-        Mount as BollardMount, MountTypeEnum, PortBinding, PortMap,
-    },
-    system::EventsOptions,
-};
 use chrono::Local;
-use futures::{StreamExt, TryStreamExt};
-use tari_launchpad_protocol::container::{StatsData, TaskProgress};
+use tari_launchpad_protocol::container::{ImageRecord, LogLevel, TaskDelta};
 
-use super::{ContainerState, Event, ImageTask};
+use super::{progress::PullTracker, Event, ImageTask};
 use crate::{
     config::ManagedProtocol,
     forwarder::{Converter, Forwarder},
@@ -49,23 +33,18 @@ use crate::{
         checker::{Logs, Stats},
         Args, Envs, Mount, Mounts, Networks, Ports, Volumes,
     },
+    runtime::{
+        AuthError, ContainerSpec, ContainerState, MountSpec, PortConflict, PullProgress, ResourceKind, RuntimeEvent,
+    },
+    scope::Report,
     task::TaskContext,
     utils::TaskGuard,
 };
 
-// TODO: Methods could be moved to `TaskContext` or `ScopedDockerDriver`
 // Container management
 impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
     pub fn subscribe_to_events(&mut self) {
-        let mut type_filter = HashMap::new();
-        type_filter.insert("type".to_string(), vec!["container".to_string()]);
-        type_filter.insert("container".to_string(), vec![self.inner.container_name.clone()]);
-        let opts = EventsOptions {
-            since: None,
-            until: None,
-            filters: type_filter,
-        };
-        let stream = self.driver.events(Some(opts)).map_err(Error::from);
+        let stream = self.driver.events(ResourceKind::Container, &self.inner.container_name);
         let sender = self.sender().get_direct().clone();
         let conv = EventConv {
             // TODO: Name is not necessary here
@@ -76,95 +55,98 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
     }
 
     pub async fn image_exists(&mut self) -> bool {
-        self.driver.inspect_image(&self.inner.image_name).await.is_ok()
+        self.driver.image_exists(&self.inner.image_name).await
     }
 
     pub async fn container_state(&mut self) -> ContainerState {
-        let res = self.driver.inspect_container(&self.inner.container_name, None).await;
-        match res {
-            Ok(ref response) => {
-                if let Some(state) = response.state.clone() {
-                    if let Some(status) = state.status {
-                        match status {
-                            ContainerStateStatusEnum::EMPTY => ContainerState::Empty,
-                            ContainerStateStatusEnum::CREATED => ContainerState::Created,
-                            ContainerStateStatusEnum::RUNNING => ContainerState::Running,
-                            ContainerStateStatusEnum::PAUSED => ContainerState::Paused,
-                            ContainerStateStatusEnum::RESTARTING => ContainerState::Restarting,
-                            ContainerStateStatusEnum::REMOVING => ContainerState::Removing,
-                            ContainerStateStatusEnum::EXITED => ContainerState::Exited,
-                            ContainerStateStatusEnum::DEAD => ContainerState::Dead,
-                        }
-                    } else {
-                        log::error!(
-                            "Status of container `{}` not defined: {:?}",
-                            self.inner.container_name,
-                            res
-                        );
-                        ContainerState::ErrorStatusNotDefined
-                    }
-                } else {
-                    log::error!(
-                        "State of container `{}` not defined: {:?}",
-                        self.inner.container_name,
-                        res
-                    );
-                    ContainerState::ErrorStateNotDefined
-                }
-            },
-            Err(_) => ContainerState::NotFound,
-        }
+        self.driver.container_state(&self.inner.container_name).await
     }
 
     pub fn pull(&mut self) -> TaskGuard<()> {
-        let opts = Some(CreateImageOptions {
-            from_image: self.inner.image_name.clone(),
-            ..Default::default()
-        });
-        let stream = self.driver.create_image(opts, None, None).map_err(Error::from);
+        let stream = self
+            .driver
+            .pull_image(&self.inner.image_name, self.inner.credentials.clone());
         let sender = self.sender().get_direct().clone();
-        Forwarder::start(stream, ProgressConv, sender)
+        Forwarder::start(stream, ProgressConv::default(), sender)
     }
 
     pub fn logs_stream(&mut self) -> Logs {
-        let opts = LogsOptions::<String> {
-            follow: true,
-            stdout: true,
-            stderr: true,
-            ..Default::default()
-        };
-        let stream = self
-            .driver
-            .logs(&self.inner.container_name, Some(opts))
-            .map(log_conv)
-            .map_err(Error::from);
-        Logs::new(stream)
+        let driver = self.driver.clone();
+        let name = self.inner.container_name.clone();
+        let source = Box::new(move |since| driver.logs(&name, since));
+        Logs::new(source, self.inner.log_cursor.clone())
     }
 
     pub fn stats_stream(&mut self) -> Stats {
-        let opts = StatsOptions {
-            stream: true,
-            one_shot: false,
-        };
-        let stream = self
-            .driver
-            .stats(&self.inner.container_name, Some(opts))
-            .map(stat_conv)
-            .map_err(Error::from);
+        let stream = self.driver.stats(&self.inner.container_name);
         Stats::new(stream)
     }
 
+    /// Checks that the host ports of the container, shifted by the offset of the scope, are free.
+    /// Busy relocatable ports are replaced with free ones. The ports to publish are kept in the task and reported.
+    pub async fn check_ports(&mut self) -> Result<(), PortConflict> {
+        let mut ports = Ports::default();
+        self.inner.image.ports(&mut ports);
+        let mut result = Vec::new();
+        for mut port in ports.build() {
+            port.host_port = port.host_port.saturating_add(self.inner.port_offset);
+            if let Err(conflict) = self.driver.check_port(&port).await {
+                let free_port = if port.relocatable {
+                    self.driver.free_port(&port).await
+                } else {
+                    None
+                };
+                let host_port = match free_port {
+                    Some(host_port) => host_port,
+                    None => return Err(conflict),
+                };
+                let message = format!("{}. Using the port {} instead.", conflict, host_port);
+                log::warn!("{} ({})", message, self.inner.container_name);
+                self.sender().send_logs(LogLevel::Warn, message).ok();
+                port.host_port = host_port;
+            }
+            result.push(port);
+        }
+        self.sender().send_report(Report::Ports(result.clone())).ok();
+        self.inner.ports = result;
+        Ok(())
+    }
+
+    /// Reports the image of the container and warns if it's not the pinned one.
+    pub async fn record_image(&mut self) -> Result<(), Error> {
+        let digest = self.driver.image_digest(&self.inner.image_name).await;
+        let record = ImageRecord {
+            image: self.inner.image_name.clone(),
+            digest,
+            pinned: self.inner.image.digest().map(String::from),
+            started_at: Local::now().naive_local(),
+        };
+        if record.is_drifted() {
+            let message = format!(
+                "The image {} has the digest {}, but it's pinned to {}",
+                record.image,
+                record.digest.as_deref().unwrap_or("unknown"),
+                record.pinned.as_deref().unwrap_or_default()
+            );
+            log::warn!("{}", message);
+            self.sender().send_error(message)?;
+        }
+        let delta = TaskDelta::ImageUsed(record);
+        self.sender().send_report(Report::Delta(delta))
+    }
+
     pub async fn try_create_container(&mut self) -> Result<(), Error> {
+        let dependency_ports: Vec<_> = self
+            .dependency_ports()
+            .map(|(task_id, ports)| (task_id.clone(), ports.to_vec()))
+            .collect();
+        for (task_id, ports) in dependency_ports {
+            self.inner.image.on_dependency_ports(&task_id, &ports);
+        }
         let mut args = Args::default();
         self.inner.image.args(&mut args);
-        let mut ports = Ports::default();
-        self.inner.image.ports(&mut ports);
         let mut envs = Envs::default();
         self.inner.image.envs(&mut envs);
-        let opts = CreateContainerOptions {
-            name: self.inner.container_name.clone(),
-            platform: None,
-        };
 
         let mut networks = Networks::default();
         self.inner.image.networks(&mut networks);
@@ -172,133 +154,101 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
 
         let mut volumes = Volumes::default();
         self.inner.image.volumes(&mut volumes);
-        let volumes = volumes_map(volumes.build());
 
         let mut mounts = Mounts::default();
         self.inner.image.mounts(&mut mounts);
         let mounts = self.mounts_map(mounts.build())?;
-        let ports = ports.build();
-        let config = Config {
-            image: Some(self.inner.image_name.clone()),
-            attach_stdin: Some(false),
-            attach_stdout: Some(false),
-            attach_stderr: Some(false),
-            exposed_ports: Some(exposed_ports(&ports)),
-            open_stdin: Some(true),
-            stdin_once: Some(false),
-            tty: Some(true),
-            env: Some(envs.build()),
-            volumes: Some(volumes),
-            cmd: Some(args.build()),
-            host_config: Some(HostConfig {
-                auto_remove: Some(true),
-                binds: Some(vec![]),
-                network_mode: Some("bridge".to_string()),
-                port_bindings: Some(ports_map(&ports)),
-                mounts: Some(mounts),
-                ..Default::default()
-            }),
-            networking_config: Some(networks),
-            ..Default::default()
+        let spec = ContainerSpec {
+            image: self.inner.image_name.clone(),
+            args: args.build(),
+            envs: envs.build(),
+            ports: self.inner.ports.clone(),
+            volumes: volumes.build(),
+            mounts,
+            networks,
+            resources: self.inner.image.resources(),
         };
-        self.driver.create_container(Some(opts), config).await?;
-        Ok(())
+        let labels = self.labels.with_config(&spec);
+        self.driver
+            .create_container(&self.inner.container_name, spec, &labels)
+            .await
     }
 
     pub async fn try_start_container(&mut self) -> Result<(), Error> {
-        self.driver
-            .start_container::<String>(&self.inner.container_name, None)
-            .await?;
-        Ok(())
+        self.driver.start_container(&self.inner.container_name).await
     }
 
     pub async fn try_kill_container(&mut self) -> Result<(), Error> {
-        self.driver
-            .kill_container::<String>(&self.inner.container_name, None)
-            .await?;
-        Ok(())
+        self.driver.kill_container(&self.inner.container_name).await
     }
 
-    pub async fn try_stop_container(&mut self, options: Option<StopContainerOptions>) -> Result<(), Error> {
-        self.driver.stop_container(&self.inner.container_name, options).await?;
-        Ok(())
+    pub async fn try_stop_container(&mut self) -> Result<(), Error> {
+        let grace = self.inner.timeouts.stop;
+        self.driver.stop_container(&self.inner.container_name, grace).await
+    }
+
+    /// Asks the container to stop in the background, since the engine waits for the grace period.
+    pub fn stop_in_background(&mut self) -> TaskGuard<()> {
+        let driver = self.driver.clone();
+        let name = self.inner.container_name.clone();
+        let grace = self.inner.timeouts.stop;
+        let fut = async move {
+            if let Err(err) = driver.stop_container(&name, grace).await {
+                log::warn!("Can't stop the container {}: {}", name, err);
+            }
+        };
+        tokio::spawn(fut).into()
+    }
+
+    /// Removes the container in the background, nothing waits for it.
+    pub fn remove_in_background(&mut self) {
+        let driver = self.driver.clone();
+        let name = self.inner.container_name.clone();
+        tokio::spawn(async move {
+            if let Err(err) = driver.remove_container(&name).await {
+                log::warn!("Can't remove the container {}: {}", name, err);
+            }
+        });
     }
 
     pub async fn try_unpause_container(&mut self) -> Result<(), Error> {
-        self.driver.unpause_container(&self.inner.container_name).await?;
-        Ok(())
+        self.driver.unpause_container(&self.inner.container_name).await
     }
 
     pub async fn try_remove_container(&mut self) -> Result<(), Error> {
-        let opts = RemoveContainerOptions {
-            force: true,
-            ..Default::default()
-        };
-        self.driver
-            .remove_container(&self.inner.container_name, Some(opts))
-            .await?;
-        Ok(())
+        self.driver.remove_container(&self.inner.container_name).await
     }
 
     pub async fn try_remove_image(&mut self) -> Result<(), Error> {
-        let image_name = self.inner.image_name.as_ref();
-        let opts = Some(RemoveImageOptions {
-            force: true,
-            ..Default::default()
-        });
-        self.driver.remove_image(image_name, opts, None).await?;
-        Ok(())
+        self.driver.remove_image(&self.inner.image_name).await
     }
 
-    fn networks_map(&self, networks: Networks) -> Result<NetworkingConfig<String>, Error> {
-        let mut endpoints = HashMap::new();
+    fn networks_map(&self, networks: Networks) -> Result<Vec<(String, String)>, Error> {
+        let mut result = Vec::new();
         for (alias, resource) in networks.build() {
             let net_name = self
                 .resource(&resource)
                 .ok_or_else(|| anyhow!("Network {:?} not available in resources. Check dependencies.", resource))?
                 .to_string();
-            let endpoint = EndpointSettings {
-                aliases: Some(vec![alias]),
-                ..Default::default()
-            };
-            endpoints.insert(net_name, endpoint);
+            result.push((net_name, alias));
         }
-        Ok(NetworkingConfig {
-            endpoints_config: endpoints,
-        })
+        Ok(result)
     }
 
-    fn make_mount(&self, mount: Mount) -> Result<BollardMount, Error> {
+    fn make_mount(&self, mount: Mount) -> Result<MountSpec, Error> {
         match mount {
             Mount::ToVolume { volume, target } => {
-                let resource = self
+                let source = self
                     .resource(&volume)
                     .ok_or_else(|| anyhow!("Resource {:?} was not found.", volume))?
                     .to_string();
-                let mount = BollardMount {
-                    target: Some(target),
-                    source: Some(resource),
-                    typ: Some(MountTypeEnum::VOLUME),
-                    volume_options: None,
-                    ..Default::default()
-                };
-                Ok(mount)
-            },
-            Mount::BindTo { source, target } => {
-                let source = canonicalize(source);
-                let mount = BollardMount {
-                    target: Some(target),
-                    source: Some(source),
-                    typ: Some(MountTypeEnum::BIND),
-                    bind_options: None,
-                    ..Default::default()
-                };
-                Ok(mount)
+                Ok(MountSpec::Volume { source, target })
             },
+            Mount::BindTo { source, target } => Ok(MountSpec::Bind { source, target }),
         }
     }
 
-    fn mounts_map(&self, mounts: Vec<Mount>) -> Result<Vec<BollardMount>, Error> {
+    fn mounts_map(&self, mounts: Vec<Mount>) -> Result<Vec<MountSpec>, Error> {
         let mut result = Vec::new();
         for mount in mounts {
             result.push(self.make_mount(mount)?);
@@ -307,86 +257,25 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
     }
 }
 
-type BollardMap = HashMap<String, HashMap<(), ()>>;
-
-fn volumes_map(volumes: Vec<String>) -> BollardMap {
-    let mut result = BollardMap::new();
-    for volume in volumes {
-        result.insert(volume, HashMap::default());
-    }
-    result
-}
-
-fn exposed_ports(ports: &[u16]) -> BollardMap {
-    let mut result = BollardMap::new();
-    for port in ports {
-        result.insert(format!("{}/tcp", port), HashMap::default());
-    }
-    result
+#[derive(Default)]
+struct ProgressConv {
+    tracker: PullTracker,
 }
 
-fn ports_map(ports: &[u16]) -> PortMap {
-    let mut result = PortMap::new();
-    for (k, _) in exposed_ports(ports) {
-        let binding = vec![PortBinding {
-            host_ip: Some(String::new()),
-            host_port: Some(k.clone()),
-        }];
-        result.insert(k, Some(binding));
-    }
-    result
-}
-
-fn log_conv(res: Result<LogOutput, BollardError>) -> Result<String, Error> {
-    if let Ok(LogOutput::Console { message }) = res {
-        match std::str::from_utf8(message.as_ref()) {
-            Ok(data) => {
-                let message = data.to_string();
-                log::trace!("Log: {}", message);
-                Ok(message)
-            },
-            Err(err) => Err(anyhow!("Can't parse log text: {}", err)),
-        }
-    } else {
-        Err(anyhow!("Unsupported log event: {:?}", res))
-    }
-}
-
-fn stat_conv(res: Result<BollardStats, BollardError>) -> Result<StatsData, Error> {
-    if let Ok(BollardStats {
-        cpu_stats,
-        memory_stats,
-        ..
-    }) = res
-    {
-        Ok(StatsData {
-            timestamp: Local::now().naive_local(),
-            system_cpu_usage: cpu_stats.system_cpu_usage.unwrap_or_default() as i64,
-            cpu_usage: cpu_stats.cpu_usage.total_usage as i64,
-            mem_limit: memory_stats.limit.unwrap_or_default().into(),
-            mem_usage: memory_stats.usage.unwrap_or_default().into(),
-        })
-    } else {
-        Err(anyhow!("Unsupported stats event: {:?}", res))
-    }
-}
-
-struct ProgressConv;
-
-impl Converter<CreateImageInfo, Event> for ProgressConv {
-    fn convert(&self, res: Result<CreateImageInfo, Error>) -> Option<Event> {
+impl Converter<PullProgress, Event> for ProgressConv {
+    fn convert(&mut self, res: Result<PullProgress, Error>) -> Option<Event> {
         if let Err(err) = res {
             log::error!("Error while pulling image: {}", err);
+            if let Some(AuthError { registry, reason }) = err.downcast_ref() {
+                return Some(Event::PullingUnauthorized {
+                    registry: registry.clone(),
+                    reason: reason.clone(),
+                });
+            }
             return Some(Event::PullingFailed(err.to_string()));
         }
         let info = res.unwrap();
-        log::debug!("Created Image Info: {:?}", info);
-        let details = info.progress_detail?;
-        let current = details.current? * 100;
-        let total = details.total?;
-        let pct = current / total;
-        let stage = info.status?;
-        let progress = TaskProgress { pct: pct as u8, stage };
+        let progress = self.tracker.update(info)?;
         Some(Event::PullingProgress(progress))
     }
 }
@@ -395,40 +284,18 @@ struct EventConv {
     pub name: String,
 }
 
-impl Converter<EventMessage, Event> for EventConv {
-    fn convert(&self, res: Result<EventMessage, Error>) -> Option<Event> {
-        if let Ok(EventMessage {
-            typ: Some(typ),
-            action: Some(action),
-            actor: Some(actor),
-            ..
-        }) = res
-        {
-            if let Some(attributes) = actor.attributes {
-                if let Some(name) = attributes.get("name") {
-                    // TODO: Check images as well
-                    if self.name == *name {
-                        // TODO: Check the name
-                        if let EventMessageTypeEnum::CONTAINER = typ {
-                            return action.try_into().ok();
-                        }
-                    } else {
-                        log::error!("Message for other container {}, but expected {}", name, self.name);
-                    }
+impl Converter<RuntimeEvent, Event> for EventConv {
+    fn convert(&mut self, res: Result<RuntimeEvent, Error>) -> Option<Event> {
+        if let Ok(event) = res {
+            // TODO: Check images as well
+            if self.name == event.name {
+                if let ResourceKind::Container = event.kind {
+                    return event.try_into().ok();
                 }
+            } else {
+                log::error!("Message for other container {}, but expected {}", event.name, self.name);
             }
         }
         None
     }
 }
-
-// FIXME: This might be replaceable by std::fs::canonicalize, but I don't have a windows machine to check
-fn canonicalize<P: AsRef<Path>>(path: P) -> String {
-    #[cfg(target_os = "windows")]
-    let path = path.as_ref().to_string_lossy().to_string();
-    #[cfg(target_os = "macos")]
-    let path = format!("/host_mnt{}", path.as_ref().to_string_lossy());
-    #[cfg(target_os = "linux")]
-    let path = path.as_ref().to_string_lossy().to_string();
-    path
-}
@@ -23,8 +23,9 @@
 
 use anyhow::Error;
 use tari_launchpad_protocol::container::{TaskProgress, TaskStatus};
+use tokio::time::Instant;
 
-use super::{Event, ImageTask, Status};
+use super::{Event, ImageTask, Phase, Status, STABLE_PERIOD};
 use crate::{
     config::ManagedProtocol,
     image::checker::{CheckerContext, CheckerEvent},
@@ -38,10 +39,11 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
             Event::Created => self.on_created(),
             Event::PullingProgress(value) => self.on_pulling_progress(value),
             Event::PullingFailed(reason) => self.on_pulling_failed(reason),
+            Event::PullingUnauthorized { registry, reason } => self.on_pulling_unauthorized(registry, reason),
             Event::Destroyed => self.on_destroyed(),
             Event::Started => self.on_started(),
             Event::Killed => self.on_killed(),
-            Event::Terminated => self.on_terminated(),
+            Event::Terminated { exit_code } => self.on_terminated(exit_code),
             Event::CheckerProgress(event) => self.on_checker_event(event),
         }
     }
@@ -68,6 +70,20 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
         Ok(())
     }
 
+    /// The pull is not retried until the credentials are changed.
+    fn on_pulling_unauthorized(&mut self, registry: String, reason: String) -> Result<(), Error> {
+        if let Status::PullingImage { .. } = self.status.get() {
+            self.status.set(Status::CannotStart);
+            let message = format!(
+                "Can't pull the image {}, the registry {} rejected the credentials: {}",
+                self.inner.image_name, registry, reason
+            );
+            self.sender().send_error(message)?;
+            self.update_task_status(TaskStatus::Unauthorized { registry, reason })?;
+        }
+        Ok(())
+    }
+
     fn on_destroyed(&mut self) -> Result<(), Error> {
         if let Status::WaitContainerRemoved = self.status.get() {
             self.status.set(Status::CleanDangling);
@@ -79,12 +95,16 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
         if let Status::WaitContainerStarted { .. } = self.status.get() {
             let checker = self.inner.image.checker();
             let logs = self.logs_stream();
+            let parser = self.inner.image.log_parser();
             let stats = self.stats_stream();
             let sender = self.sender().clone();
-            let context = CheckerContext::new(logs, stats, sender);
+            let ports = self.inner.ports.clone();
+            let context = CheckerContext::new(logs, parser, stats, ports, sender);
             let fur = checker.entrypoint(context);
             let checker = tokio::spawn(fur).into();
+            self.inner.started_at = Some(Instant::now());
             self.status.set(Status::Active { checker, ready: false });
+            self.set_deadline(Phase::Ready);
         }
         Ok(())
     }
@@ -100,6 +120,7 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
                     self.update_task_status(TaskStatus::Progress(progress))?;
                 },
                 CheckerEvent::Ready => {
+                    self.status.reset_fallback();
                     self.status.update(|status| {
                         if let Status::Active { ready, .. } = status {
                             *ready = true;
@@ -107,22 +128,46 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
                     });
                     self.update_task_status(TaskStatus::Active)?;
                 },
+                CheckerEvent::Failed(reason) => {
+                    self.status.reset_fallback();
+                    // The container is not usable, so it's removed before the next attempt or for good
+                    self.remove_in_background();
+                    self.fail_or_restart(reason, true)?;
+                },
             }
         }
         Ok(())
     }
 
-    fn on_terminated(&mut self) -> Result<(), Error> {
+    fn on_terminated(&mut self, exit_code: Option<i64>) -> Result<(), Error> {
         match self.status.get() {
             Status::WaitContainerKilled => {
                 self.status.set(Status::CleanDangling);
             },
             Status::Active { .. } => {
-                // TODO: Add waiting interval + fallback
-                // self.status.set(Status::CleanDangling);
+                self.on_unexpected_exit(exit_code)?;
             },
             _ => {},
         }
         Ok(())
     }
+
+    /// Applies the restart policy of the image to a container that exited by itself.
+    fn on_unexpected_exit(&mut self, exit_code: Option<i64>) -> Result<(), Error> {
+        let stable = self
+            .inner
+            .started_at
+            .take()
+            .map(|started_at| started_at.elapsed() >= STABLE_PERIOD)
+            .unwrap_or_default();
+        if stable {
+            self.inner.restarts = 0;
+        }
+        let reason = match exit_code {
+            Some(code) => format!("Container exited with code {}", code),
+            None => "Container exited".to_string(),
+        };
+        let failed = exit_code != Some(0);
+        self.fail_or_restart(reason, failed)
+    }
 }
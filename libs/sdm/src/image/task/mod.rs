@@ -23,16 +23,30 @@
 
 mod docker;
 mod events;
+mod progress;
 mod update;
 
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+
 use anyhow::Error;
 use async_trait::async_trait;
-use tari_launchpad_protocol::container::TaskProgress;
+use chrono::Local;
+use rand::Rng;
+use tari_launchpad_protocol::container::{TaskProgress, TaskStatus};
+use tokio::time::{Duration, Instant};
 
-use super::{checker::CheckerEvent, ManagedContainer};
+use super::{
+    checker::{CheckerEvent, LogCursor},
+    ManagedContainer, Timeouts,
+};
 use crate::{
     config::ManagedProtocol,
     error::ParseError,
+    runtime::{PortMapping, RegistryAuth, RuntimeEvent},
+    status::Fallback,
     task::{RunnableContext, RunnableTask, TaskContext, TaskStatusChecker},
     utils::TaskGuard,
 };
@@ -43,24 +57,48 @@ pub struct ImageTask<C: ManagedProtocol> {
     // TODO: Rename to `fqdn`
     image_name: String,
     image: Box<dyn ManagedContainer<Protocol = C>>,
+    timeouts: Timeouts,
+    /// Ports published by the current container, with relocated host ports
+    ports: Vec<PortMapping>,
+    /// Added to the host ports, so the containers of several scopes don't compete for them
+    port_offset: u16,
     /// A flag to ask to restart a container
     force_restart: bool,
     /// A flag to drop and pull image again
     force_pull: bool,
+    /// The image was changed by the config and the container has to be recreated from the new one
+    image_changed: bool,
+    /// Credentials of the registry the image is pulled with
+    credentials: Option<RegistryAuth>,
+    /// Restarts made since the container was running steadily
+    restarts: u32,
+    started_at: Option<Instant>,
+    /// The last log lines received from the current container
+    log_cursor: Arc<Mutex<LogCursor>>,
 }
 
 impl<C: ManagedProtocol> ImageTask<C> {
-    pub fn new(scope: &str, image: Box<dyn ManagedContainer<Protocol = C>>) -> Self {
+    pub fn new(scope: &str, port_offset: u16, image: Box<dyn ManagedContainer<Protocol = C>>) -> Self {
         // let required = image.deps().into_iter().collect();
-        let image_name = format!("{}/{}:{}", image.registry(), image.image_name(), image.tag());
+        let image_name = fqdn(image.as_ref());
         let container_name = format!("{}_{}", scope, image.image_name());
+        let timeouts = image.timeouts();
+        let credentials = image.credentials();
         Self {
             events: None,
             container_name,
             image_name,
             image,
+            timeouts,
+            ports: Vec::new(),
+            port_offset,
             force_restart: false,
             force_pull: false,
+            image_changed: false,
+            credentials,
+            restarts: 0,
+            started_at: None,
+            log_cursor: Arc::default(),
         }
     }
 }
@@ -78,6 +116,10 @@ impl<C: ManagedProtocol> RunnableTask for ImageTask<C> {
     fn is_permanent(&self) -> bool {
         false
     }
+
+    fn ports(&self) -> &[PortMapping] {
+        &self.ports
+    }
 }
 
 #[async_trait]
@@ -87,7 +129,40 @@ impl<C: ManagedProtocol> RunnableContext<ImageTask<C>> for TaskContext<ImageTask
     }
 
     fn reconfigure(&mut self, config: Option<&C::Config>) -> bool {
-        self.inner.image.reconfigure(config).unwrap_or_default()
+        let active = self.inner.image.reconfigure(config).unwrap_or_default();
+        let credentials = self.inner.image.credentials();
+        if credentials != self.inner.credentials {
+            self.inner.credentials = credentials;
+            if let Status::CannotStart = self.status.get() {
+                log::debug!("Credentials of {} are changed. Pulling again.", self.inner.image_name);
+                self.status.set(Status::InitialState);
+            }
+        }
+        let image_name = fqdn(self.inner.image.as_ref());
+        if image_name != self.inner.image_name {
+            log::info!(
+                "The image of {} is changed from {} to {}",
+                self.inner.container_name,
+                self.inner.image_name,
+                image_name
+            );
+            self.inner.image_name = image_name;
+            match self.status.get() {
+                Status::Idle
+                | Status::CannotStart
+                | Status::PullingImage { .. }
+                | Status::Exited
+                | Status::WaitRestart { .. } => {
+                    // There is no container to stop, so the new image is checked right away
+                    self.inner.restarts = 0;
+                    self.status.set(Status::InitialState);
+                },
+                _ => {
+                    self.inner.image_changed = true;
+                },
+            }
+        }
+        active
     }
 
     fn process_inner_event(&mut self, event: C::Inner) {
@@ -105,7 +180,74 @@ impl<C: ManagedProtocol> RunnableContext<ImageTask<C>> for TaskContext<ImageTask
 
 impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
     fn should_be_restarted(&self) -> bool {
-        self.force_restart || self.force_pull
+        self.force_restart || self.force_pull || self.image_changed
+    }
+
+    /// Moves the task to `Status::Timeout` if the current status is not changed in time.
+    fn set_deadline(&mut self, phase: Phase) {
+        let duration = match phase {
+            Phase::Pull => self.inner.timeouts.pull,
+            Phase::Create => self.inner.timeouts.create,
+            Phase::Start => self.inner.timeouts.start,
+            Phase::Ready => self.inner.timeouts.ready,
+        };
+        if let Some(duration) = duration {
+            let fallback = Fallback {
+                when: Instant::now() + duration,
+                next_status: Status::Timeout { phase, duration },
+            };
+            self.status.set_fallback(fallback);
+        }
+    }
+
+    /// Schedules a restart if the restart policy allows it, or marks the task as failed otherwise.
+    fn fail_or_restart(&mut self, reason: String, failed: bool) -> Result<(), Error> {
+        let policy = self.inner.image.restart_policy();
+        self.sender().send_error(reason.clone())?;
+        if policy.should_restart(failed, self.inner.restarts) {
+            self.inner.restarts += 1;
+            let delay = backoff(self.inner.restarts);
+            log::warn!(
+                "{} ({}). Restarting in {:?}, attempt {}",
+                reason,
+                self.inner.container_name,
+                delay,
+                self.inner.restarts
+            );
+            self.status.set(Status::WaitRestart {
+                when: Instant::now() + delay,
+            });
+            let status = TaskStatus::Restarting {
+                attempt: self.inner.restarts,
+                max_attempts: policy.max_attempts(),
+                next_retry: Local::now().naive_local() + chrono::Duration::from_std(delay)?,
+            };
+            self.update_task_status(status)?;
+        } else {
+            log::warn!("{} ({}). Not restarting.", reason, self.inner.container_name);
+            self.status.set(Status::Exited);
+            self.update_task_status(TaskStatus::Failed(reason))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Phase {
+    Pull,
+    Create,
+    Start,
+    Ready,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Pull => write!(f, "Pulling the image"),
+            Self::Create => write!(f, "Creating the container"),
+            Self::Start => write!(f, "Starting the container"),
+            Self::Ready => write!(f, "Waiting for readiness"),
+        }
     }
 }
 
@@ -137,6 +279,25 @@ pub enum Status {
         checker: TaskGuard<()>,
         ready: bool,
     },
+    /// The container was asked to stop and is killed if it's still there after the grace period
+    Stopping {
+        #[allow(unused)]
+        stop: TaskGuard<()>,
+    },
+    KillContainer,
+
+    /// The container exited and waits for the backoff interval to start again
+    WaitRestart {
+        when: Instant,
+    },
+    /// The container exited and will not be restarted until the task is deactivated
+    Exited,
+
+    /// A transient state was not finished in time
+    Timeout {
+        phase: Phase,
+        duration: Duration,
+    },
 
     DropImage,
 }
@@ -145,6 +306,13 @@ impl TaskStatusChecker for Status {
     fn is_ready(&self) -> bool {
         matches!(self, Self::Active { ready: true, .. })
     }
+
+    fn is_stopped(&self) -> bool {
+        matches!(
+            self,
+            Self::Idle | Self::CannotStart | Self::PullingImage { .. } | Self::Exited
+        )
+    }
 }
 
 impl Default for Status {
@@ -153,45 +321,58 @@ impl Default for Status {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub enum ContainerState {
-    Empty,
-    Created,
-    Running,
-    Paused,
-    Restarting,
-    Removing,
-    Exited,
-    Dead,
-    ErrorStatusNotDefined,
-    ErrorStateNotDefined,
-    NotFound,
-}
-
 #[derive(Debug)]
 pub enum Event {
     Destroyed,
     PullingProgress(TaskProgress),
     PullingFailed(String),
+    /// The registry rejected the credentials
+    PullingUnauthorized {
+        registry: String,
+        reason: String,
+    },
     Created,
     Started,
     Killed,
-    Terminated,
+    Terminated {
+        exit_code: Option<i64>,
+    },
     CheckerProgress(CheckerEvent),
 }
 
-impl TryFrom<String> for Event {
+impl TryFrom<RuntimeEvent> for Event {
     type Error = ParseError;
 
-    fn try_from(value: String) -> Result<Self, Self::Error> {
+    fn try_from(event: RuntimeEvent) -> Result<Self, Self::Error> {
         // Docker values!
-        match value.as_ref() {
+        match event.action.as_ref() {
             "destroy" => Ok(Self::Destroyed),
             "create" => Ok(Self::Created),
             "start" => Ok(Self::Started),
             "kill" => Ok(Self::Killed),
-            "die" => Ok(Self::Terminated),
-            _ => Err(ParseError(value)),
+            "die" => Ok(Self::Terminated {
+                exit_code: event.exit_code,
+            }),
+            _ => Err(ParseError(event.action)),
         }
     }
 }
+
+fn fqdn<C: ManagedProtocol>(image: &dyn ManagedContainer<Protocol = C>) -> String {
+    format!("{}/{}:{}", image.registry(), image.image_name(), image.tag())
+}
+
+const BACKOFF_BASE: Duration = Duration::from_secs(1);
+const BACKOFF_LIMIT: Duration = Duration::from_secs(60);
+/// Extra time given to the engine to stop a container before it's killed by the task.
+const KILL_MARGIN: Duration = Duration::from_secs(2);
+/// If a container was running longer than this period, the restarts counter is reset.
+const STABLE_PERIOD: Duration = Duration::from_secs(60);
+
+/// Exponential backoff with up to 20% of jitter.
+fn backoff(attempt: u32) -> Duration {
+    let factor = 1_u32 << attempt.saturating_sub(1).min(16);
+    let delay = BACKOFF_BASE.saturating_mul(factor).min(BACKOFF_LIMIT);
+    let jitter = rand::thread_rng().gen_range(0..=delay.as_millis() as u64 / 5);
+    delay + Duration::from_millis(jitter)
+}
@@ -0,0 +1,142 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Aggregation of the progress of pulling an image.
+//!
+//! An engine reports the progress of every layer separately and the messages of layers interleave.
+//! The tracker keeps all layers and turns them into a single monotonic progress of the image.
+
+use std::collections::HashMap;
+
+use tari_launchpad_protocol::container::{TaskProgress, TransferProgress};
+use tokio::time::Instant;
+
+use crate::runtime::PullProgress;
+
+#[derive(Debug, Default)]
+struct Layer {
+    /// The compressed size of the layer, it's unknown until the download starts
+    total: u64,
+    downloaded: u64,
+    extracted: u64,
+    /// The layer is downloaded completely
+    fetched: bool,
+    done: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct PullTracker {
+    layers: HashMap<String, Layer>,
+    /// When the first byte was received
+    started_at: Option<Instant>,
+    pct: u8,
+}
+
+impl PullTracker {
+    /// Applies a message of the engine and returns the overall progress if it's changed.
+    pub fn update(&mut self, info: PullProgress) -> Option<TaskProgress> {
+        let id = info.layer?;
+        let status = info.status?;
+        let current = info.current.unwrap_or_default().max(0) as u64;
+        let total = info.total.unwrap_or_default().max(0) as u64;
+        match status.as_str() {
+            "Pulling fs layer" | "Waiting" => {
+                self.layers.entry(id).or_default();
+            },
+            "Downloading" => {
+                self.started_at.get_or_insert_with(Instant::now);
+                let layer = self.layers.entry(id).or_default();
+                layer.total = layer.total.max(total);
+                layer.downloaded = layer.downloaded.max(current);
+            },
+            "Verifying Checksum" | "Download complete" => {
+                let layer = self.layers.entry(id).or_default();
+                layer.downloaded = layer.total;
+                layer.fetched = true;
+            },
+            "Extracting" => {
+                let layer = self.layers.entry(id).or_default();
+                layer.downloaded = layer.total;
+                layer.fetched = true;
+                // The extraction reports the compressed size as well
+                layer.extracted = layer.extracted.max(current).min(layer.total.max(total));
+            },
+            "Pull complete" => {
+                let layer = self.layers.entry(id).or_default();
+                layer.downloaded = layer.total;
+                layer.extracted = layer.total;
+                layer.fetched = true;
+                layer.done = true;
+            },
+            // Layers that exist locally and messages about the image itself
+            _ => return None,
+        }
+        Some(self.progress())
+    }
+
+    fn progress(&mut self) -> TaskProgress {
+        let total: u64 = self.layers.values().map(|layer| layer.total).sum();
+        let downloaded: u64 = self.layers.values().map(|layer| layer.downloaded).sum();
+        let extracted: u64 = self.layers.values().map(|layer| layer.extracted).sum();
+        let finished = self.layers.values().all(|layer| layer.done);
+        // Every byte is downloaded and extracted, so both halves are counted
+        let done = downloaded + extracted;
+        let pct = if finished {
+            100
+        } else if total > 0 {
+            // Sizes of waiting layers are unknown, so the value could go down when they start
+            (done * 100 / (total * 2)).min(99) as u8
+        } else {
+            0
+        };
+        self.pct = self.pct.max(pct);
+
+        let elapsed = self.started_at.map(|started_at| started_at.elapsed().as_secs_f64());
+        let rate = |bytes: u64| {
+            elapsed
+                .filter(|elapsed| *elapsed > 0.0)
+                .map(|elapsed| bytes as f64 / elapsed)
+        };
+        let speed = rate(downloaded).map(|speed| speed as u64);
+        let eta = rate(done)
+            .filter(|rate| *rate > 0.0)
+            .map(|rate| ((total * 2).saturating_sub(done) as f64 / rate).ceil() as u64);
+        let stage = if finished {
+            "Pull complete"
+        } else if self.layers.values().any(|layer| !layer.fetched) {
+            "Downloading"
+        } else {
+            "Extracting"
+        };
+        TaskProgress {
+            pct: self.pct,
+            stage: stage.to_string(),
+            transfer: Some(TransferProgress {
+                current: downloaded,
+                total,
+                speed,
+                eta,
+            }),
+        }
+    }
+}
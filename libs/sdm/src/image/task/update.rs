@@ -21,11 +21,14 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
+use std::sync::Arc;
+
 use anyhow::Error;
 use tari_launchpad_protocol::container::{TaskProgress, TaskStatus};
+use tokio::time::Instant;
 
-use super::{ContainerState, ImageTask, Status};
-use crate::{config::ManagedProtocol, task::TaskContext};
+use super::{ImageTask, Phase, Status, KILL_MARGIN};
+use crate::{config::ManagedProtocol, runtime::ContainerState, status::Fallback, task::TaskContext};
 
 impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
     pub async fn process_update_impl(&mut self) -> Result<(), Error> {
@@ -42,6 +45,11 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
             Status::StartContainer => self.do_start_container().await,
             Status::WaitContainerStarted => self.do_wait_container_started().await,
             Status::Active { .. } => self.do_active().await,
+            Status::Stopping { .. } => self.do_stopping().await,
+            Status::KillContainer => self.do_kill_container().await,
+            Status::WaitRestart { .. } => self.do_wait_restart().await,
+            Status::Exited => self.do_exited().await,
+            Status::Timeout { .. } => self.do_timeout().await,
             Status::DropImage => self.do_drop_image().await,
         }
     }
@@ -74,6 +82,7 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
         self.update_task_status(TaskStatus::Progress(progress))?;
         let progress = self.pull();
         self.status.set(Status::PullingImage { progress });
+        self.set_deadline(Phase::Pull);
         Ok(())
     }
 
@@ -104,7 +113,7 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
                     self.inner.container_name,
                     state
                 );
-                self.try_stop_container(None).await?;
+                self.try_stop_container().await?;
                 self.try_kill_container().await?;
                 self.status.set(Status::WaitContainerKilled);
             },
@@ -115,7 +124,7 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
                     state
                 );
                 self.try_unpause_container().await?;
-                self.try_stop_container(None).await?;
+                self.try_stop_container().await?;
                 self.try_kill_container().await?;
                 self.status.set(Status::WaitContainerKilled);
             },
@@ -244,7 +253,12 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
 
     async fn do_idle(&mut self) -> Result<(), Error> {
         log::trace!("[Update event: Image] `do_idle` {}", self.inner.image_name);
-        if self.force_pull {
+        if self.image_changed {
+            self.image_changed = false;
+            log::debug!("Checking the new image {} ...", self.inner.image_name);
+            self.status.set(Status::InitialState);
+            Ok(())
+        } else if self.force_pull {
             self.force_pull = false;
             self.status.set(Status::DropImage);
             let progress = TaskProgress::new("Removing image...");
@@ -263,10 +277,23 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
 
     async fn do_create_container(&mut self) -> Result<(), Error> {
         log::trace!("[Update event: Image] `do_create_container` {}", self.inner.image_name);
+        if let Err(conflict) = self.check_ports().await {
+            // The container can't be started until the port is released, so there's no sense to restart it
+            let reason = conflict.to_string();
+            log::warn!("{} ({}). Not starting.", reason, self.inner.container_name);
+            self.sender().send_error(reason.clone())?;
+            self.status.set(Status::Exited);
+            self.update_task_status(TaskStatus::Failed(reason))?;
+            return Ok(());
+        }
+        self.record_image().await?;
         log::debug!("Trying to create container {} ...", self.inner.container_name);
+        // A new container has its own logs
+        self.inner.log_cursor = Arc::default();
         // TODO: Process the result as well
         self.try_create_container().await?;
         self.status.set(Status::WaitContainerCreated);
+        self.set_deadline(Phase::Create);
         Ok(())
     }
 
@@ -275,7 +302,6 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
             "[Update event: Image] `do_wait_container_created` {}",
             self.inner.image_name
         );
-        // TODO: Check timeout
         Ok(())
     }
 
@@ -287,6 +313,7 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
             self.status.set(Status::WaitContainerRemoved);
         } else {
             self.status.set(Status::WaitContainerStarted);
+            self.set_deadline(Phase::Start);
             self.update_task_status(TaskStatus::Pending)?;
         }
         Ok(())
@@ -295,11 +322,89 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
     async fn do_active(&mut self) -> Result<(), Error> {
         log::trace!("[Update event: Image] `do_active` {}", self.inner.image_name);
         if !self.should_be_active() || self.should_be_restarted() {
+            log::debug!("Stopping container {} ...", self.inner.container_name);
+            let stop = self.stop_in_background();
+            self.status.set(Status::Stopping { stop });
+            // Docker kills the container after the grace period itself, the margin is for the case it didn't
+            let fallback = Fallback {
+                when: Instant::now() + self.inner.timeouts.stop + KILL_MARGIN,
+                next_status: Status::KillContainer,
+            };
+            self.status.set_fallback(fallback);
+            self.update_task_status(TaskStatus::ShuttingDown)?;
+        }
+        Ok(())
+    }
+
+    async fn do_stopping(&mut self) -> Result<(), Error> {
+        log::trace!("[Update event: Image] `do_stopping` {}", self.inner.image_name);
+        let state = self.container_state().await;
+        if matches!(
+            state,
+            ContainerState::NotFound | ContainerState::Dead | ContainerState::Removing
+        ) {
+            // Checks the leftovers and moves to `Idle`
             self.status.set(Status::CleanDangling);
         }
         Ok(())
     }
 
+    async fn do_kill_container(&mut self) -> Result<(), Error> {
+        log::trace!("[Update event: Image] `do_kill_container` {}", self.inner.image_name);
+        log::warn!(
+            "Container {} did not stop in {}s. Killing it.",
+            self.inner.container_name,
+            self.inner.timeouts.stop.as_secs()
+        );
+        if let Err(err) = self.try_kill_container().await {
+            log::debug!("Can't kill the container {}: {}", self.inner.container_name, err);
+        }
+        self.status.set(Status::WaitContainerKilled);
+        self.update_task_status(TaskStatus::Inactive)?;
+        Ok(())
+    }
+
+    async fn do_wait_restart(&mut self) -> Result<(), Error> {
+        log::trace!("[Update event: Image] `do_wait_restart` {}", self.inner.image_name);
+        if !self.should_be_active() {
+            self.inner.restarts = 0;
+            self.status.set(Status::Idle);
+            self.update_task_status(TaskStatus::Inactive)?;
+            return Ok(());
+        }
+        if let Status::WaitRestart { when } = self.status.get() {
+            if *when <= Instant::now() {
+                log::debug!("Restarting container {} ...", self.inner.container_name);
+                // Starts from the beginning, since the image could be removed as well
+                self.status.set(Status::InitialState);
+            }
+        }
+        Ok(())
+    }
+
+    async fn do_exited(&mut self) -> Result<(), Error> {
+        log::trace!("[Update event: Image] `do_exited` {}", self.inner.image_name);
+        if !self.should_be_active() {
+            self.inner.restarts = 0;
+            self.status.set(Status::Idle);
+            self.update_task_status(TaskStatus::Inactive)?;
+        }
+        Ok(())
+    }
+
+    async fn do_timeout(&mut self) -> Result<(), Error> {
+        log::trace!("[Update event: Image] `do_timeout` {}", self.inner.image_name);
+        if let Status::Timeout { phase, duration } = self.status.get() {
+            let reason = format!("{} timed out after {}s", phase, duration.as_secs());
+            // The container could be in any state here, so remove it before the next attempt
+            if let Err(err) = self.try_remove_container().await {
+                log::debug!("Can't remove the container after a timeout: {}", err);
+            }
+            self.fail_or_restart(reason, true)?;
+        }
+        Ok(())
+    }
+
     async fn do_wait_container_started(&mut self) -> Result<(), Error> {
         log::trace!(
             "[Update event: Image] `do_wait_container_started` {}",
@@ -310,6 +415,13 @@ impl<C: ManagedProtocol> TaskContext<ImageTask<C>> {
 
     async fn do_drop_image(&mut self) -> Result<(), Error> {
         log::trace!("[Update event: Image] `do_drop_image` {}", self.inner.image_name);
-        self.try_remove_image().await
+        if let Err(err) = self.try_remove_image().await {
+            let reason = format!("Can't remove the image {}: {}", self.inner.image_name, err);
+            log::warn!("{}", reason);
+            self.sender().send_error(reason)?;
+        }
+        // The image is pulled again if it was removed
+        self.status.set(Status::InitialState);
+        Ok(())
     }
 }
@@ -24,15 +24,17 @@
 pub mod config;
 mod error;
 mod forwarder;
+pub mod graph;
 pub mod image;
 pub mod network;
+pub mod runtime;
 mod scope;
 mod status;
 mod task;
 pub mod utils;
 pub mod volume;
 
-pub use scope::{Report, ReportEnvelope, SdmScope};
+pub use scope::{Reconciliation, Report, ReportEnvelope, SdmScope};
 
 // Is temporary mod
 pub mod ids {
@@ -27,7 +27,7 @@ use std::fmt;
 
 pub(crate) use task::NetworkTask;
 
-use crate::config::ManagedProtocol;
+use crate::{config::ManagedProtocol, runtime::NetworkSpec};
 
 pub trait ManagedNetwork: fmt::Debug + Send + 'static {
     type Protocol: ManagedProtocol;
@@ -39,4 +39,10 @@ pub trait ManagedNetwork: fmt::Debug + Send + 'static {
 
     // TODO: Move to `Hierarchy`
     fn network_name(&self) -> &str;
+
+    /// The driver and the address ranges of the network. The network is recreated
+    /// when the spec is changed.
+    fn spec(&self) -> NetworkSpec {
+        NetworkSpec::default()
+    }
 }
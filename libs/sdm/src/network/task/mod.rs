@@ -32,6 +32,7 @@ use super::ManagedNetwork;
 use crate::{
     config::ManagedProtocol,
     error::ParseError,
+    runtime::NetworkSpec,
     task::{RunnableContext, RunnableTask, TaskContext, TaskStatusChecker},
     utils::TaskGuard,
 };
@@ -40,15 +41,24 @@ pub struct NetworkTask<C: ManagedProtocol> {
     network: Box<dyn ManagedNetwork<Protocol = C>>,
     events: Option<TaskGuard<()>>,
     network_name: String,
+    spec: NetworkSpec,
+    /// The network has to be recreated with the new spec.
+    spec_changed: bool,
+    /// The validation error that has been reported already.
+    invalid_spec: Option<String>,
 }
 
 impl<C: ManagedProtocol> NetworkTask<C> {
     pub fn new(scope: &str, network: Box<dyn ManagedNetwork<Protocol = C>>) -> Self {
         let network_name = format!("{}_{}", scope, network.network_name());
+        let spec = network.spec();
         Self {
             network,
             events: None,
             network_name,
+            spec,
+            spec_changed: false,
+            invalid_spec: None,
         }
     }
 }
@@ -75,7 +85,20 @@ impl<C: ManagedProtocol> RunnableContext<NetworkTask<C>> for TaskContext<Network
     }
 
     fn reconfigure(&mut self, config: Option<&C::Config>) -> bool {
-        self.inner.network.reconfigure(config)
+        let active = self.inner.network.reconfigure(config);
+        let spec = self.inner.network.spec();
+        if spec != self.inner.spec {
+            log::info!(
+                "The spec of the network {} is changed: {:?}",
+                self.inner.network_name,
+                spec
+            );
+            self.inner.spec = spec;
+            if let Status::WaitCreating | Status::Active = self.status.get() {
+                self.inner.spec_changed = true;
+            }
+        }
+        active
     }
 
     fn process_inner_event(&mut self, _event: C::Inner) {
@@ -105,6 +128,10 @@ impl TaskStatusChecker for Status {
     fn is_ready(&self) -> bool {
         matches!(self, Self::Active)
     }
+
+    fn is_stopped(&self) -> bool {
+        matches!(self, Self::Inactive)
+    }
 }
 
 impl Default for Status {
@@ -67,9 +67,21 @@ impl<C: ManagedProtocol> TaskContext<NetworkTask<C>> {
     async fn do_inactive(&mut self) -> Result<(), Error> {
         log::trace!("[Update event: Network] `do_inactive`");
         if self.should_be_active() {
+            if let Err(err) = self.inner.spec.validate() {
+                let reason = err.to_string();
+                // The task waits for a valid spec, so the error is reported once
+                if self.inner.invalid_spec.as_ref() != Some(&reason) {
+                    log::error!("The network {} can't be created: {}", self.inner.network_name, reason);
+                    self.update_task_status(TaskStatus::Failed(reason.clone()))?;
+                    self.inner.invalid_spec = Some(reason);
+                }
+                return Ok(());
+            }
             self.try_create_network().await?;
             self.status.set(Status::WaitCreating);
             self.update_task_status(TaskStatus::Pending)?;
+        } else if self.inner.invalid_spec.take().is_some() {
+            self.update_task_status(TaskStatus::Inactive)?;
         }
         Ok(())
     }
@@ -82,7 +94,14 @@ impl<C: ManagedProtocol> TaskContext<NetworkTask<C>> {
     async fn do_active(&mut self) -> Result<(), Error> {
         log::trace!("[Update event: Network] `do_active`");
         if !self.should_be_active() {
+            // The network is created with the actual spec anyway
+            self.inner.spec_changed = false;
+            self.status.set(Status::Cleanup);
+        } else if self.inner.spec_changed {
+            log::info!("Recreating the network {} with the new spec", self.inner.network_name);
+            self.inner.spec_changed = false;
             self.status.set(Status::Cleanup);
+            self.update_task_status(TaskStatus::Pending)?;
         }
         Ok(())
     }
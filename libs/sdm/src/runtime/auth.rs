@@ -0,0 +1,159 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Credentials of image registries.
+//!
+//! The credentials are taken from the settings of an image. If there are none, the `auths` entries of the Docker
+//! CLI config (`$DOCKER_CONFIG/config.json` or `~/.docker/config.json`) are used. Credential helpers
+//! (`credsStore` and `credHelpers`) are not supported.
+
+use std::{collections::HashMap, fmt, path::PathBuf};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Deserialize;
+
+/// The registry that is used for images without a registry host.
+pub const DEFAULT_REGISTRY_HOST: &str = "docker.io";
+
+/// Credentials used to pull images from a registry.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct RegistryAuth {
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub identity_token: Option<String>,
+}
+
+impl fmt::Debug for RegistryAuth {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Secrets are never printed to logs
+        f.debug_struct("RegistryAuth")
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "***"))
+            .field("identity_token", &self.identity_token.as_ref().map(|_| "***"))
+            .finish()
+    }
+}
+
+/// A registry rejected the credentials, or requires them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthError {
+    pub registry: String,
+    pub reason: String,
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Authentication to the registry {} failed: {}",
+            self.registry, self.reason
+        )
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+/// Returns the host of the registry of the image, like `ghcr.io` for `ghcr.io/tari-project/tor:latest`.
+pub fn registry_host(image: &str) -> &str {
+    match image.split_once('/') {
+        Some((host, _)) if host.contains('.') || host.contains(':') || host == "localhost" => host,
+        _ => DEFAULT_REGISTRY_HOST,
+    }
+}
+
+/// Checks if the error message of a registry means that the credentials are missing or wrong.
+pub fn is_auth_failure(message: &str) -> bool {
+    let message = message.to_lowercase();
+    [
+        "unauthorized",
+        "authentication required",
+        "access denied",
+        "no basic auth credentials",
+    ]
+    .iter()
+    .any(|pattern| message.contains(pattern))
+}
+
+#[derive(Debug, Deserialize)]
+struct DockerConfig {
+    #[serde(default)]
+    auths: HashMap<String, DockerAuth>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DockerAuth {
+    auth: Option<String>,
+    identitytoken: Option<String>,
+}
+
+fn docker_config_path() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("DOCKER_CONFIG") {
+        return Some(PathBuf::from(dir).join("config.json"));
+    }
+    dirs_next::home_dir().map(|home| home.join(".docker").join("config.json"))
+}
+
+/// Reads the credentials of the registry from the config of the Docker CLI.
+pub fn from_docker_config(registry: &str) -> Option<RegistryAuth> {
+    let path = docker_config_path()?;
+    let data = std::fs::read_to_string(&path).ok()?;
+    let config: DockerConfig = serde_json::from_str(&data)
+        .map_err(|err| log::warn!("Can't parse the docker config {}: {}", path.display(), err))
+        .ok()?;
+    find_auth(&config, registry)
+}
+
+fn find_auth(config: &DockerConfig, registry: &str) -> Option<RegistryAuth> {
+    let registry = normalize_host(registry);
+    let entry = config
+        .auths
+        .iter()
+        .find(|(key, _)| normalize_host(key) == registry)
+        .map(|(_, entry)| entry)?;
+    let mut auth = RegistryAuth {
+        identity_token: entry.identitytoken.clone().filter(|token| !token.is_empty()),
+        ..Default::default()
+    };
+    if let Some(encoded) = &entry.auth {
+        let decoded = STANDARD.decode(encoded).ok()?;
+        let decoded = String::from_utf8(decoded).ok()?;
+        let (username, password) = decoded.split_once(':')?;
+        auth.username = Some(username.to_string());
+        auth.password = Some(password.to_string());
+    }
+    (auth != RegistryAuth::default()).then_some(auth)
+}
+
+/// Keys of the docker config could be urls, and Docker Hub has a few names.
+fn normalize_host(key: &str) -> &str {
+    let host = key
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .split('/')
+        .next()
+        .unwrap_or_default();
+    match host {
+        "index.docker.io" | "registry-1.docker.io" => DEFAULT_REGISTRY_HOST,
+        _ => host,
+    }
+}
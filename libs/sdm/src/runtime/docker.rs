@@ -0,0 +1,913 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr},
+    path::Path,
+    time::Duration,
+};
+
+use anyhow::{anyhow, Error};
+use async_trait::async_trait;
+use bollard::{
+    auth::DockerCredentials,
+    container::{
+        AttachContainerOptions, AttachContainerResults, Config, CreateContainerOptions, ListContainersOptions,
+        LogOutput, LogsOptions, NetworkingConfig, RemoveContainerOptions, ResizeContainerTtyOptions,
+        Stats as BollardStats, StatsOptions, StopContainerOptions, WaitContainerOptions,
+    },
+    errors::Error as BollardError,
+    exec::{CreateExecOptions, StartExecResults},
+    image::{CreateImageOptions, RemoveImageOptions},
+    models::{
+        ContainerStateStatusEnum, CreateImageInfo, EndpointSettings, EventMessage, EventMessageTypeEnum, HostConfig,
+        Ipam, IpamConfig, Mount as BollardMount, MountTypeEnum, PortBinding, PortMap,
+    },
+    network::{CreateNetworkOptions, InspectNetworkOptions, ListNetworksOptions},
+    system::EventsOptions,
+    volume::{CreateVolumeOptions, ListVolumesOptions},
+    Docker,
+};
+use chrono::{DateTime, Local, Utc};
+use futures::{future, stream, Stream, StreamExt, TryStreamExt};
+use tari_launchpad_protocol::container::{ExecOutput, StatsData};
+use tokio::{io::AsyncWriteExt, sync::mpsc};
+use tokio_stream::wrappers::ReceiverStream;
+
+use super::{
+    auth::{self, AuthError, RegistryAuth, DEFAULT_REGISTRY_HOST},
+    Attachment, ContainerRuntime, ContainerSpec, ContainerState, LabelledResource, Labels, LogLine, MountSpec,
+    NetworkSpec, PortMapping, PullProgress, ResourceKind, RuntimeEvent, RuntimeStream,
+};
+
+/// A runtime backed by the Docker Engine API.
+///
+/// Any engine that implements the API (Podman's compatible socket, a remote daemon)
+/// can be used by passing a configured client to [`DockerRuntime::new`].
+#[derive(Clone)]
+pub struct DockerRuntime {
+    docker: Docker,
+}
+
+impl DockerRuntime {
+    pub fn new(docker: Docker) -> Self {
+        Self { docker }
+    }
+
+    pub fn connect() -> Result<Self, Error> {
+        let docker = Docker::connect_with_local_defaults()?;
+        Ok(Self::new(docker))
+    }
+}
+
+/// A small image with `tar` and `rm` that is used to access the content of volumes.
+const HELPER_IMAGE: &str = "busybox:stable";
+/// Where the helper container mounts the volume.
+const VOLUME_PATH: &str = "/volume";
+const CHUNKS_BUFFER: usize = 16;
+
+impl DockerRuntime {
+    async fn pull_helper(&self) -> Result<(), Error> {
+        if !self.image_exists(HELPER_IMAGE).await {
+            let opts = CreateImageOptions {
+                from_image: HELPER_IMAGE,
+                ..Default::default()
+            };
+            self.docker
+                .create_image(Some(opts), None, None)
+                .try_collect::<Vec<_>>()
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Starts a helper container that runs the command over the mounted volume.
+    async fn start_helper(
+        &self,
+        volume: &str,
+        cmd: &[&str],
+        stdin: bool,
+    ) -> Result<(String, AttachContainerResults), Error> {
+        self.pull_helper().await?;
+        let name = format!("{}_helper", volume);
+        // A leftover of an interrupted transfer
+        self.remove_container(&name).await.ok();
+        let cmd = cmd.iter().map(|arg| arg.to_string()).collect();
+        let mount = MountSpec::Volume {
+            source: volume.to_string(),
+            target: VOLUME_PATH.to_string(),
+        };
+        let config = Config::<String> {
+            image: Some(HELPER_IMAGE.to_string()),
+            cmd: Some(cmd),
+            attach_stdin: Some(stdin),
+            attach_stdout: Some(true),
+            attach_stderr: Some(true),
+            open_stdin: Some(stdin),
+            stdin_once: Some(stdin),
+            tty: Some(false),
+            host_config: Some(HostConfig {
+                mounts: Some(vec![make_mount(mount)]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let opts = CreateContainerOptions {
+            name: name.clone(),
+            platform: None,
+        };
+        self.docker.create_container(Some(opts), config).await?;
+        let opts = AttachContainerOptions::<String> {
+            stdin: Some(stdin),
+            stdout: Some(true),
+            stderr: Some(true),
+            stream: Some(true),
+            logs: Some(false),
+            detach_keys: None,
+        };
+        let attached = self.docker.attach_container(&name, Some(opts)).await?;
+        self.docker.start_container::<String>(&name, None).await?;
+        Ok((name, attached))
+    }
+
+    /// Waits for the helper to exit and removes it.
+    async fn finish_helper(&self, name: &str, stderr: String) -> Result<(), Error> {
+        let exited = self
+            .docker
+            .wait_container(name, None::<WaitContainerOptions<String>>)
+            .next()
+            .await;
+        self.remove_container(name).await.ok();
+        match exited {
+            Some(Ok(response)) if response.status_code == 0 => Ok(()),
+            Some(Ok(response)) => Err(anyhow!(
+                "The helper exited with {}: {}",
+                response.status_code,
+                stderr.trim()
+            )),
+            Some(Err(err)) => Err(anyhow!("The helper failed: {} {}", err, stderr.trim())),
+            None => Err(anyhow!("The helper container {} has gone", name)),
+        }
+    }
+
+    async fn export_to(
+        &self,
+        volume: &str,
+        path: &str,
+        tx: &mpsc::Sender<Result<Vec<u8>, Error>>,
+    ) -> Result<(), Error> {
+        let cmd = ["tar", "cf", "-", "-C", VOLUME_PATH, path];
+        let (helper, AttachContainerResults { mut output, .. }) = self.start_helper(volume, &cmd, false).await?;
+        let mut stderr = String::new();
+        let mut res = Ok(());
+        while let Some(item) = output.next().await {
+            match item {
+                Ok(LogOutput::StdOut { message }) => {
+                    if tx.send(Ok(message.to_vec())).await.is_err() {
+                        // The reader has gone, `tar` will be interrupted by the closed pipe
+                        break;
+                    }
+                },
+                Ok(LogOutput::StdErr { message }) => {
+                    stderr.push_str(&String::from_utf8_lossy(&message));
+                },
+                Ok(_) => {},
+                Err(err) => {
+                    res = Err(err.into());
+                    break;
+                },
+            }
+        }
+        drop(output);
+        let finished = self.finish_helper(&helper, stderr).await;
+        res.and(finished)
+    }
+}
+
+#[async_trait]
+impl ContainerRuntime for DockerRuntime {
+    fn events(&self, kind: ResourceKind, name: &str) -> RuntimeStream<RuntimeEvent> {
+        let mut type_filter = HashMap::new();
+        type_filter.insert("type".to_string(), vec![kind.as_str().to_string()]);
+        type_filter.insert(kind.as_str().to_string(), vec![name.to_string()]);
+        let opts = EventsOptions {
+            since: None,
+            until: None,
+            filters: type_filter,
+        };
+        self.docker
+            .events(Some(opts))
+            .map_err(Error::from)
+            .try_filter_map(|msg| future::ready(Ok(event_conv(msg))))
+            .boxed()
+    }
+
+    async fn image_exists(&self, image: &str) -> bool {
+        self.docker.inspect_image(image).await.is_ok()
+    }
+
+    async fn image_digest(&self, image: &str) -> Option<String> {
+        let info = self.docker.inspect_image(image).await.ok()?;
+        // The registry digest is preferred, since the id of the image differs between machines
+        let repository = image.rsplit_once(':').map(|(repo, _)| repo).unwrap_or(image);
+        let repo_digests = info.repo_digests.unwrap_or_default();
+        let digest = repo_digests
+            .iter()
+            .find(|digest| digest.starts_with(&format!("{}@", repository)))
+            .or_else(|| repo_digests.first())
+            .and_then(|digest| digest.split_once('@'))
+            .map(|(_, digest)| digest.to_string());
+        digest.or(info.id)
+    }
+
+    fn pull_image(&self, image: &str, auth: Option<RegistryAuth>) -> RuntimeStream<PullProgress> {
+        let opts = Some(CreateImageOptions {
+            from_image: image.to_string(),
+            ..Default::default()
+        });
+        let registry = auth::registry_host(image).to_string();
+        let credentials = auth
+            .or_else(|| auth::from_docker_config(&registry))
+            .map(|auth| credentials_conv(auth, &registry));
+        self.docker
+            .create_image(opts, None, credentials)
+            .map_ok(progress_conv)
+            .map_err(move |err| pull_error_conv(err, &registry))
+            .boxed()
+    }
+
+    async fn remove_image(&self, image: &str) -> Result<(), Error> {
+        let opts = Some(RemoveImageOptions {
+            force: true,
+            ..Default::default()
+        });
+        self.docker.remove_image(image, opts, None).await?;
+        Ok(())
+    }
+
+    async fn container_state(&self, name: &str) -> ContainerState {
+        let res = self.docker.inspect_container(name, None).await;
+        match res {
+            Ok(ref response) => {
+                if let Some(state) = response.state.clone() {
+                    if let Some(status) = state.status {
+                        match status {
+                            ContainerStateStatusEnum::EMPTY => ContainerState::Empty,
+                            ContainerStateStatusEnum::CREATED => ContainerState::Created,
+                            ContainerStateStatusEnum::RUNNING => ContainerState::Running,
+                            ContainerStateStatusEnum::PAUSED => ContainerState::Paused,
+                            ContainerStateStatusEnum::RESTARTING => ContainerState::Restarting,
+                            ContainerStateStatusEnum::REMOVING => ContainerState::Removing,
+                            ContainerStateStatusEnum::EXITED => ContainerState::Exited,
+                            ContainerStateStatusEnum::DEAD => ContainerState::Dead,
+                        }
+                    } else {
+                        log::error!("Status of container `{}` not defined: {:?}", name, res);
+                        ContainerState::ErrorStatusNotDefined
+                    }
+                } else {
+                    log::error!("State of container `{}` not defined: {:?}", name, res);
+                    ContainerState::ErrorStateNotDefined
+                }
+            },
+            Err(_) => ContainerState::NotFound,
+        }
+    }
+
+    async fn create_container(&self, name: &str, spec: ContainerSpec, labels: &Labels) -> Result<(), Error> {
+        let opts = CreateContainerOptions {
+            name: name.to_string(),
+            platform: None,
+        };
+        let config = Config {
+            image: Some(spec.image),
+            attach_stdin: Some(false),
+            attach_stdout: Some(false),
+            attach_stderr: Some(false),
+            exposed_ports: Some(exposed_ports(&spec.ports)),
+            open_stdin: Some(true),
+            stdin_once: Some(false),
+            tty: Some(true),
+            env: Some(spec.envs),
+            volumes: Some(volumes_map(spec.volumes)),
+            cmd: Some(spec.args),
+            host_config: Some(HostConfig {
+                auto_remove: Some(true),
+                binds: Some(vec![]),
+                network_mode: Some("bridge".to_string()),
+                port_bindings: Some(ports_map(&spec.ports)),
+                mounts: Some(spec.mounts.into_iter().map(make_mount).collect()),
+                nano_cpus: spec.resources.nano_cpus,
+                cpuset_cpus: spec.resources.cpuset_cpus,
+                memory: spec.resources.memory,
+                memory_reservation: spec.resources.memory_reservation,
+                pids_limit: spec.resources.pids_limit,
+                ..Default::default()
+            }),
+            networking_config: Some(networks_map(spec.networks)),
+            labels: Some(labels.to_map()),
+            ..Default::default()
+        };
+        self.docker.create_container(Some(opts), config).await?;
+        Ok(())
+    }
+
+    async fn start_container(&self, name: &str) -> Result<(), Error> {
+        self.docker.start_container::<String>(name, None).await?;
+        Ok(())
+    }
+
+    async fn stop_container(&self, name: &str, grace: Duration) -> Result<(), Error> {
+        let opts = StopContainerOptions {
+            t: grace.as_secs() as i64,
+        };
+        self.docker.stop_container(name, Some(opts)).await?;
+        Ok(())
+    }
+
+    async fn kill_container(&self, name: &str) -> Result<(), Error> {
+        self.docker.kill_container::<String>(name, None).await?;
+        Ok(())
+    }
+
+    async fn unpause_container(&self, name: &str) -> Result<(), Error> {
+        self.docker.unpause_container(name).await?;
+        Ok(())
+    }
+
+    async fn remove_container(&self, name: &str) -> Result<(), Error> {
+        let opts = RemoveContainerOptions {
+            force: true,
+            ..Default::default()
+        };
+        self.docker.remove_container(name, Some(opts)).await?;
+        Ok(())
+    }
+
+    fn logs(&self, name: &str, since: Option<DateTime<Utc>>) -> RuntimeStream<LogLine> {
+        let opts = LogsOptions::<String> {
+            follow: true,
+            stdout: true,
+            stderr: true,
+            // The engine compares it in seconds
+            since: since.map(|since| since.timestamp()).unwrap_or_default(),
+            timestamps: true,
+            ..Default::default()
+        };
+        self.docker.logs(name, Some(opts)).map(log_conv).boxed()
+    }
+
+    fn stats(&self, name: &str) -> RuntimeStream<StatsData> {
+        let opts = StatsOptions {
+            stream: true,
+            one_shot: false,
+        };
+        self.docker.stats(name, Some(opts)).map(stat_conv).boxed()
+    }
+
+    fn exec(&self, name: &str, command: Vec<String>) -> RuntimeStream<ExecOutput> {
+        let docker = self.docker.clone();
+        let opts = CreateExecOptions {
+            cmd: Some(command),
+            attach_stdout: Some(true),
+            attach_stderr: Some(true),
+            ..Default::default()
+        };
+        let name = name.to_string();
+        let started = async move {
+            let exec_id = docker.create_exec(&name, opts).await?.id;
+            match docker.start_exec(&exec_id, None).await? {
+                StartExecResults::Attached { output, .. } => {
+                    // The exit code is known only when the output is closed
+                    let exited = stream::once(async move {
+                        let exit_code = docker.inspect_exec(&exec_id).await?.exit_code;
+                        Ok(ExecOutput::Exited(exit_code.unwrap_or_default()))
+                    });
+                    Ok(output.map(exec_conv).chain(exited).boxed())
+                },
+                StartExecResults::Detached => Err(anyhow!("The exec in container {} was detached", name)),
+            }
+        };
+        stream::once(started)
+            .map(|res: Result<RuntimeStream<ExecOutput>, Error>| {
+                res.unwrap_or_else(|err| stream::once(future::ready(Err(err))).boxed())
+            })
+            .flatten()
+            .boxed()
+    }
+
+    async fn attach(&self, name: &str) -> Result<Attachment, Error> {
+        let opts = AttachContainerOptions::<String> {
+            stdin: Some(true),
+            stdout: Some(true),
+            stderr: Some(true),
+            stream: Some(true),
+            // The past output is available in the logs
+            logs: Some(false),
+            detach_keys: None,
+        };
+        let AttachContainerResults { output, input } = self.docker.attach_container(name, Some(opts)).await?;
+        Ok(Attachment {
+            output: output
+                .map_err(Error::from)
+                .try_filter_map(|output| future::ready(Ok(console_conv(output))))
+                .boxed(),
+            input,
+        })
+    }
+
+    async fn resize_tty(&self, name: &str, width: u16, height: u16) -> Result<(), Error> {
+        let opts = ResizeContainerTtyOptions { width, height };
+        self.docker.resize_container_tty(name, opts).await?;
+        Ok(())
+    }
+
+    async fn volume_exists(&self, name: &str) -> bool {
+        self.docker.inspect_volume(name).await.is_ok()
+    }
+
+    async fn create_volume(&self, name: &str, labels: &Labels) -> Result<(), Error> {
+        let labels = labels.to_map();
+        let options = CreateVolumeOptions {
+            name,
+            labels: label_refs(&labels),
+            ..Default::default()
+        };
+        self.docker.create_volume(options).await?;
+        // TODO: Check warnings...
+        Ok(())
+    }
+
+    async fn remove_volume(&self, name: &str) -> Result<(), Error> {
+        self.docker.remove_volume(name, None).await?;
+        Ok(())
+    }
+
+    fn export_volume(&self, name: &str, path: &str) -> RuntimeStream<Vec<u8>> {
+        let runtime = self.clone();
+        let volume = name.to_string();
+        let path = if path.is_empty() {
+            ".".to_string()
+        } else {
+            path.to_string()
+        };
+        let (tx, rx) = mpsc::channel(CHUNKS_BUFFER);
+        tokio::spawn(async move {
+            if let Err(err) = runtime.export_to(&volume, &path, &tx).await {
+                tx.send(Err(err)).await.ok();
+            }
+        });
+        ReceiverStream::new(rx).boxed()
+    }
+
+    async fn import_volume(&self, name: &str, mut archive: RuntimeStream<Vec<u8>>) -> Result<(), Error> {
+        let cmd = ["tar", "xf", "-", "-C", VOLUME_PATH];
+        let (helper, AttachContainerResults { output, mut input }) = self.start_helper(name, &cmd, true).await?;
+        // The output is drained in parallel to not block `tar` on a full pipe
+        let stderr = tokio::spawn(collect_stderr(output));
+        let mut res = Ok(());
+        while let Some(chunk) = archive.next().await {
+            let written = match chunk {
+                Ok(chunk) => input.write_all(&chunk).await.map_err(Error::from),
+                Err(err) => Err(err),
+            };
+            if let Err(err) = written {
+                res = Err(err);
+                break;
+            }
+        }
+        // Closes stdin to let `tar` finish
+        input.shutdown().await.ok();
+        let stderr = stderr.await.unwrap_or_default();
+        let finished = self.finish_helper(&helper, stderr).await;
+        res.and(finished)
+    }
+
+    async fn clear_volume(&self, name: &str, path: &str) -> Result<(), Error> {
+        let target = format!("{}/{}", VOLUME_PATH, path);
+        let cmd = ["rm", "-rf", &target];
+        let (helper, AttachContainerResults { output, .. }) = self.start_helper(name, &cmd, false).await?;
+        let stderr = collect_stderr(output).await;
+        self.finish_helper(&helper, stderr).await
+    }
+
+    async fn volume_size(&self, name: &str) -> Option<u64> {
+        // The engine calculates the usage of all volumes at once
+        let usage = self.docker.df().await.ok()?;
+        let volume = usage.volumes?.into_iter().find(|volume| volume.name == name)?;
+        // The size is `-1` when it's not calculated
+        volume.usage_data.and_then(|data| u64::try_from(data.size).ok())
+    }
+
+    async fn volume_users(&self, name: &str) -> Result<Vec<String>, Error> {
+        let mut filters = HashMap::new();
+        filters.insert("volume".to_string(), vec![name.to_string()]);
+        let opts = ListContainersOptions {
+            all: true,
+            filters,
+            ..Default::default()
+        };
+        let containers = self.docker.list_containers(Some(opts)).await?;
+        let names = containers
+            .into_iter()
+            .flat_map(|container| container.names.unwrap_or_default())
+            .map(|name| name.trim_start_matches('/').to_string())
+            .collect();
+        Ok(names)
+    }
+
+    async fn network_exists(&self, name: &str) -> bool {
+        let opts = InspectNetworkOptions {
+            verbose: false,
+            scope: "local",
+        };
+        self.docker.inspect_network(name, Some(opts)).await.is_ok()
+    }
+
+    async fn create_network(&self, name: &str, spec: &NetworkSpec, labels: &Labels) -> Result<(), Error> {
+        let config = spec
+            .subnets
+            .iter()
+            .map(|subnet| IpamConfig {
+                subnet: Some(subnet.cidr.clone()),
+                gateway: subnet.gateway.clone(),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        let labels = labels.to_map();
+        let ipam = Ipam {
+            driver: Some("default".to_string()),
+            config: (!config.is_empty()).then_some(config),
+            options: None,
+        };
+        let options = CreateNetworkOptions {
+            name,
+            check_duplicate: true,
+            driver: spec.driver.as_str(),
+            internal: spec.internal,
+            attachable: false,
+            ingress: false,
+            ipam,
+            enable_ipv6: spec.ipv6,
+            options: Default::default(),
+            labels: label_refs(&labels),
+        };
+        self.docker.create_network(options).await?;
+        // TODO: Check warnings...
+        Ok(())
+    }
+
+    async fn remove_network(&self, name: &str) -> Result<(), Error> {
+        self.docker.remove_network(name).await?;
+        Ok(())
+    }
+
+    async fn scope_resources(&self, kind: ResourceKind, scope: &str) -> Result<Vec<LabelledResource>, Error> {
+        let mut filters = HashMap::new();
+        filters.insert("label".to_string(), vec![format!("{}={}", Labels::SCOPE, scope)]);
+        let resources: Vec<(String, HashMap<String, String>)> = match kind {
+            ResourceKind::Container => {
+                let opts = ListContainersOptions {
+                    all: true,
+                    filters,
+                    ..Default::default()
+                };
+                let containers = self.docker.list_containers(Some(opts)).await?;
+                containers
+                    .into_iter()
+                    .filter_map(|container| {
+                        let name = container.names?.first()?.trim_start_matches('/').to_string();
+                        Some((name, container.labels.unwrap_or_default()))
+                    })
+                    .collect()
+            },
+            ResourceKind::Volume => {
+                let opts = ListVolumesOptions { filters };
+                let volumes = self.docker.list_volumes(Some(opts)).await?;
+                volumes
+                    .volumes
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|volume| (volume.name, volume.labels))
+                    .collect()
+            },
+            ResourceKind::Network => {
+                let opts = ListNetworksOptions { filters };
+                let networks = self.docker.list_networks(Some(opts)).await?;
+                networks
+                    .into_iter()
+                    .filter_map(|network| Some((network.name?, network.labels.unwrap_or_default())))
+                    .collect()
+            },
+        };
+        let resources = resources
+            .into_iter()
+            .filter_map(|(name, labels)| {
+                let labels = Labels::from_map(&labels)?;
+                Some(LabelledResource { kind, name, labels })
+            })
+            .collect();
+        Ok(resources)
+    }
+}
+
+fn label_refs(labels: &HashMap<String, String>) -> HashMap<&str, &str> {
+    labels
+        .iter()
+        .map(|(key, value)| (key.as_str(), value.as_str()))
+        .collect()
+}
+
+fn event_conv(msg: EventMessage) -> Option<RuntimeEvent> {
+    let kind = match msg.typ? {
+        EventMessageTypeEnum::CONTAINER => ResourceKind::Container,
+        EventMessageTypeEnum::VOLUME => ResourceKind::Volume,
+        EventMessageTypeEnum::NETWORK => ResourceKind::Network,
+        _ => return None,
+    };
+    let action = msg.action?;
+    let mut attributes = msg.actor?.attributes?;
+    let name = attributes.remove("name")?;
+    let exit_code = attributes.get("exitCode").and_then(|code| code.parse().ok());
+    Some(RuntimeEvent {
+        kind,
+        action,
+        name,
+        exit_code,
+    })
+}
+
+fn credentials_conv(auth: RegistryAuth, registry: &str) -> DockerCredentials {
+    let serveraddress = if registry == DEFAULT_REGISTRY_HOST {
+        "https://index.docker.io/v1/".to_string()
+    } else {
+        registry.to_string()
+    };
+    DockerCredentials {
+        username: auth.username,
+        password: auth.password,
+        identitytoken: auth.identity_token,
+        serveraddress: Some(serveraddress),
+        ..Default::default()
+    }
+}
+
+/// Separates authentication failures from other errors of pulling.
+fn pull_error_conv(err: BollardError, registry: &str) -> Error {
+    let reason = match &err {
+        BollardError::DockerResponseServerError {
+            status_code: 401 | 403,
+            message,
+        } => Some(message.clone()),
+        BollardError::DockerResponseServerError { message, .. }
+        | BollardError::DockerStreamError { error: message }
+            if auth::is_auth_failure(message) =>
+        {
+            Some(message.clone())
+        },
+        _ => None,
+    };
+    match reason {
+        Some(reason) => AuthError {
+            registry: registry.to_string(),
+            reason,
+        }
+        .into(),
+        None => err.into(),
+    }
+}
+
+fn progress_conv(info: CreateImageInfo) -> PullProgress {
+    log::debug!("Created Image Info: {:?}", info);
+    let details = info.progress_detail.unwrap_or_default();
+    PullProgress {
+        layer: info.id,
+        status: info.status,
+        current: details.current,
+        total: details.total,
+    }
+}
+
+fn networks_map(networks: Vec<(String, String)>) -> NetworkingConfig<String> {
+    let mut endpoints = HashMap::new();
+    for (net_name, alias) in networks {
+        let endpoint = EndpointSettings {
+            aliases: Some(vec![alias]),
+            ..Default::default()
+        };
+        endpoints.insert(net_name, endpoint);
+    }
+    NetworkingConfig {
+        endpoints_config: endpoints,
+    }
+}
+
+fn make_mount(mount: MountSpec) -> BollardMount {
+    match mount {
+        MountSpec::Volume { source, target } => BollardMount {
+            target: Some(target),
+            source: Some(source),
+            typ: Some(MountTypeEnum::VOLUME),
+            volume_options: None,
+            ..Default::default()
+        },
+        MountSpec::Bind { source, target } => BollardMount {
+            target: Some(target),
+            source: Some(canonicalize(source)),
+            typ: Some(MountTypeEnum::BIND),
+            bind_options: None,
+            ..Default::default()
+        },
+    }
+}
+
+type BollardMap = HashMap<String, HashMap<(), ()>>;
+
+fn volumes_map(volumes: Vec<String>) -> BollardMap {
+    let mut result = BollardMap::new();
+    for volume in volumes {
+        result.insert(volume, HashMap::default());
+    }
+    result
+}
+
+fn port_key(port: &PortMapping) -> String {
+    format!("{}/{}", port.container_port, port.protocol)
+}
+
+fn exposed_ports(ports: &[PortMapping]) -> BollardMap {
+    let mut result = BollardMap::new();
+    for port in ports {
+        result.insert(port_key(port), HashMap::default());
+    }
+    result
+}
+
+fn ports_map(ports: &[PortMapping]) -> PortMap {
+    let mut result = PortMap::new();
+    for port in ports {
+        // The engine publishes the port on all IPv4 and IPv6 interfaces if the address is empty
+        let host_ip = if port.host_ip == IpAddr::V4(Ipv4Addr::UNSPECIFIED) {
+            String::new()
+        } else {
+            port.host_ip.to_string()
+        };
+        let binding = PortBinding {
+            host_ip: Some(host_ip),
+            host_port: Some(port.host_port.to_string()),
+        };
+        result
+            .entry(port_key(port))
+            .or_insert_with(|| Some(Vec::new()))
+            .get_or_insert_with(Vec::new)
+            .push(binding);
+    }
+    result
+}
+
+fn log_conv(res: Result<LogOutput, BollardError>) -> Result<LogLine, Error> {
+    if let Ok(LogOutput::Console { message }) = res {
+        match std::str::from_utf8(message.as_ref()) {
+            Ok(data) => {
+                log::trace!("Log: {}", data);
+                Ok(split_timestamp(data))
+            },
+            Err(err) => Err(anyhow!("Can't parse log text: {}", err)),
+        }
+    } else {
+        Err(anyhow!("Unsupported log event: {:?}", res))
+    }
+}
+
+fn exec_conv(res: Result<LogOutput, BollardError>) -> Result<ExecOutput, Error> {
+    let output = match res? {
+        LogOutput::StdOut { message } | LogOutput::Console { message } => {
+            ExecOutput::Stdout(String::from_utf8_lossy(&message).into_owned())
+        },
+        LogOutput::StdErr { message } => ExecOutput::Stderr(String::from_utf8_lossy(&message).into_owned()),
+        LogOutput::StdIn { message } => return Err(anyhow!("Unexpected input of exec: {:?}", message)),
+    };
+    Ok(output)
+}
+
+fn console_conv(output: LogOutput) -> Option<String> {
+    match output {
+        LogOutput::Console { message } | LogOutput::StdOut { message } | LogOutput::StdErr { message } => {
+            Some(String::from_utf8_lossy(&message).into_owned())
+        },
+        LogOutput::StdIn { .. } => None,
+    }
+}
+
+/// Reads the output of a helper container to the end and keeps its errors only.
+async fn collect_stderr<S>(mut output: S) -> String
+where
+    S: Stream<Item = Result<LogOutput, BollardError>> + Unpin,
+{
+    let mut stderr = String::new();
+    while let Some(Ok(item)) = output.next().await {
+        if let LogOutput::StdErr { message } = item {
+            stderr.push_str(&String::from_utf8_lossy(&message));
+        }
+    }
+    stderr
+}
+
+/// Lines are prefixed with an RFC 3339 timestamp with nanoseconds when `timestamps` are requested.
+fn split_timestamp(data: &str) -> LogLine {
+    let parsed = data.split_once(' ').and_then(|(timestamp, message)| {
+        let timestamp = DateTime::parse_from_rfc3339(timestamp).ok()?;
+        Some((timestamp.with_timezone(&Utc), message))
+    });
+    match parsed {
+        Some((timestamp, message)) => LogLine {
+            timestamp: Some(timestamp),
+            message: message.to_string(),
+        },
+        None => LogLine {
+            timestamp: None,
+            message: data.to_string(),
+        },
+    }
+}
+
+fn stat_conv(res: Result<BollardStats, BollardError>) -> Result<StatsData, Error> {
+    if let Ok(BollardStats {
+        cpu_stats,
+        memory_stats,
+        networks,
+        blkio_stats,
+        pids_stats,
+        ..
+    }) = res
+    {
+        // Old engines don't report the number of CPUs, but report the usage per CPU
+        let online_cpus = cpu_stats.online_cpus.unwrap_or_else(|| {
+            cpu_stats
+                .cpu_usage
+                .percpu_usage
+                .as_ref()
+                .map(|usage| usage.len() as u64)
+                .unwrap_or_default()
+        });
+        let (net_rx, net_tx) = networks
+            .unwrap_or_default()
+            .values()
+            .fold((0, 0), |(rx, tx), net| (rx + net.rx_bytes, tx + net.tx_bytes));
+        let block_io = blkio_stats.io_service_bytes_recursive.unwrap_or_default();
+        let block_bytes = |op: &str| -> u64 {
+            block_io
+                .iter()
+                .filter(|entry| entry.op.eq_ignore_ascii_case(op))
+                .map(|entry| entry.value)
+                .sum()
+        };
+        Ok(StatsData {
+            timestamp: Local::now().naive_local(),
+            system_cpu_usage: cpu_stats.system_cpu_usage.unwrap_or_default() as i64,
+            cpu_usage: cpu_stats.cpu_usage.total_usage as i64,
+            online_cpus: online_cpus as u32,
+            mem_limit: memory_stats.limit.unwrap_or_default().into(),
+            mem_usage: memory_stats.usage.unwrap_or_default().into(),
+            net_rx: net_rx.into(),
+            net_tx: net_tx.into(),
+            block_read: block_bytes("read").into(),
+            block_write: block_bytes("write").into(),
+            pids: pids_stats.current.unwrap_or_default(),
+        })
+    } else {
+        Err(anyhow!("Unsupported stats event: {:?}", res))
+    }
+}
+
+// FIXME: This might be replaceable by std::fs::canonicalize, but I don't have a windows machine to check
+fn canonicalize<P: AsRef<Path>>(path: P) -> String {
+    #[cfg(target_os = "windows")]
+    let path = path.as_ref().to_string_lossy().to_string();
+    #[cfg(target_os = "macos")]
+    let path = format!("/host_mnt{}", path.as_ref().to_string_lossy());
+    #[cfg(target_os = "linux")]
+    let path = path.as_ref().to_string_lossy().to_string();
+    path
+}
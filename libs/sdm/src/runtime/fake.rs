@@ -0,0 +1,816 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! An in-memory container engine.
+//!
+//! [`FakeRuntime`] keeps images, containers, volumes and networks in memory and emits the same
+//! lifecycle events as Docker does, so the state machines of the tasks can be exercised
+//! without a daemon. Failures are scripted with [`Fault`]s.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use anyhow::{anyhow, Error};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::{future, stream, StreamExt, TryStreamExt};
+use tari_launchpad_protocol::container::{ExecOutput, StatsData};
+use tokio::{
+    io::{self, AsyncBufReadExt, BufReader},
+    sync::broadcast,
+};
+use tokio_stream::wrappers::BroadcastStream;
+
+use super::{
+    auth::{self, AuthError, RegistryAuth},
+    Attachment, ContainerRuntime, ContainerSpec, ContainerState, LabelledResource, Labels, LogLine, MountSpec,
+    NetworkSpec, PortConflict, PortMapping, PullProgress, ResourceKind, RuntimeEvent, RuntimeStream,
+};
+
+const CHANNEL_SIZE: usize = 64;
+/// Messages of pulling an image: a layer, a status, current and total bytes.
+const PULL_SCRIPT: &[(&str, &str, i64, i64)] = &[
+    ("a", "Pulling fs layer", 0, 0),
+    ("b", "Pulling fs layer", 0, 0),
+    ("a", "Downloading", 100, 300),
+    ("b", "Downloading", 50, 100),
+    ("a", "Downloading", 200, 300),
+    ("b", "Download complete", 0, 0),
+    ("b", "Extracting", 100, 100),
+    ("b", "Pull complete", 0, 0),
+    ("a", "Downloading", 300, 300),
+    ("a", "Download complete", 0, 0),
+    ("a", "Extracting", 150, 300),
+    ("a", "Extracting", 300, 300),
+    ("a", "Pull complete", 0, 0),
+];
+/// A delay between messages of pulling an image.
+const PULL_STEP: Duration = Duration::from_millis(100);
+/// The first port of the dynamic range given to relocated ports.
+const DYNAMIC_PORTS: u16 = 49_152;
+/// The exit code of a shell when a command is not found.
+const COMMAND_NOT_FOUND: i64 = 127;
+/// The buffer of the input of an attached terminal.
+const TTY_BUFFER: usize = 1024;
+
+/// A scripted failure of the next matching call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fault {
+    PullImage {
+        image: String,
+        reason: String,
+    },
+    CreateContainer {
+        name: String,
+        reason: String,
+    },
+    StartContainer {
+        name: String,
+        reason: String,
+    },
+    /// The container ignores the stop signal and keeps running until it's killed.
+    StopContainer {
+        name: String,
+    },
+}
+
+struct FakeContainer {
+    spec: ContainerSpec,
+    state: ContainerState,
+    /// All the lines of the container, they're replayed to new followers like Docker does.
+    history: Vec<LogLine>,
+    /// Scripted outputs of commands executed in the container.
+    execs: HashMap<Vec<String>, Vec<ExecOutput>>,
+    /// Lines written to the terminal of the container.
+    input: Vec<String>,
+    logs: broadcast::Sender<LogLine>,
+    stats: broadcast::Sender<StatsData>,
+}
+
+impl FakeContainer {
+    fn new(spec: ContainerSpec, state: ContainerState) -> Self {
+        let (logs, _) = broadcast::channel(CHANNEL_SIZE);
+        let (stats, _) = broadcast::channel(CHANNEL_SIZE);
+        Self {
+            spec,
+            state,
+            history: Vec::new(),
+            execs: HashMap::new(),
+            input: Vec::new(),
+            logs,
+            stats,
+        }
+    }
+}
+
+struct Engine {
+    images: HashSet<String>,
+    /// Digests of the images, the ones without a digest report nothing.
+    digests: HashMap<String, String>,
+    /// Registries that accept only the given credentials.
+    logins: HashMap<String, RegistryAuth>,
+    containers: HashMap<String, FakeContainer>,
+    /// Volumes with the paths and contents of their files.
+    volumes: HashMap<String, BTreeMap<String, String>>,
+    networks: HashMap<String, NetworkSpec>,
+    /// Labels of the resources by the kind and the name.
+    labels: HashMap<(ResourceKind, String), Labels>,
+    events: broadcast::Sender<RuntimeEvent>,
+    /// Resources that don't emit any events.
+    muted: HashSet<String>,
+    /// Host ports held by other processes with the names of those processes.
+    occupied: HashMap<u16, String>,
+    /// How long it takes to calculate the disk usage of a volume.
+    usage_delay: Duration,
+    faults: Vec<Fault>,
+    journal: Vec<String>,
+}
+
+impl Engine {
+    fn emit(&self, kind: ResourceKind, action: &str, name: &str) {
+        self.emit_with_code(kind, action, name, None);
+    }
+
+    fn emit_with_code(&self, kind: ResourceKind, action: &str, name: &str, exit_code: Option<i64>) {
+        if !self.muted.contains(name) {
+            let event = RuntimeEvent {
+                kind,
+                action: action.to_string(),
+                name: name.to_string(),
+                exit_code,
+            };
+            self.events.send(event).ok();
+        }
+    }
+
+    fn take_fault<F>(&mut self, func: F) -> Option<Fault>
+    where
+        F: Fn(&Fault) -> bool,
+    {
+        let idx = self.faults.iter().position(func)?;
+        Some(self.faults.remove(idx))
+    }
+
+    /// Containers are created with `auto_remove`, so a stopped container disappears.
+    fn terminate(&mut self, name: &str, actions: &[&str], exit_code: i64) {
+        if self.containers.remove(name).is_some() {
+            self.labels.remove(&(ResourceKind::Container, name.to_string()));
+            for action in actions {
+                let code = (*action == "die").then_some(exit_code);
+                self.emit_with_code(ResourceKind::Container, action, name, code);
+            }
+            self.emit(ResourceKind::Container, "destroy", name);
+        }
+    }
+}
+
+/// An in-memory runtime for tests. Clones share the same engine.
+#[derive(Clone)]
+pub struct FakeRuntime {
+    engine: Arc<Mutex<Engine>>,
+}
+
+impl Default for FakeRuntime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FakeRuntime {
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(CHANNEL_SIZE);
+        let engine = Engine {
+            images: HashSet::new(),
+            digests: HashMap::new(),
+            logins: HashMap::new(),
+            containers: HashMap::new(),
+            volumes: HashMap::new(),
+            networks: HashMap::new(),
+            labels: HashMap::new(),
+            events,
+            muted: HashSet::new(),
+            occupied: HashMap::new(),
+            usage_delay: Duration::ZERO,
+            faults: Vec::new(),
+            journal: Vec::new(),
+        };
+        Self {
+            engine: Arc::new(Mutex::new(engine)),
+        }
+    }
+
+    fn engine(&self) -> MutexGuard<'_, Engine> {
+        self.engine.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn record(&self, call: &str, name: &str) -> MutexGuard<'_, Engine> {
+        let mut engine = self.engine();
+        engine.journal.push(format!("{} {}", call, name));
+        engine
+    }
+
+    pub fn add_image(&self, image: &str) {
+        self.engine().images.insert(image.to_string());
+    }
+
+    /// Sets the digest of the image that is reported when the image exists.
+    pub fn set_image_digest(&self, image: &str, digest: &str) {
+        self.engine().digests.insert(image.to_string(), digest.to_string());
+    }
+
+    /// Makes the calculation of the disk usage as slow as it could be with many resources.
+    pub fn set_usage_delay(&self, delay: Duration) {
+        self.engine().usage_delay = delay;
+    }
+
+    /// Makes the registry reject pulls without the given credentials.
+    pub fn require_auth(&self, registry: &str, auth: RegistryAuth) {
+        self.engine().logins.insert(registry.to_string(), auth);
+    }
+
+    pub fn has_image(&self, image: &str) -> bool {
+        self.engine().images.contains(image)
+    }
+
+    /// Puts a container to the engine without any events, e.g. a leftover of a previous run.
+    pub fn add_container(&self, name: &str, spec: ContainerSpec, state: ContainerState) {
+        let container = FakeContainer::new(spec, state);
+        self.engine().containers.insert(name.to_string(), container);
+    }
+
+    pub fn container_spec(&self, name: &str) -> Option<ContainerSpec> {
+        self.engine()
+            .containers
+            .get(name)
+            .map(|container| container.spec.clone())
+    }
+
+    pub fn has_volume(&self, name: &str) -> bool {
+        self.engine().volumes.contains_key(name)
+    }
+
+    /// Writes a file to an existing volume.
+    pub fn put_file(&self, volume: &str, path: &str, content: &str) {
+        if let Some(files) = self.engine().volumes.get_mut(volume) {
+            files.insert(path.to_string(), content.to_string());
+        }
+    }
+
+    pub fn file(&self, volume: &str, path: &str) -> Option<String> {
+        self.engine().volumes.get(volume)?.get(path).cloned()
+    }
+
+    /// Paths of all files of the volume.
+    pub fn files(&self, volume: &str) -> Vec<String> {
+        self.engine()
+            .volumes
+            .get(volume)
+            .map(|files| files.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn has_network(&self, name: &str) -> bool {
+        self.engine().networks.contains_key(name)
+    }
+
+    /// The spec the network was created with.
+    pub fn network_spec(&self, name: &str) -> Option<NetworkSpec> {
+        self.engine().networks.get(name).cloned()
+    }
+
+    pub fn labels(&self, kind: ResourceKind, name: &str) -> Option<Labels> {
+        self.engine().labels.get(&(kind, name.to_string())).cloned()
+    }
+
+    /// Labels an existing resource as if it was created by a scope.
+    pub fn set_labels(&self, kind: ResourceKind, name: &str, labels: Labels) {
+        self.engine().labels.insert((kind, name.to_string()), labels);
+    }
+
+    pub fn inject(&self, fault: Fault) {
+        self.engine().faults.push(fault);
+    }
+
+    /// Stops emitting events for a resource with the name.
+    pub fn mute_events(&self, name: &str) {
+        self.engine().muted.insert(name.to_string());
+    }
+
+    /// Simulates a process on the host that listens on the port.
+    pub fn occupy_port(&self, port: u16, owner: &str) {
+        self.engine().occupied.insert(port, owner.to_string());
+    }
+
+    pub fn release_port(&self, port: u16) {
+        self.engine().occupied.remove(&port);
+    }
+
+    /// Simulates an exit of the process in a container.
+    pub fn die(&self, name: &str, exit_code: i64) {
+        self.engine().terminate(name, &["die"], exit_code);
+    }
+
+    pub fn push_log(&self, name: &str, line: &str) {
+        if let Some(container) = self.engine().containers.get_mut(name) {
+            let line = LogLine {
+                timestamp: Some(Utc::now()),
+                message: line.to_string(),
+            };
+            container.history.push(line.clone());
+            container.logs.send(line).ok();
+        }
+    }
+
+    /// Closes the log streams of the container, the container keeps running.
+    pub fn disconnect_logs(&self, name: &str) {
+        if let Some(container) = self.engine().containers.get_mut(name) {
+            let (logs, _) = broadcast::channel(CHANNEL_SIZE);
+            container.logs = logs;
+        }
+    }
+
+    /// Sets the output and the exit code of a command executed in the container.
+    /// Unknown commands fail with the code `127`.
+    pub fn script_exec(&self, name: &str, command: &[&str], mut output: Vec<ExecOutput>, exit_code: i64) {
+        if let Some(container) = self.engine().containers.get_mut(name) {
+            let command = command.iter().map(|arg| arg.to_string()).collect();
+            output.push(ExecOutput::Exited(exit_code));
+            container.execs.insert(command, output);
+        }
+    }
+
+    /// Lines written to the terminal of the container by attached clients.
+    pub fn console_input(&self, name: &str) -> Vec<String> {
+        self.engine()
+            .containers
+            .get(name)
+            .map(|container| container.input.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn push_stats(&self, name: &str, stats: StatsData) {
+        if let Some(container) = self.engine().containers.get(name) {
+            container.stats.send(stats).ok();
+        }
+    }
+
+    /// Calls of the runtime in the order they were made, e.g. `start_container node`.
+    pub fn journal(&self) -> Vec<String> {
+        self.engine().journal.clone()
+    }
+}
+
+#[async_trait]
+impl ContainerRuntime for FakeRuntime {
+    fn events(&self, kind: ResourceKind, name: &str) -> RuntimeStream<RuntimeEvent> {
+        let name = name.to_string();
+        let rx = self.engine().events.subscribe();
+        BroadcastStream::new(rx)
+            .filter_map(move |res| {
+                let event = res.ok().filter(|event| event.kind == kind && event.name == name);
+                future::ready(event.map(Ok))
+            })
+            .boxed()
+    }
+
+    async fn image_exists(&self, image: &str) -> bool {
+        self.engine().images.contains(image)
+    }
+
+    async fn image_digest(&self, image: &str) -> Option<String> {
+        let engine = self.engine();
+        if engine.images.contains(image) {
+            engine.digests.get(image).cloned()
+        } else {
+            None
+        }
+    }
+
+    fn pull_image(&self, image: &str, auth: Option<RegistryAuth>) -> RuntimeStream<PullProgress> {
+        let mut engine = self.record("pull_image", image);
+        let fault = engine.take_fault(|fault| matches!(fault, Fault::PullImage { image: i, .. } if i == image));
+        let registry = auth::registry_host(image);
+        let rejected = engine
+            .logins
+            .get(registry)
+            .is_some_and(|required| Some(required) != auth.as_ref());
+        drop(engine);
+        if let Some(Fault::PullImage { reason, .. }) = fault {
+            return stream::once(future::ready(Err(anyhow!(reason)))).boxed();
+        }
+        if rejected {
+            let err = AuthError {
+                registry: registry.to_string(),
+                reason: "unauthorized: authentication required".to_string(),
+            };
+            return stream::once(future::ready(Err(err.into()))).boxed();
+        }
+        // Two layers of different sizes with interleaved messages, like Docker reports them
+        let progress = PULL_SCRIPT.iter().map(|(layer, status, current, total)| {
+            Ok(PullProgress {
+                layer: Some(layer.to_string()),
+                status: Some(status.to_string()),
+                current: (*total > 0).then_some(*current),
+                total: (*total > 0).then_some(*total),
+            })
+        });
+        let engine = self.engine.clone();
+        let image = image.to_string();
+        let done = stream::once(async move {
+            engine
+                .lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .images
+                .insert(image);
+            Ok(PullProgress {
+                status: Some("Download complete".to_string()),
+                ..Default::default()
+            })
+        });
+        stream::iter(progress.collect::<Vec<_>>())
+            .then(|item| async move {
+                tokio::time::sleep(PULL_STEP).await;
+                item
+            })
+            .chain(done)
+            .boxed()
+    }
+
+    async fn remove_image(&self, image: &str) -> Result<(), Error> {
+        let mut engine = self.record("remove_image", image);
+        if engine.images.remove(image) {
+            Ok(())
+        } else {
+            Err(anyhow!("No such image: {}", image))
+        }
+    }
+
+    async fn container_state(&self, name: &str) -> ContainerState {
+        self.engine()
+            .containers
+            .get(name)
+            .map(|container| container.state)
+            .unwrap_or(ContainerState::NotFound)
+    }
+
+    async fn create_container(&self, name: &str, spec: ContainerSpec, labels: &Labels) -> Result<(), Error> {
+        let mut engine = self.record("create_container", name);
+        let fault = engine.take_fault(|fault| matches!(fault, Fault::CreateContainer { name: n, .. } if n == name));
+        if let Some(Fault::CreateContainer { reason, .. }) = fault {
+            return Err(anyhow!(reason));
+        }
+        if engine.containers.contains_key(name) {
+            return Err(anyhow!("Conflict. The container name {} is already in use", name));
+        }
+        if !engine.images.contains(&spec.image) {
+            return Err(anyhow!("No such image: {}", spec.image));
+        }
+        let container = FakeContainer::new(spec, ContainerState::Created);
+        engine.containers.insert(name.to_string(), container);
+        engine
+            .labels
+            .insert((ResourceKind::Container, name.to_string()), labels.clone());
+        engine.emit(ResourceKind::Container, "create", name);
+        Ok(())
+    }
+
+    async fn start_container(&self, name: &str) -> Result<(), Error> {
+        let mut engine = self.record("start_container", name);
+        let fault = engine.take_fault(|fault| matches!(fault, Fault::StartContainer { name: n, .. } if n == name));
+        if let Some(Fault::StartContainer { reason, .. }) = fault {
+            return Err(anyhow!(reason));
+        }
+        let container = engine
+            .containers
+            .get_mut(name)
+            .ok_or_else(|| anyhow!("No such container: {}", name))?;
+        match container.state {
+            ContainerState::Created | ContainerState::Exited => {
+                container.state = ContainerState::Running;
+                engine.emit(ResourceKind::Container, "start", name);
+                Ok(())
+            },
+            ContainerState::Running => Ok(()),
+            state => Err(anyhow!("Can't start container {} in state {:?}", name, state)),
+        }
+    }
+
+    async fn stop_container(&self, name: &str, _grace: Duration) -> Result<(), Error> {
+        let mut engine = self.record("stop_container", name);
+        let fault = engine.take_fault(|fault| matches!(fault, Fault::StopContainer { name: n } if n == name));
+        if fault.is_some() {
+            return Ok(());
+        }
+        match engine.containers.get(name).map(|container| container.state) {
+            Some(ContainerState::Running) | Some(ContainerState::Restarting) => {
+                engine.terminate(name, &["kill", "die", "stop"], 143);
+                Ok(())
+            },
+            Some(_) => Ok(()),
+            None => Err(anyhow!("No such container: {}", name)),
+        }
+    }
+
+    async fn kill_container(&self, name: &str) -> Result<(), Error> {
+        let mut engine = self.record("kill_container", name);
+        match engine.containers.get(name).map(|container| container.state) {
+            Some(ContainerState::Running) | Some(ContainerState::Restarting) => {
+                engine.terminate(name, &["kill", "die"], 137);
+                Ok(())
+            },
+            Some(_) => Err(anyhow!("Container {} is not running", name)),
+            None => Err(anyhow!("No such container: {}", name)),
+        }
+    }
+
+    async fn unpause_container(&self, name: &str) -> Result<(), Error> {
+        let mut engine = self.record("unpause_container", name);
+        let container = engine
+            .containers
+            .get_mut(name)
+            .ok_or_else(|| anyhow!("No such container: {}", name))?;
+        if container.state == ContainerState::Paused {
+            container.state = ContainerState::Running;
+            engine.emit(ResourceKind::Container, "unpause", name);
+            Ok(())
+        } else {
+            Err(anyhow!("Container {} is not paused", name))
+        }
+    }
+
+    async fn remove_container(&self, name: &str) -> Result<(), Error> {
+        let mut engine = self.record("remove_container", name);
+        match engine.containers.get(name).map(|container| container.state) {
+            Some(ContainerState::Running) | Some(ContainerState::Restarting) | Some(ContainerState::Paused) => {
+                engine.terminate(name, &["kill", "die"], 137);
+                Ok(())
+            },
+            Some(_) => {
+                engine.terminate(name, &[], 0);
+                Ok(())
+            },
+            None => Err(anyhow!("No such container: {}", name)),
+        }
+    }
+
+    fn logs(&self, name: &str, since: Option<DateTime<Utc>>) -> RuntimeStream<LogLine> {
+        let engine = self.record("logs", name);
+        match engine.containers.get(name) {
+            Some(container) => {
+                // Compared in seconds as Docker does
+                let since = since.map(|since| since.timestamp()).unwrap_or_default();
+                let replay: Vec<_> = container
+                    .history
+                    .iter()
+                    .filter(|line| line.timestamp.map(|ts| ts.timestamp() >= since).unwrap_or(true))
+                    .cloned()
+                    .map(Ok)
+                    .collect();
+                let live =
+                    BroadcastStream::new(container.logs.subscribe()).filter_map(|res| future::ready(res.ok().map(Ok)));
+                stream::iter(replay).chain(live).boxed()
+            },
+            None => stream::once(future::ready(Err(anyhow!("No such container: {}", name)))).boxed(),
+        }
+    }
+
+    fn stats(&self, name: &str) -> RuntimeStream<StatsData> {
+        match self.engine().containers.get(name) {
+            Some(container) => BroadcastStream::new(container.stats.subscribe())
+                .filter_map(|res| future::ready(res.ok().map(Ok)))
+                .boxed(),
+            None => stream::once(future::ready(Err(anyhow!("No such container: {}", name)))).boxed(),
+        }
+    }
+
+    fn exec(&self, name: &str, command: Vec<String>) -> RuntimeStream<ExecOutput> {
+        let engine = self.record("exec", name);
+        let output = match engine.containers.get(name) {
+            Some(container) if container.state == ContainerState::Running => {
+                container.execs.get(&command).cloned().unwrap_or_else(|| {
+                    vec![
+                        ExecOutput::Stderr(format!("{}: command not found\n", command.join(" "))),
+                        ExecOutput::Exited(COMMAND_NOT_FOUND),
+                    ]
+                })
+            },
+            Some(_) => return stream::once(future::ready(Err(anyhow!("Container {} is not running", name)))).boxed(),
+            None => return stream::once(future::ready(Err(anyhow!("No such container: {}", name)))).boxed(),
+        };
+        stream::iter(output.into_iter().map(Ok)).boxed()
+    }
+
+    /// The terminal prints the lines of the container, like the logs do.
+    async fn attach(&self, name: &str) -> Result<Attachment, Error> {
+        let engine = self.record("attach", name);
+        let container = engine
+            .containers
+            .get(name)
+            .ok_or_else(|| anyhow!("No such container: {}", name))?;
+        if container.state != ContainerState::Running {
+            return Err(anyhow!("Container {} is not running", name));
+        }
+        let output = BroadcastStream::new(container.logs.subscribe())
+            .filter_map(|res| future::ready(res.ok().map(|line| Ok(format!("{}\r\n", line.message)))))
+            .boxed();
+        let (input, tty) = io::duplex(TTY_BUFFER);
+        let shared = self.engine.clone();
+        let name = name.to_string();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(tty).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let mut engine = shared.lock().unwrap_or_else(|err| err.into_inner());
+                if let Some(container) = engine.containers.get_mut(&name) {
+                    container.input.push(line);
+                }
+            }
+        });
+        Ok(Attachment {
+            output,
+            input: Box::pin(input),
+        })
+    }
+
+    async fn resize_tty(&self, name: &str, _width: u16, _height: u16) -> Result<(), Error> {
+        let engine = self.record("resize_tty", name);
+        if engine.containers.contains_key(name) {
+            Ok(())
+        } else {
+            Err(anyhow!("No such container: {}", name))
+        }
+    }
+
+    async fn volume_exists(&self, name: &str) -> bool {
+        self.engine().volumes.contains_key(name)
+    }
+
+    async fn create_volume(&self, name: &str, labels: &Labels) -> Result<(), Error> {
+        let mut engine = self.record("create_volume", name);
+        if !engine.volumes.contains_key(name) {
+            engine.volumes.insert(name.to_string(), BTreeMap::new());
+            engine
+                .labels
+                .insert((ResourceKind::Volume, name.to_string()), labels.clone());
+            engine.emit(ResourceKind::Volume, "create", name);
+        }
+        Ok(())
+    }
+
+    async fn remove_volume(&self, name: &str) -> Result<(), Error> {
+        let mut engine = self.record("remove_volume", name);
+        if engine.volumes.remove(name).is_some() {
+            engine.labels.remove(&(ResourceKind::Volume, name.to_string()));
+            engine.emit(ResourceKind::Volume, "destroy", name);
+            Ok(())
+        } else {
+            Err(anyhow!("No such volume: {}", name))
+        }
+    }
+
+    /// The archive is not a real tar, but a JSON map of the exported files.
+    fn export_volume(&self, name: &str, path: &str) -> RuntimeStream<Vec<u8>> {
+        let engine = self.record("export_volume", name);
+        let Some(files) = engine.volumes.get(name) else {
+            return stream::once(future::ready(Err(anyhow!("No such volume: {}", name)))).boxed();
+        };
+        let prefix = format!("{}/", path);
+        let exported: BTreeMap<_, _> = files
+            .iter()
+            .filter(|(file, _)| path.is_empty() || file.starts_with(&prefix))
+            .collect();
+        let archive = serde_json::to_vec(&exported).map_err(Error::from);
+        stream::once(future::ready(archive)).boxed()
+    }
+
+    async fn import_volume(&self, name: &str, archive: RuntimeStream<Vec<u8>>) -> Result<(), Error> {
+        let chunks: Vec<Vec<u8>> = archive.try_collect().await?;
+        let files: BTreeMap<String, String> = serde_json::from_slice(&chunks.concat())?;
+        let mut engine = self.record("import_volume", name);
+        let volume = engine
+            .volumes
+            .get_mut(name)
+            .ok_or_else(|| anyhow!("No such volume: {}", name))?;
+        volume.extend(files);
+        Ok(())
+    }
+
+    async fn clear_volume(&self, name: &str, path: &str) -> Result<(), Error> {
+        let mut engine = self.record("clear_volume", name);
+        let files = engine
+            .volumes
+            .get_mut(name)
+            .ok_or_else(|| anyhow!("No such volume: {}", name))?;
+        let prefix = format!("{}/", path);
+        files.retain(|file, _| !file.starts_with(&prefix));
+        Ok(())
+    }
+
+    /// Every file takes as many bytes as the length of its content.
+    async fn volume_size(&self, name: &str) -> Option<u64> {
+        let delay = self.engine().usage_delay;
+        tokio::time::sleep(delay).await;
+        let engine = self.engine();
+        let files = engine.volumes.get(name)?;
+        Some(files.values().map(|content| content.len() as u64).sum())
+    }
+
+    async fn volume_users(&self, name: &str) -> Result<Vec<String>, Error> {
+        let engine = self.engine();
+        let mut users: Vec<_> = engine
+            .containers
+            .iter()
+            .filter(|(_, container)| {
+                container
+                    .spec
+                    .mounts
+                    .iter()
+                    .any(|mount| matches!(mount, MountSpec::Volume { source, .. } if source == name))
+            })
+            .map(|(user, _)| user.clone())
+            .collect();
+        users.sort();
+        Ok(users)
+    }
+
+    async fn network_exists(&self, name: &str) -> bool {
+        self.engine().networks.contains_key(name)
+    }
+
+    async fn create_network(&self, name: &str, spec: &NetworkSpec, labels: &Labels) -> Result<(), Error> {
+        let mut engine = self.record("create_network", name);
+        if engine.networks.contains_key(name) {
+            return Err(anyhow!("Network with name {} already exists", name));
+        }
+        engine.networks.insert(name.to_string(), spec.clone());
+        engine
+            .labels
+            .insert((ResourceKind::Network, name.to_string()), labels.clone());
+        engine.emit(ResourceKind::Network, "create", name);
+        Ok(())
+    }
+
+    async fn remove_network(&self, name: &str) -> Result<(), Error> {
+        let mut engine = self.record("remove_network", name);
+        if engine.networks.remove(name).is_some() {
+            engine.labels.remove(&(ResourceKind::Network, name.to_string()));
+            engine.emit(ResourceKind::Network, "destroy", name);
+            Ok(())
+        } else {
+            Err(anyhow!("No such network: {}", name))
+        }
+    }
+
+    async fn scope_resources(&self, kind: ResourceKind, scope: &str) -> Result<Vec<LabelledResource>, Error> {
+        let engine = self.engine();
+        let mut resources: Vec<_> = engine
+            .labels
+            .iter()
+            .filter(|((resource_kind, _), labels)| *resource_kind == kind && labels.scope == scope)
+            .map(|((kind, name), labels)| LabelledResource {
+                kind: *kind,
+                name: name.clone(),
+                labels: labels.clone(),
+            })
+            .collect();
+        resources.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(resources)
+    }
+
+    async fn check_port(&self, port: &PortMapping) -> Result<(), PortConflict> {
+        match self.engine().occupied.get(&port.host_port) {
+            Some(owner) => Err(PortConflict {
+                port: port.clone(),
+                owner: Some(owner.clone()),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    async fn free_port(&self, _port: &PortMapping) -> Option<u16> {
+        let engine = self.engine();
+        (DYNAMIC_PORTS..=u16::MAX).find(|port| !engine.occupied.contains_key(port))
+    }
+}
@@ -0,0 +1,535 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Container runtimes used by SDM tasks.
+//!
+//! Tasks never talk to a container engine directly. Every call goes through the [`ContainerRuntime`]
+//! trait, so the same state machines can drive Docker, Podman's Docker-compatible socket,
+//! a remote engine, or a test double.
+
+mod auth;
+mod docker;
+#[cfg(any(test, feature = "fake"))]
+pub mod fake;
+mod ports;
+
+use std::{
+    collections::HashMap,
+    fmt,
+    net::{IpAddr, Ipv4Addr},
+    pin::Pin,
+    time::Duration,
+};
+
+use anyhow::{anyhow, Error};
+use async_trait::async_trait;
+pub use auth::{from_docker_config, is_auth_failure, registry_host, AuthError, RegistryAuth, DEFAULT_REGISTRY_HOST};
+use chrono::{DateTime, Utc};
+pub use docker::DockerRuntime;
+use futures::stream::BoxStream;
+pub use ports::PortConflict;
+use serde::Serialize;
+use tari_launchpad_protocol::container::{ExecOutput, StatsData};
+use tokio::io::AsyncWrite;
+
+pub type RuntimeStream<T> = BoxStream<'static, Result<T, Error>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ResourceKind {
+    Container,
+    Volume,
+    Network,
+}
+
+impl ResourceKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Container => "container",
+            Self::Volume => "volume",
+            Self::Network => "network",
+        }
+    }
+}
+
+/// A lifecycle event of a resource reported by a runtime.
+#[derive(Debug, Clone)]
+pub struct RuntimeEvent {
+    pub kind: ResourceKind,
+    /// Docker compatible action name: `create`, `start`, `kill`, `die`, `destroy`...
+    pub action: String,
+    pub name: String,
+    /// The exit code of a container for `die` events.
+    pub exit_code: Option<i64>,
+}
+
+/// A single progress message of pulling an image.
+#[derive(Debug, Clone, Default)]
+pub struct PullProgress {
+    /// An id of a layer if the message relates to a layer.
+    pub layer: Option<String>,
+    pub status: Option<String>,
+    pub current: Option<i64>,
+    pub total: Option<i64>,
+}
+
+/// A line of the output of a container.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogLine {
+    /// The time the engine received the line, if the engine reports it.
+    pub timestamp: Option<DateTime<Utc>>,
+    pub message: String,
+}
+
+/// A connection to the terminal of a running container. Dropping it detaches from the terminal,
+/// but the container keeps running.
+pub struct Attachment {
+    /// The raw output of the terminal, including control sequences.
+    pub output: RuntimeStream<String>,
+    pub input: Pin<Box<dyn AsyncWrite + Send>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum MountSpec {
+    Volume { source: String, target: String },
+    Bind { source: String, target: String },
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub enum PortProtocol {
+    #[default]
+    Tcp,
+    Udp,
+}
+
+impl fmt::Display for PortProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Tcp => write!(f, "tcp"),
+            Self::Udp => write!(f, "udp"),
+        }
+    }
+}
+
+/// A port of a container published on the host.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PortMapping {
+    pub host_ip: IpAddr,
+    pub host_port: u16,
+    pub container_port: u16,
+    pub protocol: PortProtocol,
+    /// The host port could be replaced with a free one if it's busy.
+    pub relocatable: bool,
+}
+
+impl PortMapping {
+    /// Creates a TCP mapping available on all interfaces.
+    pub fn new(host_port: u16, container_port: u16) -> Self {
+        Self {
+            host_ip: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            host_port,
+            container_port,
+            protocol: PortProtocol::Tcp,
+            relocatable: false,
+        }
+    }
+
+    pub fn bind(&mut self, host_ip: IpAddr) -> &mut Self {
+        self.host_ip = host_ip;
+        self
+    }
+
+    /// Makes the port available on the loopback interface only.
+    pub fn localhost(&mut self) -> &mut Self {
+        self.bind(IpAddr::V4(Ipv4Addr::LOCALHOST))
+    }
+
+    pub fn udp(&mut self) -> &mut Self {
+        self.protocol = PortProtocol::Udp;
+        self
+    }
+
+    /// Allows publishing the port on any free host port if the requested one is taken.
+    /// Use it for ports that are used by the launchpad only, since the actual port
+    /// is known at runtime.
+    pub fn relocatable(&mut self) -> &mut Self {
+        self.relocatable = true;
+        self
+    }
+}
+
+/// Resource constraints of a container. Unset values are not limited.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Resources {
+    /// CPU quota in billionths of a CPU.
+    pub nano_cpus: Option<i64>,
+    /// CPUs the container is pinned to, e.g. `0-3` or `0,2`.
+    pub cpuset_cpus: Option<String>,
+    /// The memory limit in bytes.
+    pub memory: Option<i64>,
+    /// The soft memory limit in bytes.
+    pub memory_reservation: Option<i64>,
+    /// The maximal number of processes, `-1` for unlimited.
+    pub pids_limit: Option<i64>,
+}
+
+impl Resources {
+    const MIB: i64 = 1024 * 1024;
+    const NANOS: f64 = 1_000_000_000.0;
+
+    /// Limits the container to the number of CPUs, fractions are allowed.
+    pub fn cpus(&mut self, cpus: f64) -> &mut Self {
+        self.nano_cpus = Some((cpus * Self::NANOS) as i64);
+        self
+    }
+
+    pub fn cpuset(&mut self, cpus: impl ToString) -> &mut Self {
+        self.cpuset_cpus = Some(cpus.to_string());
+        self
+    }
+
+    pub fn memory_mib(&mut self, mib: u64) -> &mut Self {
+        self.memory = Some(mib as i64 * Self::MIB);
+        self
+    }
+
+    pub fn memory_reservation_mib(&mut self, mib: u64) -> &mut Self {
+        self.memory_reservation = Some(mib as i64 * Self::MIB);
+        self
+    }
+
+    pub fn pids(&mut self, limit: i64) -> &mut Self {
+        self.pids_limit = Some(limit);
+        self
+    }
+}
+
+/// Everything required to create a container.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ContainerSpec {
+    pub image: String,
+    pub args: Vec<String>,
+    pub envs: Vec<String>,
+    pub ports: Vec<PortMapping>,
+    pub volumes: Vec<String>,
+    pub mounts: Vec<MountSpec>,
+    /// Pairs of a network name and an alias of the container in that network.
+    pub networks: Vec<(String, String)>,
+    pub resources: Resources,
+}
+
+/// An address range of a network with an optional gateway in it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Subnet {
+    /// The range in CIDR notation, e.g. `172.30.0.0/16` or `fd00:1::/64`.
+    pub cidr: String,
+    pub gateway: Option<String>,
+}
+
+impl Subnet {
+    pub fn gateway(&mut self, gateway: impl ToString) -> &mut Self {
+        self.gateway = Some(gateway.to_string());
+        self
+    }
+
+    fn validate(&self, ipv6: bool) -> Result<(), Error> {
+        let (address, prefix) = parse_cidr(&self.cidr)?;
+        if address.is_ipv6() && !ipv6 {
+            return Err(anyhow!("The subnet {} requires IPv6 to be enabled", self.cidr));
+        }
+        if let Some(gateway) = &self.gateway {
+            let gateway: IpAddr = gateway
+                .parse()
+                .map_err(|_| anyhow!("The gateway {} is not an IP address", gateway))?;
+            if !contains(address, prefix, gateway) {
+                return Err(anyhow!(
+                    "The gateway {} is outside of the subnet {}",
+                    gateway,
+                    self.cidr
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Everything required to create a network. The default is a bridge network
+/// with a subnet picked by the engine.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NetworkSpec {
+    pub driver: String,
+    /// Subnets of the network, the engine assigns one if the list is empty.
+    pub subnets: Vec<Subnet>,
+    pub ipv6: bool,
+    /// Restricts external access to the network.
+    pub internal: bool,
+}
+
+impl Default for NetworkSpec {
+    fn default() -> Self {
+        Self {
+            driver: "bridge".to_string(),
+            subnets: Vec::new(),
+            ipv6: false,
+            internal: false,
+        }
+    }
+}
+
+impl NetworkSpec {
+    pub fn driver(&mut self, driver: impl ToString) -> &mut Self {
+        self.driver = driver.to_string();
+        self
+    }
+
+    pub fn subnet(&mut self, cidr: impl ToString) -> &mut Subnet {
+        self.subnets.push(Subnet {
+            cidr: cidr.to_string(),
+            gateway: None,
+        });
+        self.subnets.last_mut().unwrap()
+    }
+
+    pub fn ipv6(&mut self) -> &mut Self {
+        self.ipv6 = true;
+        self
+    }
+
+    pub fn internal(&mut self) -> &mut Self {
+        self.internal = true;
+        self
+    }
+
+    /// Checks the spec before it's passed to an engine, since engines report
+    /// invalid addresses with obscure errors.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.driver.trim().is_empty() {
+            return Err(anyhow!("The network driver is not set"));
+        }
+        for subnet in &self.subnets {
+            subnet.validate(self.ipv6)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses an address range like `10.0.0.0/8` into the address and the prefix length.
+fn parse_cidr(cidr: &str) -> Result<(IpAddr, u8), Error> {
+    let invalid = || anyhow!("The subnet {} is not in the CIDR notation", cidr);
+    let (address, prefix) = cidr.split_once('/').ok_or_else(invalid)?;
+    let address: IpAddr = address.parse().map_err(|_| invalid())?;
+    let prefix: u8 = prefix.parse().map_err(|_| invalid())?;
+    let max = if address.is_ipv4() { 32 } else { 128 };
+    if prefix > max {
+        return Err(invalid());
+    }
+    Ok((address, prefix))
+}
+
+fn contains(network: IpAddr, prefix: u8, address: IpAddr) -> bool {
+    let (network, address, bits) = match (network, address) {
+        (IpAddr::V4(network), IpAddr::V4(address)) => (u32::from(network).into(), u32::from(address).into(), 32),
+        (IpAddr::V6(network), IpAddr::V6(address)) => (u128::from(network), u128::from(address), 128),
+        _ => return false,
+    };
+    let mask = u128::MAX.checked_shl(bits - u32::from(prefix)).unwrap_or(0);
+    network & mask == address & mask
+}
+
+/// Ownership labels of a resource created by a scope. They allow finding the resources
+/// of a scope that are left by crashed or previous versions of the launchpad.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Labels {
+    pub scope: String,
+    pub task_id: String,
+    pub version: String,
+    /// A hash of the spec the resource was created with.
+    pub config_hash: String,
+}
+
+impl Labels {
+    pub const CONFIG_HASH: &'static str = "org.tari.launchpad.config-hash";
+    pub const SCOPE: &'static str = "org.tari.launchpad.scope";
+    pub const TASK_ID: &'static str = "org.tari.launchpad.task-id";
+    pub const VERSION: &'static str = "org.tari.launchpad.version";
+
+    pub fn new(scope: impl ToString, task_id: impl ToString, version: impl ToString) -> Self {
+        Self {
+            scope: scope.to_string(),
+            task_id: task_id.to_string(),
+            version: version.to_string(),
+            config_hash: String::new(),
+        }
+    }
+
+    /// The labels of a resource created with the config. The hash is the FNV-1a of the JSON of the config,
+    /// so it's the same for every build of the launchpad.
+    pub fn with_config(&self, config: &impl Serialize) -> Self {
+        let data = serde_json::to_vec(config).unwrap_or_default();
+        Self {
+            config_hash: format!("{:016x}", fnv1a(&data)),
+            ..self.clone()
+        }
+    }
+
+    pub fn to_map(&self) -> HashMap<String, String> {
+        [
+            (Self::SCOPE, &self.scope),
+            (Self::TASK_ID, &self.task_id),
+            (Self::VERSION, &self.version),
+            (Self::CONFIG_HASH, &self.config_hash),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value.clone()))
+        .collect()
+    }
+
+    /// Reads the labels of a resource. Returns `None` if the resource is not created by a scope.
+    pub fn from_map(labels: &HashMap<String, String>) -> Option<Self> {
+        let value = |key: &str| labels.get(key).cloned().unwrap_or_default();
+        Some(Self {
+            scope: labels.get(Self::SCOPE)?.clone(),
+            task_id: labels.get(Self::TASK_ID)?.clone(),
+            version: value(Self::VERSION),
+            config_hash: value(Self::CONFIG_HASH),
+        })
+    }
+}
+
+fn fnv1a(data: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    data.iter()
+        .fold(OFFSET_BASIS, |hash, byte| (hash ^ u64::from(*byte)).wrapping_mul(PRIME))
+}
+
+/// A resource that has the labels of a scope.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabelledResource {
+    pub kind: ResourceKind,
+    pub name: String,
+    pub labels: Labels,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContainerState {
+    Empty,
+    Created,
+    Running,
+    Paused,
+    Restarting,
+    Removing,
+    Exited,
+    Dead,
+    ErrorStatusNotDefined,
+    ErrorStateNotDefined,
+    NotFound,
+}
+
+/// Operations of a container engine used by SDM tasks.
+#[async_trait]
+pub trait ContainerRuntime: Send + Sync + 'static {
+    /// Subscribes to lifecycle events of a resource with the given name.
+    fn events(&self, kind: ResourceKind, name: &str) -> RuntimeStream<RuntimeEvent>;
+
+    async fn image_exists(&self, image: &str) -> bool;
+
+    /// The content digest of the local image, like `sha256:...`.
+    async fn image_digest(&self, image: &str) -> Option<String>;
+
+    /// Pulls the image. A failed authentication is reported as [`AuthError`].
+    fn pull_image(&self, image: &str, auth: Option<RegistryAuth>) -> RuntimeStream<PullProgress>;
+
+    async fn remove_image(&self, image: &str) -> Result<(), Error>;
+
+    async fn container_state(&self, name: &str) -> ContainerState;
+
+    async fn create_container(&self, name: &str, spec: ContainerSpec, labels: &Labels) -> Result<(), Error>;
+
+    async fn start_container(&self, name: &str) -> Result<(), Error>;
+
+    /// Asks the container to stop. The engine could kill it after the `grace` period.
+    async fn stop_container(&self, name: &str, grace: Duration) -> Result<(), Error>;
+
+    async fn kill_container(&self, name: &str) -> Result<(), Error>;
+
+    async fn unpause_container(&self, name: &str) -> Result<(), Error>;
+
+    async fn remove_container(&self, name: &str) -> Result<(), Error>;
+
+    /// Follows the output of a container line by line. If `since` is set, the lines received before it
+    /// are skipped. Engines could compare it with seconds precision, so the lines of the same second could be
+    /// repeated.
+    fn logs(&self, name: &str, since: Option<DateTime<Utc>>) -> RuntimeStream<LogLine>;
+
+    fn stats(&self, name: &str) -> RuntimeStream<StatsData>;
+
+    /// Runs a command inside of a running container. The output is streamed as it's produced
+    /// and the stream ends with [`ExecOutput::Exited`].
+    fn exec(&self, name: &str, command: Vec<String>) -> RuntimeStream<ExecOutput>;
+
+    /// Attaches to the terminal of a running container.
+    async fn attach(&self, name: &str) -> Result<Attachment, Error>;
+
+    async fn resize_tty(&self, name: &str, width: u16, height: u16) -> Result<(), Error>;
+
+    async fn volume_exists(&self, name: &str) -> bool;
+
+    async fn create_volume(&self, name: &str, labels: &Labels) -> Result<(), Error>;
+
+    async fn remove_volume(&self, name: &str) -> Result<(), Error>;
+
+    /// Reads a directory of the volume as a tar archive. The paths in the archive are relative
+    /// to the root of the volume and an empty `path` exports the whole volume.
+    fn export_volume(&self, name: &str, path: &str) -> RuntimeStream<Vec<u8>>;
+
+    /// Extracts an archive produced by [`ContainerRuntime::export_volume`] into the volume.
+    async fn import_volume(&self, name: &str, archive: RuntimeStream<Vec<u8>>) -> Result<(), Error>;
+
+    /// Removes a directory of the volume with everything in it.
+    async fn clear_volume(&self, name: &str, path: &str) -> Result<(), Error>;
+
+    /// The disk space used by the volume in bytes, if the engine knows it.
+    async fn volume_size(&self, name: &str) -> Option<u64>;
+
+    /// Names of the containers that mount the volume, including stopped ones.
+    async fn volume_users(&self, name: &str) -> Result<Vec<String>, Error>;
+
+    async fn network_exists(&self, name: &str) -> bool;
+
+    async fn create_network(&self, name: &str, spec: &NetworkSpec, labels: &Labels) -> Result<(), Error>;
+
+    /// Resources of the kind labelled with the scope, including stopped containers.
+    async fn scope_resources(&self, kind: ResourceKind, scope: &str) -> Result<Vec<LabelledResource>, Error>;
+
+    async fn remove_network(&self, name: &str) -> Result<(), Error>;
+
+    /// Checks that the host port of the mapping is not used by another process.
+    async fn check_port(&self, port: &PortMapping) -> Result<(), PortConflict> {
+        ports::check_port(port)
+    }
+
+    /// Finds a free host port to publish the mapping on instead.
+    async fn free_port(&self, port: &PortMapping) -> Option<u16> {
+        ports::free_port(port)
+    }
+}
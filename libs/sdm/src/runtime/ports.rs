@@ -0,0 +1,135 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+//! Checks of host ports before they are published.
+//!
+//! A container engine reports a busy port only when a container starts, and the error doesn't say who holds
+//! the port. The helpers here try to bind the port on the host first and look up the owner of the socket.
+
+use std::{
+    fmt,
+    io::ErrorKind,
+    net::{SocketAddr, TcpListener, UdpSocket},
+};
+
+use super::{PortMapping, PortProtocol};
+
+/// A host port that can't be published, because another process listens on it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortConflict {
+    pub port: PortMapping,
+    /// A name and a pid of the process that holds the port, if it's known.
+    pub owner: Option<String>,
+}
+
+impl fmt::Display for PortConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Port {}/{} on {} is already in use",
+            self.port.host_port, self.port.protocol, self.port.host_ip
+        )?;
+        match &self.owner {
+            Some(owner) => write!(f, " by {}", owner),
+            None => write!(f, " by another process"),
+        }
+    }
+}
+
+impl std::error::Error for PortConflict {}
+
+/// Tries to bind the host port of the mapping.
+pub fn check_port(port: &PortMapping) -> Result<(), PortConflict> {
+    match bind(port, port.host_port) {
+        Ok(_) => Ok(()),
+        Err(err) if err.kind() == ErrorKind::AddrInUse => Err(PortConflict {
+            port: port.clone(),
+            owner: find_owner(port),
+        }),
+        Err(err) => {
+            // Other errors (privileged ports, for example) are not conflicts,
+            // since the engine could be allowed to publish the port.
+            log::debug!("Can't check the port {:?}: {}", port, err);
+            Ok(())
+        },
+    }
+}
+
+/// Asks the OS for a free port on the same address and with the same protocol.
+pub fn free_port(port: &PortMapping) -> Option<u16> {
+    bind(port, 0).ok()
+}
+
+/// Binds the socket, returns the bound port. The socket is closed immediately.
+fn bind(port: &PortMapping, host_port: u16) -> std::io::Result<u16> {
+    let addr = SocketAddr::new(port.host_ip, host_port);
+    let local_addr = match port.protocol {
+        PortProtocol::Tcp => TcpListener::bind(addr)?.local_addr()?,
+        PortProtocol::Udp => UdpSocket::bind(addr)?.local_addr()?,
+    };
+    Ok(local_addr.port())
+}
+
+#[cfg(target_os = "linux")]
+fn find_owner(port: &PortMapping) -> Option<String> {
+    use std::fs;
+
+    // Listening TCP sockets have the `0A` state, bound UDP sockets have `07`.
+    let (tables, state) = match port.protocol {
+        PortProtocol::Tcp => (["/proc/net/tcp", "/proc/net/tcp6"], "0A"),
+        PortProtocol::Udp => (["/proc/net/udp", "/proc/net/udp6"], "07"),
+    };
+    let inode = tables.iter().find_map(|table| {
+        let content = fs::read_to_string(table).ok()?;
+        content.lines().skip(1).find_map(|line| {
+            let fields: Vec<_> = line.split_whitespace().collect();
+            let local_port = fields.get(1)?.rsplit(':').next()?;
+            let local_port = u16::from_str_radix(local_port, 16).ok()?;
+            if local_port == port.host_port && *fields.get(3)? == state {
+                fields.get(9).map(|inode| inode.to_string())
+            } else {
+                None
+            }
+        })
+    })?;
+    let socket = format!("socket:[{}]", inode);
+    // Only processes of the same user are visible, that's the best effort.
+    fs::read_dir("/proc").ok()?.flatten().find_map(|entry| {
+        let pid: u32 = entry.file_name().to_str()?.parse().ok()?;
+        let holds_socket = fs::read_dir(entry.path().join("fd"))
+            .ok()?
+            .flatten()
+            .any(|fd| fs::read_link(fd.path()).is_ok_and(|link| link.as_os_str() == socket.as_str()));
+        if holds_socket {
+            let name = fs::read_to_string(entry.path().join("comm")).ok()?;
+            Some(format!("{} (pid {})", name.trim(), pid))
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn find_owner(_port: &PortMapping) -> Option<String> {
+    None
+}
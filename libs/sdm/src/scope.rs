@@ -21,19 +21,27 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    path::PathBuf,
+    sync::Arc,
+};
 
 use anyhow::{anyhow, Error};
-use bollard::Docker;
-use tari_launchpad_protocol::container::{TaskDelta, TaskId, TaskState};
+use tari_launchpad_protocol::container::{ExecOutput, TaskDelta, TaskId, TaskState};
 use tokio::sync::{broadcast, mpsc};
 
 use crate::{
     config::ManagedProtocol,
-    image::{ImageTask, ManagedContainer},
+    graph::{DependencyGraph, GraphError, TaskNode},
+    image::{ImageTask, ManagedContainer, Mount, Mounts, Networks},
     network::{ManagedNetwork, NetworkTask},
-    task::{ManagedTask, SdmTaskRunner},
-    volume::{ManagedVolume, VolumeTask},
+    runtime::{
+        Attachment, ContainerRuntime, DockerRuntime, LabelledResource, Labels, PortMapping, ResourceKind, RuntimeStream,
+    },
+    task::{ManagedTask, RunnableTask, SdmTaskRunner},
+    volume::{read_snapshot, write_snapshot, ManagedVolume, VolumeTask},
 };
 
 #[derive(Debug)]
@@ -46,15 +54,37 @@ pub struct ReportEnvelope<C: ManagedProtocol> {
 pub enum Report<C: ManagedProtocol> {
     State(TaskState),
     Delta(TaskDelta),
+    /// Host ports actually published by a container.
+    Ports(Vec<PortMapping>),
     Extras(C::Outer),
 }
 
+/// The result of the reconciliation of a scope.
+#[derive(Debug, Default)]
+pub struct Reconciliation {
+    /// Names of the removed containers and networks
+    pub removed: Vec<String>,
+    /// Names of the volumes that don't belong to the tasks of the scope. They are kept.
+    pub orphaned_volumes: Vec<String>,
+}
+
 pub struct SdmScope<C: ManagedProtocol> {
     scope: String,
-    docker: Docker,
+    runtime: Arc<dyn ContainerRuntime>,
     reporter: mpsc::UnboundedReceiver<ReportEnvelope<C>>,
     report_sender: mpsc::UnboundedSender<ReportEnvelope<C>>,
     sender: broadcast::Sender<ControlEvent<C>>,
+    graph: DependencyGraph,
+    /// Names of the containers of image tasks
+    containers: HashMap<TaskId, String>,
+    /// Names of the volumes of volume tasks
+    volumes: HashMap<TaskId, String>,
+    /// Names of the networks of network tasks
+    networks: HashMap<TaskId, String>,
+    /// The version of the application the resources are labelled with
+    version: String,
+    /// Added to the host ports of the containers
+    port_offset: u16,
 }
 
 // TODO: Move to the `task` mod?
@@ -65,56 +95,129 @@ pub enum ControlEvent<C: ManagedProtocol> {
         task_id: TaskId,
         /// id or name in the docker
         name: String,
+        /// Host ports published by the resource
+        ports: Vec<PortMapping>,
     },
     ResourceClosed {
         task_id: TaskId,
     },
     InnerEvent(C::Inner),
+    /// Stops the tasks for good. Every task sends its id to `ack` when it's down.
+    Stop {
+        tasks: Arc<HashSet<TaskId>>,
+        ack: mpsc::UnboundedSender<TaskId>,
+    },
+    /// Keeps the tasks stopped until they're released. Every task sends its id to `ack` when it's down.
+    Hold {
+        tasks: Arc<HashSet<TaskId>>,
+        ack: mpsc::UnboundedSender<TaskId>,
+    },
+    Release {
+        tasks: Arc<HashSet<TaskId>>,
+    },
 }
 
 impl<C: ManagedProtocol> Clone for ControlEvent<C> {
     fn clone(&self) -> Self {
         match self {
             Self::SetConfig(config) => Self::SetConfig(config.clone()),
-            Self::ResourceReady { task_id, name } => Self::ResourceReady {
+            Self::ResourceReady { task_id, name, ports } => Self::ResourceReady {
                 task_id: task_id.clone(),
                 name: name.clone(),
+                ports: ports.clone(),
             },
             Self::ResourceClosed { task_id } => Self::ResourceClosed {
                 task_id: task_id.clone(),
             },
             Self::InnerEvent(inner) => Self::InnerEvent(inner.clone()),
+            Self::Stop { tasks, ack } => Self::Stop {
+                tasks: tasks.clone(),
+                ack: ack.clone(),
+            },
+            Self::Hold { tasks, ack } => Self::Hold {
+                tasks: tasks.clone(),
+                ack: ack.clone(),
+            },
+            Self::Release { tasks } => Self::Release { tasks: tasks.clone() },
         }
     }
 }
 
 impl<C: ManagedProtocol> SdmScope<C> {
+    /// Creates a scope that manages containers of the local Docker engine.
     pub fn connect(scope: &str) -> Result<Self, Error> {
-        let docker = Docker::connect_with_local_defaults()?;
+        let runtime = DockerRuntime::connect()?;
+        Ok(Self::with_runtime(scope, Arc::new(runtime)))
+    }
+
+    pub fn with_runtime(scope: &str, runtime: Arc<dyn ContainerRuntime>) -> Self {
         // TODO: Use `rx` later to control entries
         let (req_tx, _req_rx) = broadcast::channel(16);
         let (rep_tx, rep_rx) = mpsc::unbounded_channel();
-        Ok(Self {
+        Self {
             scope: scope.to_string(),
-            docker,
+            runtime,
             reporter: rep_rx,
             report_sender: rep_tx,
             sender: req_tx,
-        })
+            graph: DependencyGraph::default(),
+            containers: HashMap::new(),
+            volumes: HashMap::new(),
+            networks: HashMap::new(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            port_offset: 0,
+        }
+    }
+
+    /// The name of the scope, it prefixes the names of all the resources.
+    pub fn name(&self) -> &str {
+        &self.scope
+    }
+
+    /// Shifts the host ports of the containers added after the call, so scopes
+    /// could run side by side without conflicts.
+    pub fn set_port_offset(&mut self, offset: u16) {
+        self.port_offset = offset;
+    }
+
+    /// Sets the version of the application that is written to the labels of the created resources.
+    /// It's applied to the tasks added after the call.
+    pub fn set_version(&mut self, version: impl ToString) {
+        self.version = version.to_string();
+    }
+
+    fn labels(&self, task_id: &TaskId) -> Labels {
+        Labels::new(&self.scope, task_id, &self.version)
     }
 
     pub fn add_image<I>(&mut self, entry: I) -> Result<(), Error>
     where
         I: ManagedContainer<Protocol = C> + ManagedTask,
     {
+        let mut node = TaskNode::new(I::id(), ResourceKind::Container, I::deps());
+        let mut networks = Networks::default();
+        entry.networks(&mut networks);
+        for (_, network) in networks.build() {
+            node.resources.push((ResourceKind::Network, network));
+        }
+        let mut mounts = Mounts::default();
+        entry.mounts(&mut mounts);
+        for mount in mounts.build() {
+            if let Mount::ToVolume { volume, .. } = mount {
+                node.resources.push((ResourceKind::Volume, volume));
+            }
+        }
+        self.graph.add(node)?;
         // TODO: DRY!
         let entry = Box::new(entry);
-        let inner = ImageTask::new(&self.scope, entry);
+        let inner = ImageTask::new(&self.scope, self.port_offset, entry);
+        self.containers.insert(I::id(), inner.name().to_string());
         let runner = SdmTaskRunner::new::<I>(
             self.sender.clone(),
             self.report_sender.clone(),
             inner,
-            self.docker.clone(),
+            self.runtime.clone(),
+            self.labels(&I::id()),
         );
         tokio::spawn(runner.entrypoint());
         Ok(())
@@ -124,14 +227,18 @@ impl<C: ManagedProtocol> SdmScope<C> {
     where
         N: ManagedNetwork<Protocol = C> + ManagedTask,
     {
+        let node = TaskNode::new(N::id(), ResourceKind::Network, N::deps());
+        self.graph.add(node)?;
         // TODO: DRY!
         let entry = Box::new(entry);
         let inner = NetworkTask::new(&self.scope, entry);
+        self.networks.insert(N::id(), inner.name().to_string());
         let runner = SdmTaskRunner::new::<N>(
             self.sender.clone(),
             self.report_sender.clone(),
             inner,
-            self.docker.clone(),
+            self.runtime.clone(),
+            self.labels(&N::id()),
         );
         tokio::spawn(runner.entrypoint());
         Ok(())
@@ -141,14 +248,18 @@ impl<C: ManagedProtocol> SdmScope<C> {
     where
         V: ManagedVolume<Protocol = C> + ManagedTask,
     {
+        let node = TaskNode::new(V::id(), ResourceKind::Volume, V::deps());
+        self.graph.add(node)?;
         // TODO: DRY!
         let entry = Box::new(entry);
         let inner = VolumeTask::new(&self.scope, entry);
+        self.volumes.insert(V::id(), inner.name().to_string());
         let runner = SdmTaskRunner::new::<V>(
             self.sender.clone(),
             self.report_sender.clone(),
             inner,
-            self.docker.clone(),
+            self.runtime.clone(),
+            self.labels(&V::id()),
         );
         tokio::spawn(runner.entrypoint());
         Ok(())
@@ -171,5 +282,309 @@ impl<C: ManagedProtocol> SdmScope<C> {
         self.reporter.recv().await
     }
 
-    pub fn stop(&self) {}
+    /// Checks that the dependencies of all the added tasks are known.
+    /// Should be called when all the tasks are added.
+    pub fn validate(&self) -> Result<(), GraphError> {
+        self.graph.validate()
+    }
+
+    /// The dependency graph of the tasks to inspect or export it.
+    pub fn graph(&self) -> &DependencyGraph {
+        &self.graph
+    }
+
+    /// Runs a command inside of the container of an image task.
+    /// The container has to be running, the stream ends with [`ExecOutput::Exited`].
+    pub fn exec(&self, task_id: &TaskId, command: Vec<String>) -> Result<RuntimeStream<ExecOutput>, Error> {
+        let name = self.container(task_id)?;
+        if command.is_empty() {
+            return Err(anyhow!("No command to execute in {}", name));
+        }
+        Ok(self.runtime.exec(name, command))
+    }
+
+    /// Attaches to the terminal of the running container of an image task and resizes it.
+    pub async fn attach(&self, task_id: &TaskId, width: u16, height: u16) -> Result<Attachment, Error> {
+        let name = self.container(task_id)?;
+        let attachment = self.runtime.attach(name).await?;
+        // The output is still readable with the default size
+        if let Err(err) = self.runtime.resize_tty(name, width, height).await {
+            log::warn!("Can't resize the terminal of {}: {}", name, err);
+        }
+        Ok(attachment)
+    }
+
+    fn container(&self, task_id: &TaskId) -> Result<&str, Error> {
+        self.containers
+            .get(task_id)
+            .map(String::as_str)
+            .ok_or_else(|| anyhow!("Task {} has no container", task_id))
+    }
+
+    fn volume(&self, task_id: &TaskId) -> Result<String, Error> {
+        self.volumes
+            .get(task_id)
+            .cloned()
+            .ok_or_else(|| anyhow!("Task {} has no volume", task_id))
+    }
+
+    /// Exports the volume of a task, or a directory of it, to a compressed snapshot file.
+    /// The tasks that depend on the volume are stopped while it's read and started again after that.
+    pub fn export_volume(
+        &self,
+        task_id: &TaskId,
+        dir: Option<String>,
+        path: PathBuf,
+    ) -> impl Future<Output = Result<(), Error>> + Send + 'static {
+        let volume = self.volume(task_id);
+        let runtime = self.runtime.clone();
+        let work = async move {
+            let volume = volume?;
+            let dir = dir.map(|dir| volume_dir(&volume, &dir)).transpose()?;
+            let archive = runtime.export_volume(&volume, dir.as_deref().unwrap_or_default());
+            write_snapshot(archive, path).await
+        };
+        self.while_held(task_id, work)
+    }
+
+    /// Restores a snapshot made by [`SdmScope::export_volume`]. A snapshot of the whole volume
+    /// replaces it with a fresh one, a snapshot of a directory is extracted over the existing files.
+    /// The whole volume is replaced only when the snapshot is extracted to a staging volume successfully.
+    /// The tasks that depend on the volume are stopped while it's written and started again after that.
+    pub fn import_volume(
+        &self,
+        task_id: &TaskId,
+        dir: Option<String>,
+        path: PathBuf,
+    ) -> impl Future<Output = Result<(), Error>> + Send + 'static {
+        let volume = self.volume(task_id);
+        let runtime = self.runtime.clone();
+        let labels = self.labels(task_id);
+        let work = async move {
+            let volume = volume?;
+            let labels = labels.with_config(&volume);
+            if let Some(dir) = dir {
+                volume_dir(&volume, &dir)?;
+                runtime.create_volume(&volume, &labels).await?;
+                return runtime.import_volume(&volume, read_snapshot(path)).await;
+            }
+            // A truncated or wrong snapshot fails here and the current data is kept
+            let staging = format!("{}_import", volume);
+            if runtime.volume_exists(&staging).await {
+                runtime.remove_volume(&staging).await?;
+            }
+            runtime.create_volume(&staging, &labels).await?;
+            if let Err(err) = runtime.import_volume(&staging, read_snapshot(path)).await {
+                runtime.remove_volume(&staging).await.ok();
+                return Err(err);
+            }
+            if runtime.volume_exists(&volume).await {
+                runtime.remove_volume(&volume).await?;
+            }
+            runtime.create_volume(&volume, &labels).await?;
+            let archive = runtime.export_volume(&staging, "");
+            runtime
+                .import_volume(&volume, archive)
+                .await
+                .map_err(|err| anyhow!("Can't move the restored data from {}: {}", staging, err))?;
+            runtime.remove_volume(&staging).await
+        };
+        self.while_held(task_id, work)
+    }
+
+    /// Removes the volume of a task when no container uses it. The dependants are stopped before the check,
+    /// so only foreign containers could block the removal. An active volume task creates a fresh volume again.
+    pub fn remove_volume(&self, task_id: &TaskId) -> impl Future<Output = Result<(), Error>> + Send + 'static {
+        let volume = self.volume(task_id);
+        let runtime = self.runtime.clone();
+        let work = async move {
+            let volume = volume?;
+            let users = runtime.volume_users(&volume).await?;
+            if !users.is_empty() {
+                return Err(anyhow!("Volume {} is used by containers: {}", volume, users.join(", ")));
+            }
+            runtime.remove_volume(&volume).await
+        };
+        self.while_held(task_id, work)
+    }
+
+    /// Removes a directory of the volume of a task while the dependants are stopped.
+    pub fn clear_volume(
+        &self,
+        task_id: &TaskId,
+        dir: &str,
+    ) -> impl Future<Output = Result<(), Error>> + Send + 'static {
+        let volume = self.volume(task_id);
+        let runtime = self.runtime.clone();
+        let dir = dir.to_string();
+        let work = async move {
+            let volume = volume?;
+            let dir = volume_dir(&volume, &dir)?;
+            runtime.clear_volume(&volume, &dir).await
+        };
+        self.while_held(task_id, work)
+    }
+
+    /// Holds the dependants of the task while the `work` is running and releases them after it.
+    fn while_held<F>(&self, task_id: &TaskId, work: F) -> impl Future<Output = Result<(), Error>> + Send + 'static
+    where
+        F: Future<Output = Result<(), Error>> + Send + 'static,
+    {
+        let dependants = self.graph.dependants(task_id);
+        let stages = self.graph.stop_stages_of(&dependants);
+        let sender = self.sender.clone();
+        async move {
+            let held = wait_stages(&sender, stages, |tasks, ack| ControlEvent::Hold { tasks, ack }).await;
+            let res = match held {
+                Ok(()) => work.await,
+                Err(err) => Err(err),
+            };
+            log::debug!("Releasing tasks: {:?}", dependants);
+            let req = ControlEvent::Release {
+                tasks: Arc::new(dependants),
+            };
+            sender
+                .send(req)
+                .map_err(|req| anyhow!("Can't send a request: {:?}", req))?;
+            res
+        }
+    }
+
+    /// Removes the containers and networks labelled with the scope that don't belong to its tasks: leftovers
+    /// of removed or renamed tasks and of crashed launchpads. Orphaned volumes are only reported, since they keep
+    /// the data of the user, see `remove_orphaned_volumes`. Should be called when all the tasks are added.
+    pub fn reconcile(&self) -> impl Future<Output = Result<Reconciliation, Error>> + Send + 'static {
+        let runtime = self.runtime.clone();
+        let scope = self.scope.clone();
+        // Containers are removed first, since they use networks
+        let registered = [
+            (ResourceKind::Container, self.containers.clone()),
+            (ResourceKind::Network, self.networks.clone()),
+        ];
+        let volumes = self.volumes.clone();
+        async move {
+            let mut removed = Vec::new();
+            for (kind, names) in registered {
+                removed.extend(remove_orphans(runtime.as_ref(), &scope, kind, &names).await?);
+            }
+            let orphaned_volumes = find_orphans(runtime.as_ref(), &scope, ResourceKind::Volume, &volumes)
+                .await?
+                .into_iter()
+                .map(|resource| resource.name)
+                .collect();
+            Ok(Reconciliation {
+                removed,
+                orphaned_volumes,
+            })
+        }
+    }
+
+    /// Removes the volumes reported as orphaned by `reconcile` with all their data.
+    /// Resolves to the names of the removed volumes.
+    pub fn remove_orphaned_volumes(&self) -> impl Future<Output = Result<Vec<String>, Error>> + Send + 'static {
+        let runtime = self.runtime.clone();
+        let scope = self.scope.clone();
+        let volumes = self.volumes.clone();
+        async move { remove_orphans(runtime.as_ref(), &scope, ResourceKind::Volume, &volumes).await }
+    }
+
+    /// Stops all the tasks of the scope. Dependants are stopped before their dependencies,
+    /// so the returned future resolves when every task of the scope is down.
+    pub fn stop(&self) -> impl Future<Output = Result<(), Error>> + Send + 'static {
+        let stages = self.graph.stop_stages();
+        let sender = self.sender.clone();
+        async move { wait_stages(&sender, stages, |tasks, ack| ControlEvent::Stop { tasks, ack }).await }
+    }
+}
+
+/// A directory relative to the root of the volume that doesn't leave it.
+fn volume_dir(volume: &str, dir: &str) -> Result<String, Error> {
+    let dir = dir.trim_matches('/');
+    if dir.is_empty() || dir.split('/').any(|part| part == "..") {
+        return Err(anyhow!("Invalid directory {:?} of volume {}", dir, volume));
+    }
+    Ok(dir.to_string())
+}
+
+/// The resources of the kind labelled with the scope, but not registered with the `names` of its tasks.
+async fn find_orphans(
+    runtime: &dyn ContainerRuntime,
+    scope: &str,
+    kind: ResourceKind,
+    names: &HashMap<TaskId, String>,
+) -> Result<Vec<LabelledResource>, Error> {
+    let resources = runtime.scope_resources(kind, scope).await?;
+    Ok(resources
+        .into_iter()
+        .filter(|resource| {
+            let task_id = TaskId::from(resource.labels.task_id.as_str());
+            names.get(&task_id) != Some(&resource.name)
+        })
+        .collect())
+}
+
+/// Resolves to the names of the removed resources, fails if any of the orphans can't be removed.
+async fn remove_orphans(
+    runtime: &dyn ContainerRuntime,
+    scope: &str,
+    kind: ResourceKind,
+    names: &HashMap<TaskId, String>,
+) -> Result<Vec<String>, Error> {
+    let mut removed = Vec::new();
+    let mut failed = Vec::new();
+    for resource in find_orphans(runtime, scope, kind, names).await? {
+        log::info!(
+            "Removing the orphaned {} {} of {} ({})",
+            kind.as_str(),
+            resource.name,
+            resource.labels.task_id,
+            resource.labels.version
+        );
+        let res = match kind {
+            ResourceKind::Container => runtime.remove_container(&resource.name).await,
+            ResourceKind::Network => runtime.remove_network(&resource.name).await,
+            ResourceKind::Volume => runtime.remove_volume(&resource.name).await,
+        };
+        match res {
+            Ok(()) => removed.push(resource.name),
+            Err(err) => {
+                log::warn!("Can't remove the orphaned {} {}: {}", kind.as_str(), resource.name, err);
+                failed.push(resource.name);
+            },
+        }
+    }
+    if failed.is_empty() {
+        Ok(removed)
+    } else {
+        Err(anyhow!("Can't remove orphaned resources: {}", failed.join(", ")))
+    }
+}
+
+/// Sends a request to every stage of tasks and waits until all the tasks of the stage are down.
+async fn wait_stages<C, F>(
+    sender: &broadcast::Sender<ControlEvent<C>>,
+    stages: Vec<HashSet<TaskId>>,
+    request: F,
+) -> Result<(), Error>
+where
+    C: ManagedProtocol,
+    F: Fn(Arc<HashSet<TaskId>>, mpsc::UnboundedSender<TaskId>) -> ControlEvent<C>,
+{
+    for stage in stages {
+        log::debug!("Stopping tasks: {:?}", stage);
+        let (ack_tx, mut ack_rx) = mpsc::unbounded_channel();
+        let mut pending = stage.clone();
+        let req = request(Arc::new(stage), ack_tx);
+        sender
+            .send(req)
+            .map_err(|req| anyhow!("Can't send a request: {:?}", req))?;
+        while !pending.is_empty() {
+            let task_id = ack_rx
+                .recv()
+                .await
+                .ok_or_else(|| anyhow!("Tasks {:?} were terminated before they stopped", pending))?;
+            pending.remove(&task_id);
+        }
+    }
+    Ok(())
 }
@@ -90,7 +90,12 @@ impl<S: TaskStatusChecker> SdmStatus<S> {
         func(&mut self.status);
     }
 
-    // pub fn set_fallback(&mut self, fallback: Fallback<S>) {
-    // self.fallback = Some(fallback);
-    // }
+    /// Sets the status that will be applied if the current one is not changed before `when`.
+    pub fn set_fallback(&mut self, fallback: Fallback<S>) {
+        self.fallback = Some(fallback);
+    }
+
+    pub fn reset_fallback(&mut self) {
+        self.fallback = None;
+    }
 }
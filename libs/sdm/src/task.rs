@@ -21,11 +21,10 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
-use std::{collections::HashMap, fmt};
+use std::{collections::HashMap, fmt, sync::Arc};
 
 use anyhow::Error;
 use async_trait::async_trait;
-use bollard::Docker;
 use chrono::Local;
 use derive_more::{Deref, DerefMut};
 use futures::StreamExt;
@@ -43,6 +42,7 @@ use tokio_stream::wrappers::{BroadcastStream, UnboundedReceiverStream};
 
 use crate::{
     config::ManagedProtocol,
+    runtime::{ContainerRuntime, Labels, PortMapping},
     scope::{ControlEvent, Report, ReportEnvelope},
     status::SdmStatus,
 };
@@ -59,6 +59,11 @@ pub trait TaskStatusChecker: fmt::Debug + Default + Send {
     fn is_ready(&self) -> bool {
         false
     }
+
+    /// Indicates whether the task released everything it started and could be left.
+    fn is_stopped(&self) -> bool {
+        true
+    }
 }
 
 #[async_trait]
@@ -71,6 +76,11 @@ pub trait RunnableTask: Sized + Send + 'static {
 
     /// Indicates whether any resources created by this task should persist between runs.
     fn is_permanent(&self) -> bool;
+
+    /// Host ports published by the task.
+    fn ports(&self) -> &[PortMapping] {
+        &[]
+    }
 }
 
 #[async_trait]
@@ -137,16 +147,28 @@ impl<E, P: ManagedProtocol> TaskSender<E, P> {
             .map_err(|_| Error::msg("Can't send a report"))
     }
 
-    pub fn send_logs(&self, message: String) -> Result<(), Error> {
+    pub fn send_logs(&self, level: LogLevel, message: String) -> Result<(), Error> {
         let record = LogRecord {
             datetime: Local::now().naive_local(),
-            // TODO: Detect the real level
-            level: LogLevel::Info,
+            level,
             message,
         };
+        self.send_log_record(record)
+    }
+
+    /// Sends the record to the logs. Errors are added to the fails of the task as well.
+    pub fn send_log_record(&self, record: LogRecord) -> Result<(), Error> {
+        let error = (record.level == LogLevel::Error).then(|| ErrorRecord {
+            datetime: record.datetime,
+            message: record.message.clone(),
+        });
         let delta = TaskDelta::LogRecord(record);
-        let report = Report::Delta(delta);
-        self.send_report(report)
+        self.send_report(Report::Delta(delta))?;
+        if let Some(error) = error {
+            let delta = TaskDelta::LogError(error);
+            self.send_report(Report::Delta(delta))?;
+        }
+        Ok(())
     }
 
     pub fn send_stats(&self, record: StatsData) -> Result<(), Error> {
@@ -171,11 +193,14 @@ pub struct TaskContext<T: RunnableTask> {
     /// Filled by a dependencies controller
     dependencies_ready: bool,
     resources_map: HashMap<TaskId, String>,
+    ports_map: HashMap<TaskId, Vec<PortMapping>>,
     /// Depends on the config
     should_start: bool,
     pub status: SdmStatus<T::Status>,
     sender: TaskSender<T::Event, T::Protocol>,
-    pub driver: Docker,
+    pub driver: Arc<dyn ContainerRuntime>,
+    /// Ownership labels of the resources created by the task
+    pub labels: Labels,
     #[deref]
     #[deref_mut]
     pub inner: T,
@@ -190,6 +215,11 @@ impl<T: RunnableTask> TaskContext<T> {
         self.resources_map.get(id).map(String::as_ref)
     }
 
+    /// Host ports published by the ready dependencies.
+    pub fn dependency_ports(&self) -> impl Iterator<Item = (&TaskId, &[PortMapping])> {
+        self.ports_map.iter().map(|(id, ports)| (id, ports.as_slice()))
+    }
+
     pub fn sender(&self) -> &TaskSender<T::Event, T::Protocol> {
         &self.sender
     }
@@ -210,6 +240,14 @@ pub struct SdmTaskRunner<R: RunnableTask> {
     /// Waits when these dependencies started.
     dependencies: HashMap<TaskId, bool>,
     ready_to_use: bool,
+    /// Set when the scope stops the task. The config is ignored after that.
+    halted: bool,
+    /// Set while the scope keeps the task stopped, e.g. to access the volumes it uses.
+    held: bool,
+    /// The task is activated by the last config.
+    wanted: bool,
+    /// Acknowledges the stop of the task once it's down.
+    stop_ack: Option<mpsc::UnboundedSender<TaskId>>,
 }
 
 impl<R: RunnableTask> SdmTaskRunner<R>
@@ -220,7 +258,8 @@ where
         req_tx: broadcast::Sender<ControlEvent<R::Protocol>>,
         rep_tx: mpsc::UnboundedSender<ReportEnvelope<R::Protocol>>,
         inner: R,
-        docker: Docker,
+        driver: Arc<dyn ContainerRuntime>,
+        labels: Labels,
     ) -> Self {
         let task_id = M::id();
         let (event_tx, event_rx) = mpsc::unbounded_channel();
@@ -233,10 +272,12 @@ where
         let context = TaskContext {
             dependencies_ready: false,
             resources_map: HashMap::new(),
+            ports_map: HashMap::new(),
             should_start: false,
             status: SdmStatus::new(inner.name().to_string()),
             sender,
-            driver: docker,
+            driver,
+            labels,
             inner,
         };
         // It subscribed here to avoid the gap if that will subscribe in the routine.
@@ -251,6 +292,10 @@ where
             context,
             dependencies,
             ready_to_use: false,
+            halted: false,
+            held: false,
+            wanted: false,
+            stop_ack: None,
         }
     }
 
@@ -307,6 +352,7 @@ where
             }
             self.update().await;
             self.notify_dependants();
+            self.check_stopped();
         }
         Ok(())
     }
@@ -318,7 +364,8 @@ where
                 self.ready_to_use = true;
                 let task_id = self.task_id.clone();
                 let name = self.context.name().to_owned();
-                let event = ControlEvent::ResourceReady { task_id, name };
+                let ports = self.context.ports().to_vec();
+                let event = ControlEvent::ResourceReady { task_id, name, ports };
                 self.broadcast(event);
             }
         } else {
@@ -332,6 +379,15 @@ where
         }
     }
 
+    fn check_stopped(&mut self) {
+        if self.context.status.is_stopped() {
+            if let Some(ack) = self.stop_ack.take() {
+                debug!("[SdmTaskRunner::check_stopped] Task {} is stopped", self.task_id);
+                ack.send(self.task_id.clone()).ok();
+            }
+        }
+    }
+
     fn broadcast(&mut self, event: ControlEvent<R::Protocol>) {
         if let Err(err) = self.requests_sender.send(event) {
             log::error!("Can't broadcast event: {:?}", err);
@@ -341,13 +397,16 @@ where
     fn process_request(&mut self, req: ControlEvent<R::Protocol>) {
         match req {
             ControlEvent::SetConfig(config) => {
-                let config = config.as_deref();
-                self.reconfigure(config);
+                if !self.halted {
+                    let config = config.as_deref();
+                    self.reconfigure(config);
+                }
             },
-            ControlEvent::ResourceReady { task_id, name } => {
+            ControlEvent::ResourceReady { task_id, name, ports } => {
                 if let Some(flag) = self.dependencies.get_mut(&task_id) {
                     *flag = true;
-                    self.context.resources_map.insert(task_id, name);
+                    self.context.resources_map.insert(task_id.clone(), name);
+                    self.context.ports_map.insert(task_id, ports);
                     // Check dependencies if any flag changed
                     self.check_dependencies();
                 }
@@ -356,6 +415,7 @@ where
                 if let Some(flag) = self.dependencies.get_mut(&task_id) {
                     *flag = false;
                     self.context.resources_map.remove(&task_id);
+                    self.context.ports_map.remove(&task_id);
                     // Check dependencies if any flag changed
                     self.check_dependencies();
                 }
@@ -363,6 +423,21 @@ where
             ControlEvent::InnerEvent(inner) => {
                 self.process_inner_event(inner);
             },
+            ControlEvent::Stop { tasks, ack } => {
+                if tasks.contains(&self.task_id) {
+                    self.halt(ack);
+                }
+            },
+            ControlEvent::Hold { tasks, ack } => {
+                if tasks.contains(&self.task_id) {
+                    self.hold(ack);
+                }
+            },
+            ControlEvent::Release { tasks } => {
+                if tasks.contains(&self.task_id) {
+                    self.release();
+                }
+            },
         }
     }
 
@@ -384,20 +459,53 @@ where
 
     pub fn reconfigure(&mut self, config: Option<&<R::Protocol as ManagedProtocol>::Config>) {
         let is_active = self.context.reconfigure(config);
+        self.wanted = is_active;
+        self.set_active(is_active && !self.held);
+    }
+
+    fn set_active(&mut self, is_active: bool) {
         if is_active && self.context.should_start != is_active {
             drop(self.context.update_task_status(TaskStatusValue::Waiting));
-            debug!("[SdmTaskRunner::reconfigure] Task {} is queued to start", self.task_id)
+            debug!("[SdmTaskRunner::set_active] Task {} is queued to start", self.task_id)
         } else if !is_active && self.context.should_start != is_active {
             drop(self.context.update_task_status(TaskStatusValue::ShuttingDown));
         } else {
             debug!(
-                "[SdmTaskRunner::reconfigure] Task {} is NOT queued to start",
+                "[SdmTaskRunner::set_active] Task {} is NOT queued to start",
                 self.task_id
             )
         }
         self.context.should_start = is_active;
     }
 
+    /// Deactivates the task for good. The `ack` is notified when the task is stopped.
+    pub fn halt(&mut self, ack: mpsc::UnboundedSender<TaskId>) {
+        debug!("[SdmTaskRunner::halt] Task {} is stopping", self.task_id);
+        if self.context.should_start {
+            drop(self.context.update_task_status(TaskStatusValue::ShuttingDown));
+        }
+        self.context.should_start = false;
+        self.halted = true;
+        self.stop_ack = Some(ack);
+    }
+
+    /// Deactivates the task until it's released. The `ack` is notified when the task is stopped.
+    pub fn hold(&mut self, ack: mpsc::UnboundedSender<TaskId>) {
+        debug!("[SdmTaskRunner::hold] Task {} is held", self.task_id);
+        self.held = true;
+        self.set_active(false);
+        self.stop_ack = Some(ack);
+    }
+
+    /// Activates the held task again if the config requires it.
+    pub fn release(&mut self) {
+        debug!("[SdmTaskRunner::release] Task {} is released", self.task_id);
+        self.held = false;
+        if !self.halted {
+            self.set_active(self.wanted);
+        }
+    }
+
     pub fn process_inner_event(&mut self, event: <R::Protocol as ManagedProtocol>::Inner) {
         self.context.process_inner_event(event);
     }
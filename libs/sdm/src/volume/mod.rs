@@ -21,10 +21,12 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
+mod snapshot;
 mod task;
 
 use std::fmt;
 
+pub(crate) use snapshot::{read_snapshot, write_snapshot};
 pub(crate) use task::VolumeTask;
 
 use crate::config::ManagedProtocol;
@@ -0,0 +1,109 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+//! Compressed snapshots of volumes.
+//!
+//! A snapshot is the tar archive exported by the runtime compressed with gzip. Files are
+//! written and read in blocking tasks connected to the runtime streams with channels.
+
+use std::{
+    fs::{self, File},
+    io::{BufReader, BufWriter, Read, Write},
+    path::PathBuf,
+};
+
+use anyhow::{anyhow, Error};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use futures::StreamExt;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+use crate::runtime::RuntimeStream;
+
+const CHUNKS_BUFFER: usize = 16;
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Compresses the archive to the file. An incomplete file is removed.
+pub(crate) async fn write_snapshot(mut archive: RuntimeStream<Vec<u8>>, path: PathBuf) -> Result<(), Error> {
+    let (tx, mut rx) = mpsc::channel::<Vec<u8>>(CHUNKS_BUFFER);
+    let file = path.clone();
+    let writer = tokio::task::spawn_blocking(move || -> Result<(), Error> {
+        let mut encoder = GzEncoder::new(BufWriter::new(File::create(file)?), Compression::default());
+        while let Some(chunk) = rx.blocking_recv() {
+            encoder.write_all(&chunk)?;
+        }
+        encoder.finish()?.flush()?;
+        Ok(())
+    });
+    let mut res = Ok(());
+    while let Some(chunk) = archive.next().await {
+        match chunk {
+            Ok(chunk) => {
+                if tx.send(chunk).await.is_err() {
+                    // The writer has failed, the reason is returned by the handle
+                    break;
+                }
+            },
+            Err(err) => {
+                res = Err(err);
+                break;
+            },
+        }
+    }
+    drop(tx);
+    let written = writer
+        .await
+        .map_err(|err| anyhow!("The writer of the snapshot failed: {}", err))?;
+    let res = written.and(res);
+    if res.is_err() {
+        fs::remove_file(&path).ok();
+    }
+    res
+}
+
+/// Decompresses the file to an archive.
+pub(crate) fn read_snapshot(path: PathBuf) -> RuntimeStream<Vec<u8>> {
+    let (tx, rx) = mpsc::channel(CHUNKS_BUFFER);
+    tokio::task::spawn_blocking(move || {
+        let mut decoder = match File::open(&path) {
+            Ok(file) => GzDecoder::new(BufReader::new(file)),
+            Err(err) => {
+                let err = anyhow!("Can't open the snapshot {}: {}", path.display(), err);
+                tx.blocking_send(Err(err)).ok();
+                return;
+            },
+        };
+        let mut buf = vec![0; CHUNK_SIZE];
+        loop {
+            let res = match decoder.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => Ok(buf[..n].to_vec()),
+                Err(err) => Err(err.into()),
+            };
+            let failed = res.is_err();
+            if tx.blocking_send(res).is_err() || failed {
+                break;
+            }
+        }
+    });
+    ReceiverStream::new(rx).boxed()
+}
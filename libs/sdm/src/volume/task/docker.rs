@@ -21,34 +21,22 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
-use std::collections::HashMap;
-
 use anyhow::Error;
-use bollard::{
-    models::{EventMessage, EventMessageTypeEnum},
-    system::EventsOptions,
-    volume::CreateVolumeOptions,
-};
-use futures::TryStreamExt;
+use tari_launchpad_protocol::container::TaskDelta;
+use tokio::time::Instant;
 
 use super::{Event, VolumeTask};
 use crate::{
     forwarder::{Converter, Forwarder},
+    runtime::{ResourceKind, RuntimeEvent},
+    scope::Report,
     task::TaskContext,
     volume::ManagedProtocol,
 };
 
 impl<C: ManagedProtocol> TaskContext<VolumeTask<C>> {
     pub fn subscribe_to_events(&mut self) {
-        let mut type_filter = HashMap::new();
-        type_filter.insert("type".to_string(), vec!["volume".to_string()]);
-        type_filter.insert("volume".to_string(), vec![self.inner.volume_name.clone()]);
-        let opts = EventsOptions {
-            since: None,
-            until: None,
-            filters: type_filter,
-        };
-        let stream = self.driver.events(Some(opts)).map_err(Error::from);
+        let stream = self.driver.events(ResourceKind::Volume, &self.inner.volume_name);
         let sender = self.sender().get_direct().clone();
         let conv = EventConv {
             // TODO: Name is not necessary here
@@ -60,50 +48,55 @@ impl<C: ManagedProtocol> TaskContext<VolumeTask<C>> {
 
     pub async fn volume_exists(&mut self) -> bool {
         // TODO: Move to context directly
-        self.driver.inspect_volume(&self.inner.volume_name).await.is_ok()
+        self.driver.volume_exists(&self.inner.volume_name).await
     }
 
     pub async fn try_create_volume(&mut self) -> Result<(), Error> {
-        let options = CreateVolumeOptions {
-            name: self.inner.volume_name.as_ref() as &str,
-            ..Default::default()
-        };
-        self.driver.create_volume(options).await?;
-        // TODO: Check warnings...
-        Ok(())
+        let labels = self.labels.with_config(&self.inner.volume_name);
+        self.driver.create_volume(&self.inner.volume_name, &labels).await
+    }
+
+    /// The engine scans all its resources to calculate the usage, so it's measured in the background
+    /// and delivered as `Event::DiskUsage` to not block the task meanwhile.
+    pub fn check_disk_usage(&mut self) {
+        if self.inner.usage_check.is_some() {
+            return;
+        }
+        self.inner.usage_checked_at = Some(Instant::now());
+        let driver = self.driver.clone();
+        let name = self.inner.volume_name.clone();
+        let sender = self.sender().get_direct().clone();
+        let handle = tokio::spawn(async move {
+            let size = driver.volume_size(&name).await;
+            sender.send(Event::DiskUsage(size)).ok();
+        });
+        self.inner.usage_check = Some(handle.into());
     }
 
-    // pub async fn try_remove_volume(&mut self) -> Result<(), Error> {
-    // let opts = RemoveVolumeOptions { force: true };
-    // self.driver.remove_volume(&self.inner.volume_name, Some(opts)).await?;
-    // Ok(())
-    // }
+    /// Reports the usage if it has changed.
+    pub fn report_disk_usage(&mut self, size: Option<u64>) -> Result<(), Error> {
+        if self.inner.disk_usage != size {
+            self.inner.disk_usage = size;
+            let delta = TaskDelta::DiskUsage(size);
+            self.sender().send_report(Report::Delta(delta))?;
+        }
+        Ok(())
+    }
 }
 
 struct EventConv {
     name: String,
 }
 
-impl Converter<EventMessage, Event> for EventConv {
-    fn convert(&self, res: Result<EventMessage, Error>) -> Option<Event> {
-        if let Ok(EventMessage {
-            typ: Some(typ),
-            action: Some(action),
-            actor: Some(actor),
-            ..
-        }) = res
-        {
-            if let Some(attributes) = actor.attributes {
-                if let Some(name) = attributes.get("name") {
-                    if self.name == *name {
-                        // TODO: Check the name
-                        if let EventMessageTypeEnum::VOLUME = typ {
-                            return action.try_into().ok();
-                        }
-                    } else {
-                        log::error!("Message for other volume {}, but expected {}", name, self.name);
-                    }
+impl Converter<RuntimeEvent, Event> for EventConv {
+    fn convert(&mut self, res: Result<RuntimeEvent, Error>) -> Option<Event> {
+        if let Ok(RuntimeEvent { kind, action, name, .. }) = res {
+            if self.name == name {
+                if let ResourceKind::Volume = kind {
+                    return action.try_into().ok();
                 }
+            } else {
+                log::error!("Message for other volume {}, but expected {}", name, self.name);
             }
         }
         None
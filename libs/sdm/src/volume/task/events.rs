@@ -32,9 +32,18 @@ impl<C: ManagedProtocol> TaskContext<VolumeTask<C>> {
         match event {
             Event::Created => self.on_created(),
             Event::Destroyed => self.on_destroyed(),
+            Event::DiskUsage(size) => self.on_disk_usage(size),
         }
     }
 
+    fn on_disk_usage(&mut self, size: Option<u64>) -> Result<(), Error> {
+        self.inner.usage_check.take();
+        if let Status::Active = self.status.get() {
+            self.report_disk_usage(size)?;
+        }
+        Ok(())
+    }
+
     fn on_created(&mut self) -> Result<(), Error> {
         if let Status::WaitCreating = self.status.get() {
             self.status.set(Status::Active);
@@ -43,7 +52,17 @@ impl<C: ManagedProtocol> TaskContext<VolumeTask<C>> {
         Ok(())
     }
 
+    /// The volume was removed, it's created again if the task is still active.
     fn on_destroyed(&mut self) -> Result<(), Error> {
+        if let Status::Active = self.status.get() {
+            log::info!("Volume {} was removed", self.inner.volume_name);
+            self.status.set(Status::Checking);
+            self.update_task_status(TaskStatus::Pending)?;
+            // A new volume is measured as soon as it's created
+            self.inner.usage_check.take();
+            self.inner.usage_checked_at.take();
+            self.report_disk_usage(None)?;
+        }
         Ok(())
     }
 }
@@ -27,6 +27,7 @@ mod update;
 
 use anyhow::Error;
 use async_trait::async_trait;
+use tokio::time::{Duration, Instant};
 
 use super::ManagedVolume;
 use crate::{
@@ -36,11 +37,19 @@ use crate::{
     utils::TaskGuard,
 };
 
+/// How often the disk usage of an active volume is checked.
+const USAGE_INTERVAL: Duration = Duration::from_secs(60);
+
 pub struct VolumeTask<C: ManagedProtocol> {
     events: Option<TaskGuard<()>>,
     volume: Box<dyn ManagedVolume<Protocol = C>>,
 
     volume_name: String,
+    /// The last reported disk usage
+    disk_usage: Option<u64>,
+    usage_checked_at: Option<Instant>,
+    /// The measurement of the disk usage in progress
+    usage_check: Option<TaskGuard<()>>,
 }
 
 impl<C: ManagedProtocol> VolumeTask<C> {
@@ -50,6 +59,9 @@ impl<C: ManagedProtocol> VolumeTask<C> {
             events: None,
             volume,
             volume_name,
+            disk_usage: None,
+            usage_checked_at: None,
+            usage_check: None,
         }
     }
 }
@@ -116,6 +128,8 @@ impl Default for Status {
 pub enum Event {
     Destroyed,
     Created,
+    /// The disk usage measured in the background
+    DiskUsage(Option<u64>),
 }
 
 impl TryFrom<String> for Event {
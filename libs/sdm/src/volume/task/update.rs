@@ -24,7 +24,7 @@
 use anyhow::Error;
 use tari_launchpad_protocol::container::TaskStatus;
 
-use super::{Status, VolumeTask};
+use super::{Status, VolumeTask, USAGE_INTERVAL};
 use crate::{config::ManagedProtocol, task::TaskContext};
 
 impl<C: ManagedProtocol> TaskContext<VolumeTask<C>> {
@@ -46,6 +46,9 @@ impl<C: ManagedProtocol> TaskContext<VolumeTask<C>> {
 
     async fn do_checking(&mut self) -> Result<(), Error> {
         log::trace!("[Update event: Volume] `do_checking`");
+        if !self.should_be_active() {
+            return Ok(());
+        }
         if self.volume_exists().await {
             self.status.set(Status::Active);
             self.update_task_status(TaskStatus::Active)?;
@@ -69,7 +72,14 @@ impl<C: ManagedProtocol> TaskContext<VolumeTask<C>> {
     async fn do_active(&mut self) -> Result<(), Error> {
         log::trace!("[Update event: Volume] `do_active`");
         if !self.should_be_active() {
-            // self.status.set(Status::Checking);
+            // Volumes are permanent, the data is kept until the volume is removed explicitly
+            self.status.set(Status::InitialState);
+        } else if self
+            .inner
+            .usage_checked_at
+            .is_none_or(|checked_at| checked_at.elapsed() >= USAGE_INTERVAL)
+        {
+            self.check_disk_usage();
         }
         Ok(())
     }
@@ -0,0 +1,323 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::{anyhow, Error};
+use tari_launchpad_protocol::container::{TaskId, TaskState, TaskStatus};
+use tari_sdm::{
+    config::ManagedProtocol,
+    ids::ManagedTask,
+    image::{
+        checker::{ContainerChecker, LogMatch, ReadyIfStarted, ReadyWhen},
+        log_parser::{Log4rsParser, LogParser},
+        ManagedContainer, Mounts, Networks, Ports, RestartPolicy,
+    },
+    network::ManagedNetwork,
+    runtime::{fake::FakeRuntime, NetworkSpec, PortMapping, RegistryAuth, Resources},
+    volume::ManagedVolume,
+    Report, SdmScope,
+};
+use tokio::time::{timeout, timeout_at, Duration, Instant};
+
+pub const SCOPE: &str = "test";
+pub const NODE_IMAGE: &str = "registry/node:latest";
+pub const NODE_CONTAINER: &str = "test_node";
+pub const NODE_DIGEST: &str = "sha256:0d2c";
+pub const MINER_IMAGE: &str = "registry/miner:latest";
+pub const MINER_CONTAINER: &str = "test_miner";
+
+#[derive(Debug)]
+pub struct TestProtocol;
+
+#[derive(Debug, Default)]
+pub struct TestConfig {
+    /// Overrides the tag of the miner image
+    pub miner_tag: Option<String>,
+    /// Credentials to pull the miner image
+    pub miner_credentials: Option<RegistryAuth>,
+    /// The miner is ready when the line appears in the logs within 10 seconds
+    pub miner_ready_line: Option<String>,
+    /// Addresses of the network
+    pub network: Option<NetworkSpec>,
+}
+
+impl ManagedProtocol for TestProtocol {
+    type Config = TestConfig;
+    type Inner = ();
+    type Outer = ();
+}
+
+#[derive(Debug, Default)]
+pub struct Net {
+    spec: NetworkSpec,
+}
+
+impl ManagedTask for Net {
+    fn id() -> TaskId {
+        "Net".into()
+    }
+}
+
+impl ManagedNetwork for Net {
+    type Protocol = TestProtocol;
+
+    fn reconfigure(&mut self, config: Option<&TestConfig>) -> bool {
+        self.spec = config.and_then(|config| config.network.clone()).unwrap_or_default();
+        config.is_some()
+    }
+
+    fn network_name(&self) -> &str {
+        "net"
+    }
+
+    fn spec(&self) -> NetworkSpec {
+        self.spec.clone()
+    }
+}
+
+#[derive(Debug)]
+pub struct Vol;
+
+impl ManagedTask for Vol {
+    fn id() -> TaskId {
+        "Vol".into()
+    }
+}
+
+impl ManagedVolume for Vol {
+    type Protocol = TestProtocol;
+
+    fn volume_name(&self) -> &str {
+        "vol"
+    }
+}
+
+#[derive(Debug)]
+pub struct Node;
+
+impl ManagedTask for Node {
+    fn id() -> TaskId {
+        "Node".into()
+    }
+
+    fn deps() -> Vec<TaskId> {
+        vec![Net::id(), Vol::id()]
+    }
+}
+
+impl ManagedContainer for Node {
+    type Protocol = TestProtocol;
+
+    fn registry(&self) -> &str {
+        "registry"
+    }
+
+    fn image_name(&self) -> &str {
+        "node"
+    }
+
+    fn log_parser(&self) -> Box<dyn LogParser> {
+        Box::<Log4rsParser>::default()
+    }
+
+    fn digest(&self) -> Option<&str> {
+        Some(NODE_DIGEST)
+    }
+
+    fn ports(&self, ports: &mut Ports) {
+        ports.add(18_142).localhost();
+        ports.forward(19_000, 9_000).udp().relocatable();
+    }
+
+    fn networks(&self, networks: &mut Networks) {
+        networks.add("node", Net::id());
+    }
+
+    fn mounts(&self, mounts: &mut Mounts) {
+        mounts.add_volume(Vol::id(), "/data");
+    }
+
+    fn resources(&self) -> Resources {
+        let mut resources = Resources::default();
+        resources.cpus(1.5).memory_mib(512).pids(64);
+        resources
+    }
+
+    fn restart_policy(&self) -> RestartPolicy {
+        RestartPolicy::OnFailure { max_attempts: 2 }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Miner {
+    tag: Option<String>,
+    credentials: Option<RegistryAuth>,
+    ready_line: Option<String>,
+}
+
+impl ManagedTask for Miner {
+    fn id() -> TaskId {
+        "Miner".into()
+    }
+
+    fn deps() -> Vec<TaskId> {
+        vec![Node::id()]
+    }
+}
+
+impl ManagedContainer for Miner {
+    type Protocol = TestProtocol;
+
+    fn registry(&self) -> &str {
+        "registry"
+    }
+
+    fn image_name(&self) -> &str {
+        "miner"
+    }
+
+    fn tag(&self) -> &str {
+        self.tag.as_deref().unwrap_or("latest")
+    }
+
+    fn reconfigure(&mut self, config: Option<&TestConfig>) -> Option<bool> {
+        let config = config?;
+        self.tag = config.miner_tag.clone();
+        self.credentials = config.miner_credentials.clone();
+        self.ready_line = config.miner_ready_line.clone();
+        Some(true)
+    }
+
+    fn credentials(&self) -> Option<RegistryAuth> {
+        self.credentials.clone()
+    }
+
+    fn checker(&mut self) -> Box<dyn ContainerChecker<TestProtocol>> {
+        match &self.ready_line {
+            Some(line) => {
+                let probe = LogMatch::new(line).timeout(Duration::from_secs(10));
+                Box::new(ReadyWhen::new(probe))
+            },
+            None => Box::<ReadyIfStarted>::default(),
+        }
+    }
+}
+
+pub struct Harness {
+    pub runtime: FakeRuntime,
+    pub scope: SdmScope<TestProtocol>,
+    pub states: HashMap<TaskId, TaskState>,
+    /// The last reported published ports of the tasks
+    pub ports: HashMap<TaskId, Vec<PortMapping>>,
+}
+
+impl Harness {
+    /// Creates a scope with all test tasks over a fake runtime.
+    pub fn setup(runtime: FakeRuntime) -> Result<Self, Error> {
+        Self::setup_scope(runtime, SCOPE, 0)
+    }
+
+    /// Creates a scope with the name and the port offset, so several scopes could share the runtime.
+    pub fn setup_scope(runtime: FakeRuntime, name: &str, port_offset: u16) -> Result<Self, Error> {
+        let mut scope = SdmScope::with_runtime(name, Arc::new(runtime.clone()));
+        scope.set_port_offset(port_offset);
+        scope.add_network(Net::default())?;
+        scope.add_volume(Vol)?;
+        scope.add_image(Node)?;
+        scope.add_image(Miner::default())?;
+        Ok(Self {
+            runtime,
+            scope,
+            states: HashMap::new(),
+            ports: HashMap::new(),
+        })
+    }
+
+    pub fn activate(&mut self, active: bool) -> Result<(), Error> {
+        let config = active.then(TestConfig::default);
+        self.scope.set_config(config)
+    }
+
+    pub fn configure(&mut self, config: TestConfig) -> Result<(), Error> {
+        self.scope.set_config(Some(config))
+    }
+
+    pub fn state(&self, id: &TaskId) -> Option<&TaskState> {
+        self.states.get(id)
+    }
+
+    /// Processes reports until the state of the task matches the predicate.
+    pub async fn wait_for<F>(&mut self, id: &TaskId, func: F) -> Result<(), Error>
+    where
+        F: Fn(&TaskState) -> bool,
+    {
+        loop {
+            if self.state(id).map(&func).unwrap_or_default() {
+                return Ok(());
+            }
+            timeout(Duration::from_secs(120), self.step())
+                .await
+                .map_err(|_| anyhow!("Time elapsed"))??;
+        }
+    }
+
+    pub async fn wait_status<F>(&mut self, id: &TaskId, func: F) -> Result<(), Error>
+    where
+        F: Fn(&TaskStatus) -> bool,
+    {
+        self.wait_for(id, |state| func(&state.status)).await
+    }
+
+    /// Processes reports for the given (virtual) time.
+    pub async fn idle(&mut self, duration: Duration) -> Result<(), Error> {
+        let deadline = Instant::now() + duration;
+        while let Ok(res) = timeout_at(deadline, self.step()).await {
+            res?;
+        }
+        Ok(())
+    }
+
+    async fn step(&mut self) -> Result<(), Error> {
+        let envelope = self
+            .scope
+            .recv()
+            .await
+            .ok_or_else(|| anyhow!("Reports channel closed"))?;
+        match envelope.details {
+            Report::State(state) => {
+                self.states.insert(envelope.task_id, state);
+            },
+            Report::Delta(delta) => {
+                if let Some(state) = self.states.get_mut(&envelope.task_id) {
+                    state.apply(delta);
+                }
+            },
+            Report::Ports(ports) => {
+                self.ports.insert(envelope.task_id, ports);
+            },
+            Report::Extras(_) => {},
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,119 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+#[allow(dead_code)]
+mod common;
+
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::Error;
+use common::{Miner, Net, Node, TestProtocol, Vol};
+use tari_launchpad_protocol::container::{TaskId, TaskState, TaskStatus};
+use tari_sdm::{
+    graph::{DependencyGraph, GraphError, TaskNode},
+    ids::ManagedTask,
+    runtime::{fake::FakeRuntime, ResourceKind},
+    SdmScope,
+};
+
+fn node(id: &str, deps: &[&str]) -> TaskNode {
+    let deps = deps.iter().map(|dep| TaskId::from(dep.to_string())).collect();
+    TaskNode::new(id.to_string().into(), ResourceKind::Container, deps)
+}
+
+#[test]
+fn test_cycle_is_rejected() {
+    let mut graph = DependencyGraph::default();
+    graph.add(node("a", &["b"])).unwrap();
+    graph.add(node("b", &["c"])).unwrap();
+    let err = graph.add(node("c", &["a"])).unwrap_err();
+    assert_eq!(err.to_string(), "Dependency cycle: c -> a -> b -> c");
+    let err = graph.add(node("d", &["d"])).unwrap_err();
+    assert!(matches!(err, GraphError::Cycle(_)));
+    let err = graph.add(node("a", &[])).unwrap_err();
+    assert!(matches!(err, GraphError::Duplicate(_)));
+}
+
+#[test]
+fn test_unknown_dependency() {
+    let mut graph = DependencyGraph::default();
+    graph.add(node("a", &["b"])).unwrap();
+    graph.add(node("b", &["typo"])).unwrap();
+    let err = graph.validate().unwrap_err();
+    assert_eq!(err.to_string(), "Task b depends on the unknown task typo");
+}
+
+#[test]
+fn test_stop_stages() {
+    let mut graph = DependencyGraph::default();
+    graph.add(node("net", &[])).unwrap();
+    graph.add(node("node", &["net"])).unwrap();
+    graph.add(node("miner", &["node"])).unwrap();
+    graph.add(node("proxy", &["node"])).unwrap();
+    let stages: Vec<Vec<String>> = graph
+        .stop_stages()
+        .into_iter()
+        .map(|stage| {
+            let mut ids: Vec<_> = stage.iter().map(TaskId::to_string).collect();
+            ids.sort();
+            ids
+        })
+        .collect();
+    assert_eq!(stages, vec![vec!["miner", "proxy"], vec!["node"], vec!["net"]]);
+}
+
+#[test]
+fn test_blocking_dependencies_are_exported() -> Result<(), Error> {
+    let mut graph = DependencyGraph::default();
+    graph.add(node("node", &[]))?;
+    graph.add(node("miner", &["node"]))?;
+    let mut states = HashMap::new();
+    let mut state = TaskState::new(false);
+    state.status = TaskStatus::Pending;
+    states.insert(TaskId::from("node".to_string()), state);
+
+    let dot = graph.to_dot(&states);
+    assert!(dot.contains("\"miner\" -> \"node\" [color=red];"));
+    let json: serde_json::Value = serde_json::from_str(&graph.to_json(&states)?)?;
+    assert_eq!(json[1]["blocked_by"], serde_json::json!(["node"]));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_scope_resources_are_validated() -> Result<(), Error> {
+    let runtime = Arc::new(FakeRuntime::new());
+    let mut scope = SdmScope::<TestProtocol>::with_runtime("test", runtime.clone());
+    scope.add_network(Net::default())?;
+    scope.add_volume(Vol)?;
+    scope.add_image(Node)?;
+    scope.add_image(Miner::default())?;
+    scope.validate()?;
+
+    // The node mounts the volume that is not added
+    let mut scope = SdmScope::<TestProtocol>::with_runtime("test", runtime);
+    scope.add_network(Net::default())?;
+    scope.add_image(Node)?;
+    let err = scope.validate().unwrap_err();
+    assert!(matches!(err, GraphError::UnknownDependency { dependency, .. } if dependency == Vol::id()));
+    Ok(())
+}
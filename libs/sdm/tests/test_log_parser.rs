@@ -0,0 +1,109 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use chrono::{NaiveDate, Timelike};
+use tari_launchpad_protocol::container::LogLevel;
+use tari_sdm::image::log_parser::{Log4rsParser, LogParser, LogfmtParser, PlainParser, TorParser, XmrigParser};
+
+#[test]
+fn test_log4rs_lines() {
+    let parser = Log4rsParser::default();
+    let record = parser
+        .parse("2023-07-12 10:11:12.345 [c::bn::sync] [abcd] ERROR Header sync failed")
+        .unwrap();
+    assert_eq!(record.level, LogLevel::Error);
+    assert_eq!(record.message, "Header sync failed");
+    let expected = NaiveDate::from_ymd_opt(2023, 7, 12)
+        .unwrap()
+        .and_hms_milli_opt(10, 11, 12, 345)
+        .unwrap();
+    assert_eq!(record.datetime, expected);
+
+    // The console pattern with colors
+    let record = parser.parse("10:11 \x1b[33mWARN \x1b[0m Low disk space").unwrap();
+    assert_eq!(record.level, LogLevel::Warn);
+    assert_eq!(record.message, "Low disk space");
+    assert_eq!((record.datetime.hour(), record.datetime.minute()), (10, 11));
+
+    assert!(parser.parse("Starting the node...").is_none());
+    assert_eq!(parser.parse_or_plain("Starting the node...").level, LogLevel::Info);
+}
+
+#[test]
+fn test_tor_lines() {
+    let parser = TorParser::default();
+    let record = parser
+        .parse("Jul 12 10:11:12.345 [notice] Bootstrapped 100% (done): Done")
+        .unwrap();
+    assert_eq!(record.level, LogLevel::Info);
+    assert_eq!(record.message, "Bootstrapped 100% (done): Done");
+    assert_eq!((record.datetime.hour(), record.datetime.second()), (10, 12));
+    let record = parser
+        .parse("Jul  2 10:11:12.345 [err] Could not bind to 0.0.0.0:9050")
+        .unwrap();
+    assert_eq!(record.level, LogLevel::Error);
+    assert_eq!(
+        parser.parse("Jul 12 10:11:12.345 [warn] Clock skew").unwrap().level,
+        LogLevel::Warn
+    );
+}
+
+#[test]
+fn test_xmrig_lines() {
+    let parser = XmrigParser::default();
+    let record = parser
+        .parse(
+            "[2023-07-12 10:11:12.345]  \x1b[1;31mnet      \x1b[0m\x1b[31mconnect error: \"connection refused\"\x1b[0m",
+        )
+        .unwrap();
+    assert_eq!(record.level, LogLevel::Error);
+    assert_eq!(record.message, "net      connect error: \"connection refused\"");
+    let record = parser
+        .parse("[2023-07-12 10:11:12.345]  \x1b[1;36mcpu      \x1b[0muse profile rx")
+        .unwrap();
+    assert_eq!(record.level, LogLevel::Info);
+    assert_eq!(record.message, "cpu      use profile rx");
+}
+
+#[test]
+fn test_logfmt_lines() {
+    let parser = LogfmtParser;
+    let record = parser
+        .parse(r#"level=error ts=2023-07-12T10:11:12.345Z caller=main.go:1 msg="push failed" err="connection \"refused\"""#)
+        .unwrap();
+    assert_eq!(record.level, LogLevel::Error);
+    assert_eq!(record.message, r#"push failed: connection "refused""#);
+    let record = parser
+        .parse(r#"logger=settings t=2023-07-12T10:11:12+00:00 level=warn msg="Config is deprecated""#)
+        .unwrap();
+    assert_eq!(record.level, LogLevel::Warn);
+    assert_eq!(record.message, "Config is deprecated");
+    assert!(parser.parse("plain text").is_none());
+}
+
+#[test]
+fn test_plain_lines() {
+    let record = PlainParser.parse("\x1b[32mready\x1b[0m").unwrap();
+    assert_eq!(record.level, LogLevel::Info);
+    assert_eq!(record.message, "ready");
+}
@@ -0,0 +1,170 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+use async_trait::async_trait;
+use tari_sdm::{
+    image::checker::{AllOf, AnyOf, GrpcHealth, HttpStatus, LogMatch, Probe, ProbeStatus, TcpPort},
+    runtime::PortMapping,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+    time::{sleep, Duration},
+};
+
+struct Fixed(ProbeStatus);
+
+#[async_trait]
+impl Probe for Fixed {
+    async fn check(&mut self, _ports: &[PortMapping]) -> ProbeStatus {
+        self.0.clone()
+    }
+}
+
+fn failed(reason: &str) -> Fixed {
+    Fixed(ProbeStatus::Failed(reason.into()))
+}
+
+/// A port nobody listens on.
+async fn closed_port() -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    listener.local_addr().unwrap().port()
+}
+
+/// Responds to every request with the status.
+async fn http_server(status: &'static str) -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let mut buf = [0; 1024];
+            // The request is not parsed
+            let Ok(_len) = stream.read(&mut buf).await else {
+                continue;
+            };
+            let response = format!("HTTP/1.1 {}\r\nContent-Length: 0\r\n\r\n", status);
+            stream.write_all(response.as_bytes()).await.ok();
+        }
+    });
+    port
+}
+
+#[tokio::test]
+async fn test_tcp_port() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let host_port = listener.local_addr().unwrap().port();
+    // The container port is published on another host port
+    let ports = vec![PortMapping::new(host_port, 18_142)];
+    assert_eq!(TcpPort::new(18_142).check(&ports).await, ProbeStatus::Ready);
+    drop(listener);
+    assert_eq!(TcpPort::new(18_142).check(&ports).await, ProbeStatus::Waiting);
+}
+
+#[tokio::test]
+async fn test_http_status() {
+    let port = http_server("200 OK").await;
+    assert_eq!(HttpStatus::new(port, "/ready").check(&[]).await, ProbeStatus::Ready);
+
+    let port = http_server("503 Service Unavailable").await;
+    let mut probe = HttpStatus::new(port, "/ready");
+    assert_eq!(probe.check(&[]).await, ProbeStatus::Waiting);
+    let mut probe = HttpStatus::new(port, "/ready").expect(200..=503);
+    assert_eq!(probe.check(&[]).await, ProbeStatus::Ready);
+
+    let port = closed_port().await;
+    assert_eq!(HttpStatus::new(port, "/").check(&[]).await, ProbeStatus::Waiting);
+}
+
+#[tokio::test]
+async fn test_grpc_health_without_server() {
+    let port = closed_port().await;
+    let mut probe = GrpcHealth::new(port).service("tari.rpc.BaseNode");
+    assert_eq!(probe.check(&[]).await, ProbeStatus::Waiting);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_log_match() {
+    let mut probe = LogMatch::new(r"Listening on \S+").timeout(Duration::from_secs(10));
+    probe.on_log_event("Starting...");
+    assert_eq!(probe.check(&[]).await, ProbeStatus::Waiting);
+    probe.on_log_event("Listening on 0.0.0.0:18081");
+    assert_eq!(probe.check(&[]).await, ProbeStatus::Ready);
+    // The match is kept after the timeout
+    sleep(Duration::from_secs(11)).await;
+    assert_eq!(probe.check(&[]).await, ProbeStatus::Ready);
+
+    let mut probe = LogMatch::new("Listening").timeout(Duration::from_secs(10));
+    sleep(Duration::from_secs(11)).await;
+    assert!(matches!(probe.check(&[]).await, ProbeStatus::Failed(_)));
+}
+
+#[tokio::test]
+async fn test_log_match_colored_line() {
+    // XMRig highlights the keywords when it writes to a terminal
+    let line = "[2023-07-01 12:00:00.123]  \x1b[1;36mcpu      \x1b[0m\x1b[1;32mREADY\x1b[0m threads \x1b[1;36m4/4 \
+                (4)\x1b[0m huge pages \x1b[1;31m0% 0/4\x1b[0m memory \x1b[1;30m8192 KB\x1b[0m \x1b[1;30m(12 ms)\x1b[0m";
+    let mut probe = LogMatch::new(r"READY threads");
+    probe.on_log_event(line);
+    assert_eq!(probe.check(&[]).await, ProbeStatus::Ready);
+}
+
+#[tokio::test]
+async fn test_all_of() {
+    let mut probe = AllOf::new()
+        .with(Fixed(ProbeStatus::Ready))
+        .with(Fixed(ProbeStatus::Ready));
+    assert_eq!(probe.check(&[]).await, ProbeStatus::Ready);
+
+    let mut probe = AllOf::new()
+        .with(Fixed(ProbeStatus::Ready))
+        .with(Fixed(ProbeStatus::Waiting));
+    assert_eq!(probe.check(&[]).await, ProbeStatus::Waiting);
+
+    let mut probe = AllOf::new().with(Fixed(ProbeStatus::Waiting)).with(failed("down"));
+    assert_eq!(probe.check(&[]).await, ProbeStatus::Failed("down".into()));
+
+    // Log lines are passed to all the probes
+    let mut probe = AllOf::new().with(LogMatch::new("first")).with(LogMatch::new("second"));
+    probe.on_log_event("first");
+    assert_eq!(probe.check(&[]).await, ProbeStatus::Waiting);
+    probe.on_log_event("second");
+    assert_eq!(probe.check(&[]).await, ProbeStatus::Ready);
+}
+
+#[tokio::test]
+async fn test_any_of() {
+    let mut probe = AnyOf::new().with(failed("down")).with(Fixed(ProbeStatus::Ready));
+    assert_eq!(probe.check(&[]).await, ProbeStatus::Ready);
+
+    let mut probe = AnyOf::new().with(failed("down")).with(Fixed(ProbeStatus::Waiting));
+    assert_eq!(probe.check(&[]).await, ProbeStatus::Waiting);
+
+    let mut probe = AnyOf::new().with(failed("first")).with(failed("second"));
+    assert_eq!(probe.check(&[]).await, ProbeStatus::Failed("first; second".into()));
+
+    let port = closed_port().await;
+    let mut probe = AnyOf::new().with(TcpPort::new(port)).with(LogMatch::new("Ready"));
+    assert_eq!(probe.check(&[]).await, ProbeStatus::Waiting);
+    probe.on_log_event("Ready");
+    assert_eq!(probe.check(&[]).await, ProbeStatus::Ready);
+}
@@ -0,0 +1,1056 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//
+
+mod common;
+
+use std::cell::RefCell;
+
+use anyhow::Error;
+use common::{
+    Harness, Miner, Net, Node, TestConfig, Vol, MINER_CONTAINER, MINER_IMAGE, NODE_CONTAINER, NODE_DIGEST, NODE_IMAGE,
+};
+use futures::{StreamExt, TryStreamExt};
+use tari_launchpad_protocol::container::{ExecOutput, LogLevel, TaskId, TaskStatus};
+use tari_sdm::{
+    ids::ManagedTask,
+    runtime::{
+        fake::{FakeRuntime, Fault},
+        ContainerRuntime, ContainerSpec, ContainerState, Labels, MountSpec, NetworkSpec, PortMapping, RegistryAuth,
+        ResourceKind, DEFAULT_REGISTRY_HOST,
+    },
+};
+use tokio::{
+    io::AsyncWriteExt,
+    time::{timeout, Duration, Instant},
+};
+
+fn pulled() -> FakeRuntime {
+    let runtime = FakeRuntime::new();
+    runtime.add_image(NODE_IMAGE);
+    runtime.set_image_digest(NODE_IMAGE, NODE_DIGEST);
+    runtime.add_image(MINER_IMAGE);
+    runtime
+}
+
+fn dangling(runtime: &FakeRuntime, state: ContainerState) {
+    let spec = ContainerSpec {
+        image: NODE_IMAGE.to_string(),
+        ..Default::default()
+    };
+    runtime.add_container(NODE_CONTAINER, spec, state);
+}
+
+fn position(journal: &[String], call: &str) -> Option<usize> {
+    journal.iter().position(|item| item == call)
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_pull_and_start() -> Result<(), Error> {
+    let mut harness = Harness::setup(FakeRuntime::new())?;
+    harness.activate(true)?;
+    harness.wait_status(&Node::id(), TaskStatus::is_active).await?;
+    harness.wait_status(&Miner::id(), TaskStatus::is_active).await?;
+
+    let runtime = &harness.runtime;
+    assert!(runtime.has_image(NODE_IMAGE));
+    assert!(runtime.has_network("test_net"));
+    assert!(runtime.has_volume("test_vol"));
+    assert_eq!(runtime.container_state(NODE_CONTAINER).await, ContainerState::Running);
+
+    let spec = runtime.container_spec(NODE_CONTAINER).unwrap();
+    let mut grpc = PortMapping::new(18_142, 18_142);
+    grpc.localhost();
+    let mut udp = PortMapping::new(19_000, 9_000);
+    udp.udp().relocatable();
+    assert_eq!(spec.ports, vec![grpc, udp]);
+    assert!(spec.ports[0].host_ip.is_loopback());
+    assert!(spec.ports[1].host_ip.is_unspecified());
+    assert_eq!(spec.networks, vec![("test_net".to_string(), "node".to_string())]);
+    assert_eq!(spec.mounts, vec![MountSpec::Volume {
+        source: "test_vol".to_string(),
+        target: "/data".to_string(),
+    }]);
+
+    let journal = runtime.journal();
+    let pulled = position(&journal, "pull_image registry/node:latest").unwrap();
+    let created = position(&journal, "create_container test_node").unwrap();
+    let miner_created = position(&journal, "create_container test_miner").unwrap();
+    assert!(pulled < created);
+    assert!(created < miner_created);
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_resource_limits_are_applied() -> Result<(), Error> {
+    let mut harness = Harness::setup(FakeRuntime::new())?;
+    harness.activate(true)?;
+    harness.wait_status(&Node::id(), TaskStatus::is_active).await?;
+
+    let spec = harness.runtime.container_spec(NODE_CONTAINER).unwrap();
+    assert_eq!(spec.resources.nano_cpus, Some(1_500_000_000));
+    assert_eq!(spec.resources.memory, Some(512 * 1024 * 1024));
+    assert_eq!(spec.resources.pids_limit, Some(64));
+    assert_eq!(spec.resources.cpuset_cpus, None);
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_pull_progress_is_aggregated() -> Result<(), Error> {
+    let mut harness = Harness::setup(FakeRuntime::new())?;
+    harness.activate(true)?;
+    let history = RefCell::new(Vec::new());
+    harness
+        .wait_status(&Node::id(), |status| {
+            if let TaskStatus::Progress(progress) = status {
+                if let Some(transfer) = &progress.transfer {
+                    history.borrow_mut().push((progress.pct, transfer.clone()));
+                }
+            }
+            status.is_active()
+        })
+        .await?;
+
+    let history = history.into_inner();
+    assert!(history.len() > 1);
+    assert!(history.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+    let (pct, last) = history.last().unwrap();
+    assert_eq!(*pct, 100);
+    assert_eq!((last.current, last.total), (400, 400));
+    assert!(history.iter().any(|(_, transfer)| transfer.speed.is_some()));
+    assert!(history.iter().any(|(_, transfer)| transfer.eta.is_some()));
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_pull_failure_blocks_dependants() -> Result<(), Error> {
+    let runtime = FakeRuntime::new();
+    runtime.add_image(MINER_IMAGE);
+    runtime.inject(Fault::PullImage {
+        image: NODE_IMAGE.to_string(),
+        reason: "manifest unknown".to_string(),
+    });
+    let mut harness = Harness::setup(runtime)?;
+    harness.activate(true)?;
+    harness
+        .wait_status(&Node::id(), |status| {
+            *status == TaskStatus::Failed("manifest unknown".to_string())
+        })
+        .await?;
+    harness.idle(Duration::from_secs(10)).await?;
+
+    let miner = harness.state(&Miner::id()).unwrap();
+    assert!(!miner.status.is_active());
+    assert_eq!(
+        position(&harness.runtime.journal(), "create_container test_miner"),
+        None
+    );
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_clean_dangling_running_container() -> Result<(), Error> {
+    let runtime = pulled();
+    dangling(&runtime, ContainerState::Running);
+    let mut harness = Harness::setup(runtime)?;
+    harness.activate(true)?;
+    harness.wait_status(&Node::id(), TaskStatus::is_active).await?;
+
+    let journal = harness.runtime.journal();
+    let stopped = position(&journal, "stop_container test_node").unwrap();
+    let created = position(&journal, "create_container test_node").unwrap();
+    assert!(stopped < created);
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_clean_dangling_paused_container() -> Result<(), Error> {
+    let runtime = pulled();
+    dangling(&runtime, ContainerState::Paused);
+    let mut harness = Harness::setup(runtime)?;
+    harness.activate(true)?;
+    harness.wait_status(&Node::id(), TaskStatus::is_active).await?;
+
+    let journal = harness.runtime.journal();
+    let unpaused = position(&journal, "unpause_container test_node").unwrap();
+    let created = position(&journal, "create_container test_node").unwrap();
+    assert!(unpaused < created);
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_clean_dangling_exited_container() -> Result<(), Error> {
+    let runtime = pulled();
+    dangling(&runtime, ContainerState::Exited);
+    let mut harness = Harness::setup(runtime)?;
+    harness.activate(true)?;
+    harness.wait_status(&Node::id(), TaskStatus::is_active).await?;
+
+    let journal = harness.runtime.journal();
+    let removed = position(&journal, "remove_container test_node").unwrap();
+    let created = position(&journal, "create_container test_node").unwrap();
+    assert!(removed < created);
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_start_failure_is_reported() -> Result<(), Error> {
+    let runtime = pulled();
+    runtime.inject(Fault::StartContainer {
+        name: NODE_CONTAINER.to_string(),
+        reason: "port is already allocated".to_string(),
+    });
+    let mut harness = Harness::setup(runtime)?;
+    harness.activate(true)?;
+    harness.wait_status(&Node::id(), TaskStatus::is_active).await?;
+
+    let node = harness.state(&Node::id()).unwrap();
+    assert!(node
+        .fails
+        .iter()
+        .any(|record| record.message == "port is already allocated"));
+    let journal = harness.runtime.journal();
+    let starts = journal
+        .iter()
+        .filter(|item| *item == "start_container test_node")
+        .count();
+    assert_eq!(starts, 2);
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_deactivation() -> Result<(), Error> {
+    let mut harness = Harness::setup(pulled())?;
+    harness.activate(true)?;
+    harness.wait_status(&Miner::id(), TaskStatus::is_active).await?;
+    harness.activate(false)?;
+    harness.wait_status(&Node::id(), TaskStatus::is_inactive).await?;
+    harness.wait_status(&Miner::id(), TaskStatus::is_inactive).await?;
+
+    let runtime = &harness.runtime;
+    assert_eq!(runtime.container_state(NODE_CONTAINER).await, ContainerState::NotFound);
+    assert_eq!(runtime.container_state(MINER_CONTAINER).await, ContainerState::NotFound);
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_restart_on_failure() -> Result<(), Error> {
+    let mut harness = Harness::setup(pulled())?;
+    harness.activate(true)?;
+    for attempt in 1..=2 {
+        harness.wait_status(&Node::id(), TaskStatus::is_active).await?;
+        harness.runtime.die(NODE_CONTAINER, 1);
+        harness
+            .wait_status(&Node::id(), |status| {
+                matches!(status, TaskStatus::Restarting { attempt: a, max_attempts: Some(2), .. } if *a == attempt)
+            })
+            .await?;
+    }
+    harness.wait_status(&Node::id(), TaskStatus::is_active).await?;
+    harness.runtime.die(NODE_CONTAINER, 1);
+    harness.wait_status(&Node::id(), TaskStatus::is_failed).await?;
+    harness.idle(Duration::from_secs(120)).await?;
+
+    let node = harness.state(&Node::id()).unwrap();
+    assert_eq!(
+        node.status,
+        TaskStatus::Failed("Container exited with code 1".to_string())
+    );
+    assert_eq!(
+        harness.runtime.container_state(NODE_CONTAINER).await,
+        ContainerState::NotFound
+    );
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_clean_exit_is_not_restarted() -> Result<(), Error> {
+    let mut harness = Harness::setup(pulled())?;
+    harness.activate(true)?;
+    harness.wait_status(&Miner::id(), TaskStatus::is_active).await?;
+    harness.runtime.die(MINER_CONTAINER, 0);
+    harness.wait_status(&Miner::id(), TaskStatus::is_failed).await?;
+    harness.idle(Duration::from_secs(120)).await?;
+    assert_eq!(
+        harness.runtime.container_state(MINER_CONTAINER).await,
+        ContainerState::NotFound
+    );
+
+    // Toggling the session brings it back
+    harness.activate(false)?;
+    harness.wait_status(&Miner::id(), TaskStatus::is_inactive).await?;
+    harness.activate(true)?;
+    harness.wait_status(&Miner::id(), TaskStatus::is_active).await?;
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_create_timeout() -> Result<(), Error> {
+    let runtime = pulled();
+    runtime.mute_events(NODE_CONTAINER);
+    let mut harness = Harness::setup(runtime)?;
+    harness.activate(true)?;
+    harness
+        .wait_status(&Node::id(), |status| {
+            matches!(status, TaskStatus::Restarting { attempt: 1, .. })
+        })
+        .await?;
+    harness.wait_status(&Node::id(), TaskStatus::is_failed).await?;
+
+    let node = harness.state(&Node::id()).unwrap();
+    let timeouts = node
+        .fails
+        .iter()
+        .filter(|record| record.message == "Creating the container timed out after 30s")
+        .count();
+    assert_eq!(timeouts, 3);
+    let journal = harness.runtime.journal();
+    let removals = journal
+        .iter()
+        .filter(|item| *item == "remove_container test_node")
+        .count();
+    assert_eq!(removals, 3);
+    assert_eq!(position(&journal, "start_container test_node"), None);
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_port_conflict_is_reported() -> Result<(), Error> {
+    let runtime = pulled();
+    runtime.occupy_port(18_142, "tari_base_node (pid 42)");
+    let mut harness = Harness::setup(runtime)?;
+    harness.activate(true)?;
+    harness.wait_status(&Node::id(), TaskStatus::is_failed).await?;
+    harness.idle(Duration::from_secs(120)).await?;
+
+    let reason = "Port 18142/tcp on 127.0.0.1 is already in use by tari_base_node (pid 42)";
+    let node = harness.state(&Node::id()).unwrap();
+    assert_eq!(node.status, TaskStatus::Failed(reason.to_string()));
+    assert!(node.fails.iter().any(|record| record.message == reason));
+    assert_eq!(position(&harness.runtime.journal(), "create_container test_node"), None);
+
+    // Starts when the port is released
+    harness.runtime.release_port(18_142);
+    harness.activate(false)?;
+    harness.wait_status(&Node::id(), TaskStatus::is_inactive).await?;
+    harness.activate(true)?;
+    harness.wait_status(&Node::id(), TaskStatus::is_active).await?;
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_relocatable_port() -> Result<(), Error> {
+    let runtime = pulled();
+    runtime.occupy_port(19_000, "dnsmasq");
+    let mut harness = Harness::setup(runtime)?;
+    harness.activate(true)?;
+    harness.wait_status(&Node::id(), TaskStatus::is_active).await?;
+
+    let spec = harness.runtime.container_spec(NODE_CONTAINER).unwrap();
+    let udp = spec.ports.iter().find(|port| port.container_port == 9_000).unwrap();
+    assert_eq!(udp.host_port, 49_152);
+    assert_eq!(harness.ports.get(&Node::id()), Some(&spec.ports));
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_stop_in_reverse_order() -> Result<(), Error> {
+    let mut harness = Harness::setup(pulled())?;
+    harness.activate(true)?;
+    harness.wait_status(&Miner::id(), TaskStatus::is_active).await?;
+    let stop = harness.scope.stop();
+    timeout(Duration::from_secs(120), stop).await??;
+
+    let runtime = harness.runtime.clone();
+    assert_eq!(runtime.container_state(NODE_CONTAINER).await, ContainerState::NotFound);
+    assert_eq!(runtime.container_state(MINER_CONTAINER).await, ContainerState::NotFound);
+    let journal = runtime.journal();
+    let miner = position(&journal, "stop_container test_miner").unwrap();
+    let node = position(&journal, "stop_container test_node").unwrap();
+    let network = position(&journal, "remove_network test_net").unwrap();
+    assert!(miner < node);
+    assert!(node < network);
+    assert_eq!(position(&journal, "kill_container test_node"), None);
+
+    // The stopped scope ignores the config
+    harness.activate(true)?;
+    harness.idle(Duration::from_secs(30)).await?;
+    assert_eq!(runtime.container_state(NODE_CONTAINER).await, ContainerState::NotFound);
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_stop_escalates_to_kill() -> Result<(), Error> {
+    let mut harness = Harness::setup(pulled())?;
+    harness.activate(true)?;
+    harness.wait_status(&Miner::id(), TaskStatus::is_active).await?;
+    harness.runtime.inject(Fault::StopContainer {
+        name: NODE_CONTAINER.to_string(),
+    });
+    let started = Instant::now();
+    let stop = harness.scope.stop();
+    timeout(Duration::from_secs(120), stop).await??;
+
+    // The default grace period is 10 seconds
+    assert!(started.elapsed() >= Duration::from_secs(10));
+    assert_eq!(
+        harness.runtime.container_state(NODE_CONTAINER).await,
+        ContainerState::NotFound
+    );
+    let journal = harness.runtime.journal();
+    let stopped = position(&journal, "stop_container test_node").unwrap();
+    let killed = position(&journal, "kill_container test_node").unwrap();
+    assert!(stopped < killed);
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_image_digest_is_recorded() -> Result<(), Error> {
+    let mut harness = Harness::setup(pulled())?;
+    harness.activate(true)?;
+    harness.wait_status(&Miner::id(), TaskStatus::is_active).await?;
+
+    let node = harness.state(&Node::id()).unwrap();
+    let record = node.image.as_ref().unwrap();
+    assert_eq!(record.image, NODE_IMAGE);
+    assert_eq!(record.digest.as_deref(), Some(NODE_DIGEST));
+    assert!(!record.is_drifted());
+    assert!(node.fails.iter().next().is_none());
+    let miner = harness.state(&Miner::id()).unwrap();
+    let record = miner.image.as_ref().unwrap();
+    assert_eq!(record.pinned, None);
+    assert!(!record.is_drifted());
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_image_drift_is_reported() -> Result<(), Error> {
+    let runtime = pulled();
+    runtime.set_image_digest(NODE_IMAGE, "sha256:7f3a");
+    let mut harness = Harness::setup(runtime)?;
+    harness.activate(true)?;
+    harness.wait_status(&Node::id(), TaskStatus::is_active).await?;
+
+    let node = harness.state(&Node::id()).unwrap();
+    let record = node.image.as_ref().unwrap();
+    assert_eq!(record.digest.as_deref(), Some("sha256:7f3a"));
+    assert!(record.is_drifted());
+    let message = format!(
+        "The image {} has the digest sha256:7f3a, but it's pinned to {}",
+        NODE_IMAGE, NODE_DIGEST
+    );
+    assert!(node.fails.iter().any(|record| record.message == message));
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_image_change_recreates_container() -> Result<(), Error> {
+    let runtime = pulled();
+    let mut harness = Harness::setup(runtime.clone())?;
+    harness.activate(true)?;
+    harness.wait_status(&Miner::id(), TaskStatus::is_active).await?;
+
+    let image = "registry/miner:v2";
+    harness.configure(TestConfig {
+        miner_tag: Some("v2".to_string()),
+        ..Default::default()
+    })?;
+    harness
+        .wait_for(&Miner::id(), |state| {
+            state.status.is_active() && state.image.as_ref().is_some_and(|record| record.image == image)
+        })
+        .await?;
+
+    assert!(runtime.has_image(image));
+    let spec = runtime.container_spec(MINER_CONTAINER).unwrap();
+    assert_eq!(spec.image, image);
+    let journal = runtime.journal();
+    let stopped = position(&journal, "stop_container test_miner").unwrap();
+    let pulled = position(&journal, &format!("pull_image {}", image)).unwrap();
+    assert!(stopped < pulled);
+    // The node is not affected
+    assert!(position(&journal, "stop_container test_node").is_none());
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_pull_with_credentials() -> Result<(), Error> {
+    let runtime = FakeRuntime::new();
+    runtime.add_image(NODE_IMAGE);
+    let credentials = RegistryAuth {
+        username: Some("user".to_string()),
+        password: Some("secret".to_string()),
+        ..Default::default()
+    };
+    runtime.require_auth(DEFAULT_REGISTRY_HOST, credentials.clone());
+    let mut harness = Harness::setup(runtime.clone())?;
+    harness.activate(true)?;
+    harness
+        .wait_status(
+            &Miner::id(),
+            |status| matches!(status, TaskStatus::Unauthorized { registry, .. } if registry == DEFAULT_REGISTRY_HOST),
+        )
+        .await?;
+    assert!(!runtime.has_image(MINER_IMAGE));
+    let miner = harness.state(&Miner::id()).unwrap();
+    assert!(miner.fails.iter().next().is_some());
+
+    // New credentials are applied without a restart of the app
+    harness.configure(TestConfig {
+        miner_credentials: Some(credentials),
+        ..Default::default()
+    })?;
+    harness.wait_status(&Miner::id(), TaskStatus::is_active).await?;
+    assert!(runtime.has_image(MINER_IMAGE));
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_error_logs_are_reported() -> Result<(), Error> {
+    let runtime = pulled();
+    let mut harness = Harness::setup(runtime.clone())?;
+    harness.activate(true)?;
+    harness.wait_status(&Node::id(), TaskStatus::is_active).await?;
+
+    runtime.push_log(
+        NODE_CONTAINER,
+        "2023-07-12 10:11:12.345 [c::bn::sync] WARN  Peer is slow",
+    );
+    runtime.push_log(
+        NODE_CONTAINER,
+        "2023-07-12 10:11:13.001 [c::bn::db] ERROR Database is corrupted",
+    );
+    harness
+        .wait_for(&Node::id(), |state| {
+            state
+                .fails
+                .iter()
+                .any(|record| record.message == "Database is corrupted")
+        })
+        .await?;
+
+    let node = harness.state(&Node::id()).unwrap();
+    let levels: Vec<_> = node.tail.iter().map(|record| record.level).collect();
+    assert!(levels.ends_with(&[LogLevel::Warn, LogLevel::Error]));
+    assert_eq!(
+        node.fails
+            .iter()
+            .filter(|record| record.message == "Peer is slow")
+            .count(),
+        0
+    );
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_logs_are_resumed_without_duplicates() -> Result<(), Error> {
+    let runtime = pulled();
+    let mut harness = Harness::setup(runtime.clone())?;
+    harness.activate(true)?;
+    harness.wait_status(&Node::id(), TaskStatus::is_active).await?;
+
+    runtime.push_log(NODE_CONTAINER, "first");
+    runtime.push_log(NODE_CONTAINER, "second");
+    harness
+        .wait_for(&Node::id(), |state| {
+            state.tail.iter().any(|record| record.message == "second")
+        })
+        .await?;
+    // The lines of the same second are replayed after the reconnect
+    runtime.disconnect_logs(NODE_CONTAINER);
+    runtime.push_log(NODE_CONTAINER, "third");
+    harness
+        .wait_for(&Node::id(), |state| {
+            state.tail.iter().any(|record| record.message == "third")
+        })
+        .await?;
+    harness.idle(Duration::from_secs(3)).await?;
+
+    let node = harness.state(&Node::id()).unwrap();
+    let messages: Vec<_> = node.tail.iter().map(|record| record.message.as_str()).collect();
+    assert_eq!(messages, vec!["first", "second", "third"]);
+    let subscriptions = runtime
+        .journal()
+        .iter()
+        .filter(|call| *call == "logs test_node")
+        .count();
+    assert_eq!(subscriptions, 2);
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_task_waits_for_readiness_probe() -> Result<(), Error> {
+    let runtime = pulled();
+    let mut harness = Harness::setup(runtime.clone())?;
+    harness.configure(TestConfig {
+        miner_ready_line: Some("Mining started".into()),
+        ..Default::default()
+    })?;
+    harness.wait_status(&Miner::id(), TaskStatus::is_started).await?;
+    harness.idle(Duration::from_secs(3)).await?;
+    assert!(!harness.state(&Miner::id()).unwrap().status.is_active());
+
+    runtime.push_log(MINER_CONTAINER, "Mining started with 4 threads");
+    harness.wait_status(&Miner::id(), TaskStatus::is_active).await?;
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_failed_readiness_probe_stops_the_task() -> Result<(), Error> {
+    let runtime = pulled();
+    let mut harness = Harness::setup(runtime.clone())?;
+    harness.configure(TestConfig {
+        miner_ready_line: Some("Mining started".into()),
+        ..Default::default()
+    })?;
+    harness.wait_status(&Miner::id(), TaskStatus::is_failed).await?;
+    let miner = harness.state(&Miner::id()).unwrap();
+    assert!(miner
+        .fails
+        .iter()
+        .any(|record| record.message.starts_with("No log line matched `Mining started`")));
+    // The reason of the checker is the only error
+    assert!(!miner.fails.iter().any(|record| record.message.contains("timed out")));
+    harness.idle(Duration::from_secs(1)).await?;
+    assert!(position(&runtime.journal(), "remove_container test_miner").is_some());
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_exec_streams_output_and_exit_code() -> Result<(), Error> {
+    let runtime = pulled();
+    let mut harness = Harness::setup(runtime.clone())?;
+    harness.activate(true)?;
+    harness.wait_status(&Node::id(), TaskStatus::is_active).await?;
+
+    let output = vec![
+        ExecOutput::Stdout("config\nnextnet\n".into()),
+        ExecOutput::Stderr("ls: peer_db: Permission denied\n".into()),
+    ];
+    runtime.script_exec(NODE_CONTAINER, &["ls", "/blockchain"], output.clone(), 1);
+    let command = vec!["ls".to_string(), "/blockchain".to_string()];
+    let received: Vec<_> = harness.scope.exec(&Node::id(), command)?.try_collect().await?;
+    let mut expected = output;
+    expected.push(ExecOutput::Exited(1));
+    assert_eq!(received, expected);
+
+    let command = vec!["uptime".to_string()];
+    let received: Vec<_> = harness.scope.exec(&Node::id(), command)?.try_collect().await?;
+    assert_eq!(received.last(), Some(&ExecOutput::Exited(127)));
+    assert!(position(&runtime.journal(), "exec test_node").is_some());
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_exec_requires_running_container() -> Result<(), Error> {
+    let runtime = pulled();
+    let harness = Harness::setup(runtime.clone())?;
+    let command = vec!["ls".to_string()];
+
+    let mut stream = harness.scope.exec(&Node::id(), command.clone())?;
+    assert!(stream.next().await.unwrap().is_err());
+    let unknown = TaskId::from("unknown");
+    assert!(harness.scope.exec(&unknown, command).is_err());
+    assert!(harness.scope.exec(&Node::id(), Vec::new()).is_err());
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_console_is_attached_and_detached() -> Result<(), Error> {
+    let runtime = pulled();
+    let mut harness = Harness::setup(runtime.clone())?;
+    assert!(harness.scope.attach(&Node::id(), 80, 24).await.is_err());
+    harness.activate(true)?;
+    harness.wait_status(&Node::id(), TaskStatus::is_active).await?;
+
+    let mut attachment = harness.scope.attach(&Node::id(), 80, 24).await?;
+    attachment.input.write_all(b"status\n").await?;
+    attachment.input.flush().await?;
+    runtime.push_log(NODE_CONTAINER, "Height: 42");
+    let output = timeout(Duration::from_secs(1), attachment.output.next()).await?;
+    assert_eq!(output.transpose()?, Some("Height: 42\r\n".to_string()));
+    assert!(position(&runtime.journal(), "resize_tty test_node").is_some());
+
+    // The node keeps running when the console is detached
+    drop(attachment);
+    harness.idle(Duration::from_secs(3)).await?;
+    assert_eq!(runtime.console_input(NODE_CONTAINER), vec!["status"]);
+    assert!(harness.state(&Node::id()).unwrap().status.is_active());
+    assert!(position(&runtime.journal(), "stop_container test_node").is_none());
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_volume_snapshot_is_exported_and_restored() -> Result<(), Error> {
+    let runtime = pulled();
+    let mut harness = Harness::setup(runtime.clone())?;
+    let path = std::env::temp_dir().join(format!("sdm_snapshot_{}.tar.gz", std::process::id()));
+    let export = harness.scope.export_volume(&Node::id(), None, path.clone());
+    assert!(export.await.is_err());
+    harness.activate(true)?;
+    harness.wait_status(&Miner::id(), TaskStatus::is_active).await?;
+    runtime.put_file("test_vol", "nextnet/data.mdb", "chain");
+    runtime.put_file("test_vol", "igor/data.mdb", "other");
+
+    // The dependants are stopped while the volume is read and started again after that
+    let export = harness
+        .scope
+        .export_volume(&Vol::id(), Some("nextnet".into()), path.clone());
+    timeout(Duration::from_secs(120), export).await??;
+    let journal = runtime.journal();
+    let miner = position(&journal, "stop_container test_miner").unwrap();
+    let node = position(&journal, "stop_container test_node").unwrap();
+    let exported = position(&journal, "export_volume test_vol").unwrap();
+    assert!(miner < node);
+    assert!(node < exported);
+    harness.idle(Duration::from_secs(30)).await?;
+    assert_eq!(runtime.container_state(NODE_CONTAINER).await, ContainerState::Running);
+    assert_eq!(runtime.container_state(MINER_CONTAINER).await, ContainerState::Running);
+
+    // Only the network is restored, other files are kept
+    runtime.put_file("test_vol", "nextnet/data.mdb", "broken");
+    runtime.put_file("test_vol", "igor/data.mdb", "changed");
+    let import = harness
+        .scope
+        .import_volume(&Vol::id(), Some("nextnet".into()), path.clone());
+    timeout(Duration::from_secs(120), import).await??;
+    assert_eq!(runtime.file("test_vol", "nextnet/data.mdb").as_deref(), Some("chain"));
+    assert_eq!(runtime.file("test_vol", "igor/data.mdb").as_deref(), Some("changed"));
+
+    // A snapshot of the whole volume replaces it with a fresh one
+    let export = harness.scope.export_volume(&Vol::id(), None, path.clone());
+    timeout(Duration::from_secs(120), export).await??;
+    runtime.put_file("test_vol", "stagenet/data.mdb", "extra");
+    let import = harness.scope.import_volume(&Vol::id(), None, path.clone());
+    timeout(Duration::from_secs(120), import).await??;
+    std::fs::remove_file(&path)?;
+    assert_eq!(runtime.files("test_vol"), vec!["igor/data.mdb", "nextnet/data.mdb"]);
+    harness.idle(Duration::from_secs(30)).await?;
+    assert_eq!(runtime.container_state(NODE_CONTAINER).await, ContainerState::Running);
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_broken_snapshot_keeps_the_volume() -> Result<(), Error> {
+    let runtime = pulled();
+    let mut harness = Harness::setup(runtime.clone())?;
+    harness.activate(true)?;
+    harness.wait_status(&Miner::id(), TaskStatus::is_active).await?;
+    runtime.put_file("test_vol", "nextnet/data.mdb", "chain");
+    let path = std::env::temp_dir().join(format!("sdm_broken_{}.tar.gz", std::process::id()));
+
+    // Directories outside of the volume are rejected
+    let export = harness
+        .scope
+        .export_volume(&Vol::id(), Some("../etc".into()), path.clone());
+    assert!(timeout(Duration::from_secs(120), export).await?.is_err());
+    let import = harness.scope.import_volume(&Vol::id(), Some("/".into()), path.clone());
+    assert!(timeout(Duration::from_secs(120), import).await?.is_err());
+
+    std::fs::write(&path, b"not a snapshot")?;
+    let import = harness.scope.import_volume(&Vol::id(), None, path.clone());
+    let res = timeout(Duration::from_secs(120), import).await?;
+    std::fs::remove_file(&path)?;
+    assert!(res.is_err());
+    assert_eq!(runtime.file("test_vol", "nextnet/data.mdb").as_deref(), Some("chain"));
+    assert!(!runtime.has_volume("test_vol_import"));
+    harness.idle(Duration::from_secs(30)).await?;
+    assert_eq!(runtime.container_state(NODE_CONTAINER).await, ContainerState::Running);
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_released_tasks_follow_the_config() -> Result<(), Error> {
+    let runtime = pulled();
+    let mut harness = Harness::setup(runtime.clone())?;
+    harness.activate(true)?;
+    harness.wait_status(&Miner::id(), TaskStatus::is_active).await?;
+    runtime.put_file("test_vol", "nextnet/data.mdb", "chain");
+    let path = std::env::temp_dir().join(format!("sdm_released_{}.tar.gz", std::process::id()));
+    let export = harness.scope.export_volume(&Vol::id(), None, path.clone());
+
+    // The session is turned off while the tasks are held
+    harness.activate(false)?;
+    timeout(Duration::from_secs(120), export).await??;
+    std::fs::remove_file(&path)?;
+    harness.idle(Duration::from_secs(30)).await?;
+    assert_eq!(runtime.container_state(NODE_CONTAINER).await, ContainerState::NotFound);
+    assert_eq!(runtime.container_state(MINER_CONTAINER).await, ContainerState::NotFound);
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_volume_is_removed_when_unused() -> Result<(), Error> {
+    let runtime = pulled();
+    let mut harness = Harness::setup(runtime.clone())?;
+    harness.activate(true)?;
+    harness.wait_status(&Miner::id(), TaskStatus::is_active).await?;
+    runtime.put_file("test_vol", "nextnet/data.mdb", "chain");
+    let spec = ContainerSpec {
+        image: NODE_IMAGE.to_string(),
+        mounts: vec![MountSpec::Volume {
+            source: "test_vol".to_string(),
+            target: "/data".to_string(),
+        }],
+        ..Default::default()
+    };
+    runtime.add_container("foreign", spec, ContainerState::Exited);
+
+    // Containers of the scope are stopped, but a foreign one keeps the volume
+    let remove = harness.scope.remove_volume(&Vol::id());
+    let err = timeout(Duration::from_secs(120), remove).await?.unwrap_err();
+    assert!(err.to_string().contains("foreign"));
+    assert!(position(&runtime.journal(), "remove_volume test_vol").is_none());
+    harness.idle(Duration::from_secs(30)).await?;
+    assert_eq!(runtime.container_state(NODE_CONTAINER).await, ContainerState::Running);
+
+    // The active volume task creates an empty volume again
+    runtime.remove_container("foreign").await?;
+    let remove = harness.scope.remove_volume(&Vol::id());
+    timeout(Duration::from_secs(120), remove).await??;
+    harness.idle(Duration::from_secs(30)).await?;
+    assert!(runtime.has_volume("test_vol"));
+    assert!(runtime.files("test_vol").is_empty());
+    assert_eq!(runtime.container_state(NODE_CONTAINER).await, ContainerState::Running);
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_volume_directory_is_cleared() -> Result<(), Error> {
+    let runtime = pulled();
+    let mut harness = Harness::setup(runtime.clone())?;
+    harness.activate(true)?;
+    harness.wait_status(&Miner::id(), TaskStatus::is_active).await?;
+    runtime.put_file("test_vol", "nextnet/data.mdb", "chain");
+    runtime.put_file("test_vol", "nextnet/peer_db/peers", "peers");
+    runtime.put_file("test_vol", "igor/data.mdb", "other");
+
+    let clear = harness.scope.clear_volume(&Vol::id(), "../nextnet");
+    assert!(timeout(Duration::from_secs(120), clear).await?.is_err());
+    let clear = harness.scope.clear_volume(&Vol::id(), "nextnet");
+    timeout(Duration::from_secs(120), clear).await??;
+    assert_eq!(runtime.files("test_vol"), vec!["igor/data.mdb"]);
+    let journal = runtime.journal();
+    let node = position(&journal, "stop_container test_node").unwrap();
+    let cleared = position(&journal, "clear_volume test_vol").unwrap();
+    assert!(node < cleared);
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_volume_disk_usage_is_reported() -> Result<(), Error> {
+    let runtime = pulled();
+    let mut harness = Harness::setup(runtime.clone())?;
+    harness.activate(true)?;
+    harness
+        .wait_for(&Vol::id(), |state| state.disk_usage == Some(0))
+        .await?;
+    runtime.put_file("test_vol", "nextnet/data.mdb", "chain");
+    harness
+        .wait_for(&Vol::id(), |state| state.disk_usage == Some(5))
+        .await?;
+
+    // The data is kept when the volume is deactivated
+    harness.activate(false)?;
+    harness.wait_status(&Vol::id(), |status| !status.is_active()).await?;
+    assert_eq!(runtime.file("test_vol", "nextnet/data.mdb").as_deref(), Some("chain"));
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_slow_disk_usage_does_not_block_the_volume() -> Result<(), Error> {
+    let runtime = pulled();
+    runtime.set_usage_delay(Duration::from_secs(100));
+    let mut harness = Harness::setup(runtime.clone())?;
+    harness.activate(true)?;
+    harness.wait_status(&Node::id(), TaskStatus::is_active).await?;
+
+    // The volume is created again while the usage is still being calculated
+    let remove = harness.scope.remove_volume(&Vol::id());
+    timeout(Duration::from_secs(120), remove).await??;
+    harness.idle(Duration::from_secs(30)).await?;
+    assert!(runtime.has_volume("test_vol"));
+    assert_eq!(runtime.container_state(NODE_CONTAINER).await, ContainerState::Running);
+    harness
+        .wait_for(&Vol::id(), |state| state.disk_usage == Some(0))
+        .await?;
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_network_is_recreated_when_spec_changes() -> Result<(), Error> {
+    let runtime = pulled();
+    let mut harness = Harness::setup(runtime.clone())?;
+    harness.activate(true)?;
+    harness.wait_status(&Node::id(), TaskStatus::is_active).await?;
+    assert_eq!(runtime.network_spec("test_net"), Some(NetworkSpec::default()));
+
+    let mut spec = NetworkSpec::default();
+    spec.internal().subnet("172.30.0.0/16").gateway("172.30.0.1");
+    spec.ipv6().subnet("fd00:1::/64");
+    let config = TestConfig {
+        network: Some(spec.clone()),
+        ..Default::default()
+    };
+    harness.configure(config)?;
+    harness.wait_status(&Node::id(), |status| !status.is_active()).await?;
+    harness.wait_status(&Node::id(), TaskStatus::is_active).await?;
+    assert_eq!(runtime.network_spec("test_net"), Some(spec));
+    let journal = runtime.journal();
+    let created = journal.iter().filter(|call| *call == "create_network test_net").count();
+    assert_eq!(created, 2);
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_invalid_network_spec_is_reported() -> Result<(), Error> {
+    let runtime = pulled();
+    let mut harness = Harness::setup(runtime.clone())?;
+    let mut spec = NetworkSpec::default();
+    spec.subnet("172.30.0.0/16").gateway("10.0.0.1");
+    let config = TestConfig {
+        network: Some(spec),
+        ..Default::default()
+    };
+    harness.configure(config)?;
+    harness.wait_status(&Net::id(), TaskStatus::is_failed).await?;
+    assert!(!runtime.has_network("test_net"));
+    assert!(!harness.state(&Node::id()).is_some_and(|state| state.status.is_active()));
+
+    let mut spec = NetworkSpec::default();
+    spec.subnet("172.30.0.0/16").gateway("172.30.0.1");
+    let config = TestConfig {
+        network: Some(spec),
+        ..Default::default()
+    };
+    harness.configure(config)?;
+    harness.wait_status(&Node::id(), TaskStatus::is_active).await?;
+    assert!(runtime.has_network("test_net"));
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_resources_are_labelled() -> Result<(), Error> {
+    let runtime = pulled();
+    let mut harness = Harness::setup(runtime.clone())?;
+    harness.activate(true)?;
+    harness.wait_status(&Miner::id(), TaskStatus::is_active).await?;
+
+    let labels = runtime.labels(ResourceKind::Container, NODE_CONTAINER).unwrap();
+    assert_eq!(labels.scope, "test");
+    assert_eq!(labels.task_id, "Node");
+    assert_eq!(labels.version, env!("CARGO_PKG_VERSION"));
+    let volume = runtime.labels(ResourceKind::Volume, "test_vol").unwrap();
+    assert_eq!(volume.task_id, "Vol");
+    let network = runtime.labels(ResourceKind::Network, "test_net").unwrap();
+    assert_eq!(network.task_id, "Net");
+    let miner = runtime.labels(ResourceKind::Container, MINER_CONTAINER).unwrap();
+    assert_ne!(labels.config_hash, miner.config_hash);
+    Ok(())
+}
+
+#[test]
+fn test_config_hash_is_stable() {
+    // The hash must not change between builds, otherwise all the containers are recreated
+    let labels = Labels::default().with_config(&"launchpad");
+    assert_eq!(labels.config_hash, "6ae3de787e0d2f7f");
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_orphaned_resources_are_removed() -> Result<(), Error> {
+    let runtime = pulled();
+    let orphan = |task_id: &str| Labels::new("test", task_id, "0.0.1");
+    runtime.add_container("test_old_miner", ContainerSpec::default(), ContainerState::Running);
+    runtime.set_labels(ResourceKind::Container, "test_old_miner", orphan("OldMiner"));
+    // A container of a known task, but with a name of an older version
+    runtime.add_container("test_node_v0", ContainerSpec::default(), ContainerState::Exited);
+    runtime.set_labels(ResourceKind::Container, "test_node_v0", orphan("Node"));
+    runtime.create_volume("test_old_vol", &orphan("OldVol")).await?;
+    // Resources of other scopes and foreign ones are kept
+    runtime
+        .create_volume("other_vol", &Labels::new("other", "Vol", "0.0.1"))
+        .await?;
+    runtime
+        .create_network("custom", &NetworkSpec::default(), &Labels::default())
+        .await?;
+
+    let mut harness = Harness::setup(runtime.clone())?;
+    harness.activate(true)?;
+    harness.wait_status(&Node::id(), TaskStatus::is_active).await?;
+    let reconcile = harness.scope.reconcile();
+    let reconciliation = timeout(Duration::from_secs(120), reconcile).await??;
+    assert_eq!(reconciliation.removed, vec!["test_node_v0", "test_old_miner"]);
+    assert_eq!(
+        runtime.container_state("test_old_miner").await,
+        ContainerState::NotFound
+    );
+    // The data of volumes is removed on request only
+    assert_eq!(reconciliation.orphaned_volumes, vec!["test_old_vol"]);
+    assert!(runtime.has_volume("test_old_vol"));
+    let remove = harness.scope.remove_orphaned_volumes();
+    let removed = timeout(Duration::from_secs(120), remove).await??;
+    assert_eq!(removed, vec!["test_old_vol"]);
+    assert!(!runtime.has_volume("test_old_vol"));
+    assert!(runtime.has_volume("other_vol"));
+    assert!(runtime.has_network("custom"));
+    assert_eq!(runtime.container_state(NODE_CONTAINER).await, ContainerState::Running);
+    assert!(runtime.has_volume("test_vol"));
+    assert!(runtime.has_network("test_net"));
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_scopes_run_side_by_side() -> Result<(), Error> {
+    let runtime = pulled();
+    let mut test = Harness::setup(runtime.clone())?;
+    let mut main = Harness::setup_scope(runtime.clone(), "main", 100)?;
+    test.activate(true)?;
+    main.activate(true)?;
+    test.wait_status(&Node::id(), TaskStatus::is_active).await?;
+    main.wait_status(&Node::id(), TaskStatus::is_active).await?;
+
+    let grpc = |name: &str| {
+        let spec = runtime.container_spec(name).unwrap();
+        spec.ports
+            .iter()
+            .find(|port| port.container_port == 18_142)
+            .unwrap()
+            .host_port
+    };
+    assert_eq!(grpc(NODE_CONTAINER), 18_142);
+    assert_eq!(grpc("main_node"), 18_242);
+    assert!(runtime.has_volume("test_vol"));
+    assert!(runtime.has_volume("main_vol"));
+    assert!(runtime.has_network("main_net"));
+
+    // Resources of the other scope are not orphans
+    let reconcile = main.scope.reconcile();
+    let reconciliation = timeout(Duration::from_secs(120), reconcile).await??;
+    assert!(reconciliation.removed.is_empty());
+    assert!(reconciliation.orphaned_volumes.is_empty());
+
+    main.activate(false)?;
+    main.wait_status(&Node::id(), TaskStatus::is_inactive).await?;
+    assert_eq!(runtime.container_state("main_node").await, ContainerState::NotFound);
+    assert_eq!(runtime.container_state(NODE_CONTAINER).await, ContainerState::Running);
+    Ok(())
+}
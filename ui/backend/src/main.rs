@@ -23,14 +23,12 @@
 
 #![cfg_attr(all(not(debug_assertions), target_os = "windows"), windows_subsystem = "windows")]
 
-use std::{env, thread, time::Duration};
+use std::env;
 
 use anyhow::{Context, Error};
-use tari_launchpad_protocol::{
-    launchpad::{Action, LaunchpadAction},
-    session::LaunchpadSession,
-};
+use tari_launchpad_protocol::launchpad::{Action, LaunchpadAction};
 use tari_sdm_assets::configurator::Configurator;
+use tari_sdm_launchpad::tauri::SHUTDOWN_TIMEOUT;
 use tauri::Manager;
 use tokio::sync::mpsc::UnboundedSender;
 
@@ -62,16 +60,22 @@ fn main() -> Result<(), Error> {
 
     app.run(|app_handle, event| {
         if let tauri::RunEvent::ExitRequested { api, .. } = event {
+            // The bus exits the app when all the containers are stopped
             let bus_requester = app_handle.state::<UnboundedSender<Action>>();
-            let mut new_session = LaunchpadSession::default();
-            new_session.stop_all();
-            bus_requester
-                .send(Action::Action(LaunchpadAction::ChangeSession(new_session)))
-                .unwrap();
-
+            if let Err(err) = bus_requester.send(Action::Action(LaunchpadAction::Shutdown)) {
+                log::error!("Can't stop the containers, the bus has gone: {}", err);
+                return;
+            }
             api.prevent_exit();
-            thread::sleep(Duration::from_secs(3));
-            app_handle.exit(0);
+            let app_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                tokio::time::sleep(SHUTDOWN_TIMEOUT).await;
+                log::warn!(
+                    "The containers are not stopped in {:?}, exiting anyway",
+                    SHUTDOWN_TIMEOUT
+                );
+                app_handle.exit(0);
+            });
         }
     });
     Ok(())
@@ -11,6 +11,7 @@ import SettingsDialog from './containers/SettingsContainer/SettingsDialog';
 import DockerWarning from './containers/DockerWarning/DockerWarning';
 import MiningScheduleDialog from './containers/MiningContainer/MiningSchedule/MiningScheduleDialog';
 import { useShallow } from 'zustand/react/shallow';
+import { AppState, NetworkState } from './store/types';
 
 const containerIdToKey: { [key: string]: string | undefined} = {
   'Tor': 'tor',
@@ -173,7 +174,9 @@ function App() {
                 const lastSystemCpu = newContainers[key].stats?.system_cpu_usage;
                 newContainers[key].stats = delta.StatsRecord;
 
-                const cpuUsagePercent = ((delta.StatsRecord.cpu_usage - lastCpu) / (delta.StatsRecord.system_cpu_usage - lastSystemCpu)) * 100;
+                // 100% is a fully loaded CPU, like `docker stats` shows
+                const cpus = Math.max(delta.StatsRecord.online_cpus || 1, 1);
+                const cpuUsagePercent = ((delta.StatsRecord.cpu_usage - lastCpu) / (delta.StatsRecord.system_cpu_usage - lastSystemCpu)) * cpus * 100;
                 newContainers[key].stats.cpu = !isNaN(cpuUsagePercent) ? cpuUsagePercent : 0;
                 setContainers(newContainers);
               }
@@ -183,6 +186,10 @@ function App() {
               console.log('Unknown delta: ' + JSON.stringify(delta));
             }
           }
+          if (payload?.Delta.Network) {
+            const { network, delta } = payload?.Delta.Network;
+            setAppState(applyNetworkDelta(appState, network, delta));
+          }
         }
       }))();
 
@@ -191,6 +198,74 @@ function App() {
     };
   });
 
+  // The same as `LaunchpadState::apply` does for the networks that aren't selected
+  function applyNetworkDelta(state: AppState, network: string, delta: any): AppState {
+    if (delta.AddError) {
+      const errors = { ...state.errors, data: [...state.errors.data, delta.AddError] };
+      return { ...state, errors };
+    }
+    const networks = { ...(state.networks || {}) };
+    const networkState: NetworkState = {
+      session: {
+        all_active: false,
+        base_layer_active: false,
+        base_node_active: false,
+        grafana_active: false,
+        loki_active: false,
+        merge_layer_active: false,
+        mm_proxy_active: false,
+        sha3x_layer_active: false,
+        xmrig_active: false,
+      },
+      containers: {},
+      node: {
+        chain_height: 0,
+        identity: {},
+        peer_count: 0,
+        sync_status: '',
+      },
+      ...networks[network],
+    };
+    if (delta.UpdateSession) {
+      networkState.session = delta.UpdateSession;
+    }
+    if (delta.TaskAdded) {
+      const { id, state: taskState } = delta.TaskAdded;
+      networkState.containers = { ...networkState.containers, [id]: taskState };
+    }
+    if (delta.TaskDelta) {
+      const { id, delta: taskDelta } = delta.TaskDelta;
+      const container = networkState.containers[id];
+      if (container && taskDelta.UpdateStatus) {
+        networkState.containers = {
+          ...networkState.containers,
+          [id]: { ...container, status: taskDelta.UpdateStatus },
+        };
+      }
+    }
+    if (delta.NodeDelta) {
+      networkState.node = { ...networkState.node, ...nodeChange(delta.NodeDelta) };
+    }
+    networks[network] = networkState;
+    return { ...state, networks };
+  }
+
+  function nodeChange(delta: any) {
+    if (delta.SetChainLength !== undefined) {
+      return { chain_height: delta.SetChainLength };
+    }
+    if (delta.SetPeerCount !== undefined) {
+      return { peer_count: delta.SetPeerCount };
+    }
+    if (delta.SetSyncStatus !== undefined) {
+      return { sync_status: delta.SetSyncStatus };
+    }
+    if (delta.SetIdentity !== undefined) {
+      return { identity: delta.SetIdentity };
+    }
+    return {};
+  }
+
   function printStatus(status: any) {
     if (status === undefined) {
       return '...';
@@ -200,6 +275,16 @@ function App() {
       return status.Progress.stage;
     }
 
+    if (status.hasOwnProperty('Restarting')) {
+      const { attempt, max_attempts } = status.Restarting;
+      return 'Restarting (attempt ' + attempt + (max_attempts ? '/' + max_attempts : '') + ')';
+    }
+
+    if (status.hasOwnProperty('Unauthorized')) {
+      setOpenDockerWarning(true);
+      return 'Login to ' + status.Unauthorized.registry + ' failed';
+    }
+
     if (status.hasOwnProperty('Failed')) {
       setOpenDockerWarning(true);
       return 'Failed:' + status.Failed;
@@ -51,6 +51,7 @@ const useAppStateStore = create<AppStateStore>((set, get) => ({
       peer_count: 0,
       sync_status: '',
     },
+    networks: {},
   },
   setAppState: (newState) => set({ appState: newState }),
 
@@ -51,11 +51,19 @@ export interface NodeState {
   sync_status: string;
 }
 
+// The state of a network that runs besides the selected one
+export interface NetworkState {
+  session: ConfigState['session'];
+  containers: ContainerState;
+  node: NodeState;
+}
+
 export interface AppState {
   config: ConfigState;
   containers: ContainerState;
   errors: ErrorState;
   node: NodeState;
+  networks: { [network: string]: NetworkState };
 }
 
 export type MiningType = 'Sha' | 'Merge' | 'Monero' | 'All';